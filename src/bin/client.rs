@@ -6,14 +6,25 @@ extern crate raft;
 extern crate tokio;
 extern crate tokio_codec;
 
-use clap::{App, Arg, SubCommand};
+use clap::{App, Arg, ArgMatches, SubCommand};
 use kv_raft::client::Client;
+use kv_raft::public::{request, response, Response};
 use tokio::prelude::*;
 
 fn main() {
     let matches = App::new("Client")
         .arg(Arg::with_name("host").short("h").takes_value(true))
-        .subcommand(SubCommand::with_name("get").arg(Arg::with_name("KEY").takes_value(true)))
+        .arg(
+            Arg::with_name("cluster")
+                .long("cluster")
+                .takes_value(true)
+                .help("Refuse a server that belongs to any other cluster"),
+        )
+        .subcommand(
+            SubCommand::with_name("get")
+                .arg(Arg::with_name("KEY").takes_value(true))
+                .args(&consistency_args()),
+        )
         .subcommand(SubCommand::with_name("delete").arg(Arg::with_name("KEY").takes_value(true)))
         .subcommand(
             SubCommand::with_name("set")
@@ -24,6 +35,11 @@ fn main() {
             SubCommand::with_name("add_node")
                 .arg(Arg::with_name("ID").takes_value(true))
                 .arg(Arg::with_name("ADDR").takes_value(true))
+                .arg(
+                    Arg::with_name("PUBLIC_ADDR")
+                        .takes_value(true)
+                        .help("The address the node serves clients on"),
+                )
                 .arg(
                     Arg::with_name("learner")
                         .long("learner")
@@ -33,7 +49,40 @@ fn main() {
         .subcommand(
             SubCommand::with_name("remove_node").arg(Arg::with_name("ID").takes_value(true)),
         )
-        .subcommand(SubCommand::with_name("scan"))
+        .subcommand(
+            SubCommand::with_name("promote_node").arg(Arg::with_name("ID").takes_value(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("transfer_leader").arg(Arg::with_name("ID").takes_value(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("update_peer_address")
+                .arg(Arg::with_name("ID").takes_value(true))
+                .arg(Arg::with_name("ADDR").takes_value(true))
+                .arg(
+                    Arg::with_name("PUBLIC_ADDR")
+                        .takes_value(true)
+                        .help("The address the node serves clients on"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("scan")
+                .arg(Arg::with_name("start").long("start").takes_value(true))
+                .arg(Arg::with_name("end").long("end").takes_value(true))
+                .arg(Arg::with_name("prefix").long("prefix").takes_value(true))
+                .arg(Arg::with_name("limit").long("limit").takes_value(true))
+                .arg(
+                    Arg::with_name("reverse")
+                        .long("reverse")
+                        .help("Scan from the last key to the first"),
+                )
+                .arg(
+                    Arg::with_name("values")
+                        .long("values")
+                        .help("Print the values along with the keys"),
+                )
+                .args(&consistency_args()),
+        )
         .subcommand(SubCommand::with_name("info"))
         .subcommand(SubCommand::with_name("ping"))
         .subcommand(SubCommand::with_name("bench"))
@@ -45,18 +94,21 @@ fn main() {
         .parse()
         .unwrap();
 
-    let task = Client::connect(&addr)
-        .map_err(|e| println!("err while connecting: {:?}", e))
+    let cluster = matches.value_of("cluster").unwrap_or("").to_string();
+    let task = Client::connect_to_cluster(&addr, &cluster)
+        .map_err(|e| println!("err while connecting: {}", e))
         .and_then(move |client| {
             match matches.subcommand() {
                 ("get", Some(sub)) => {
                     let key = sub.value_of("KEY").unwrap();
+                    let (consistency, max_lag) = consistency(sub);
                     ::tokio::spawn(
                         client
-                            .get(&key)
+                            .get_with(&key, consistency, max_lag)
                             .map_err(|e| println!("err while setting: {:?}", e))
                             .and_then(move |(_, resp)| {
                                 let resp = resp.expect("Response missing");
+                                exit_on_error(&resp);
 
                                 if resp.get_get().get_is_found() {
                                     println!("{}", resp.get_get().get_value());
@@ -77,6 +129,7 @@ fn main() {
                             .map_err(|e| println!("err while setting: {:?}", e))
                             .and_then(move |(_, resp)| {
                                 let resp = resp.expect("Response missing");
+                                exit_on_error(&resp);
                                 if !resp.get_success() {
                                     println!("Value not set");
                                     ::std::process::exit(1);
@@ -88,13 +141,15 @@ fn main() {
                 ("add_node", Some(sub)) => {
                     let id = sub.value_of("ID").unwrap().parse().unwrap();
                     let addr = sub.value_of("ADDR").unwrap();
+                    let public_addr = sub.value_of("PUBLIC_ADDR").unwrap_or("");
                     let is_learner = sub.is_present("learner");
                     ::tokio::spawn(
                         client
-                            .add_node(id, addr.to_string(), is_learner)
+                            .add_node(id, addr.to_string(), public_addr.to_string(), is_learner)
                             .map_err(|e| println!("err while setting: {:?}", e))
                             .and_then(move |(_, resp)| {
                                 let resp = resp.expect("Response missing");
+                                exit_on_error(&resp);
                                 if !resp.get_success() {
                                     println!("Node failed to add");
                                     ::std::process::exit(1);
@@ -111,6 +166,7 @@ fn main() {
                             .map_err(|e| println!("err while setting: {:?}", e))
                             .and_then(move |(_, resp)| {
                                 let resp = resp.expect("Response missing");
+                                exit_on_error(&resp);
                                 if !resp.get_success() {
                                     println!("Node failed to remove");
                                     ::std::process::exit(1);
@@ -119,6 +175,56 @@ fn main() {
                             }),
                     );
                 }
+                ("promote_node", Some(sub)) => {
+                    let id = sub.value_of("ID").unwrap().parse().unwrap();
+                    ::tokio::spawn(
+                        client
+                            .promote_node(id)
+                            .map_err(|e| println!("err while promoting: {:?}", e))
+                            .and_then(move |(_, resp)| {
+                                let resp = resp.expect("Response missing");
+                                exit_on_error(&resp);
+                                if !resp.get_success() {
+                                    println!("Node failed to promote");
+                                    ::std::process::exit(1);
+                                }
+                                Ok(())
+                            }),
+                    );
+                }
+                ("transfer_leader", Some(sub)) => {
+                    let id = sub.value_of("ID").unwrap().parse().unwrap();
+                    ::tokio::spawn(
+                        client
+                            .transfer_leader(id)
+                            .map_err(|e| println!("err while transferring: {:?}", e))
+                            .and_then(move |(_, resp)| {
+                                let resp = resp.expect("Response missing");
+                                exit_on_error(&resp);
+                                println!("Node {} is the leader", id);
+                                Ok(())
+                            }),
+                    );
+                }
+                ("update_peer_address", Some(sub)) => {
+                    let id = sub.value_of("ID").unwrap().parse().unwrap();
+                    let addr = sub.value_of("ADDR").unwrap().to_string();
+                    let public_addr = sub.value_of("PUBLIC_ADDR").unwrap_or("").to_string();
+                    ::tokio::spawn(
+                        client
+                            .update_peer_address(id, addr, public_addr)
+                            .map_err(|e| println!("err while updating: {:?}", e))
+                            .and_then(move |(_, resp)| {
+                                let resp = resp.expect("Response missing");
+                                exit_on_error(&resp);
+                                if !resp.get_success() {
+                                    println!("Node failed to update");
+                                    ::std::process::exit(1);
+                                }
+                                Ok(())
+                            }),
+                    );
+                }
                 ("delete", Some(sub)) => {
                     let key = sub.value_of("KEY").unwrap();
                     ::tokio::spawn(
@@ -127,6 +233,7 @@ fn main() {
                             .map_err(|e| println!("err while setting: {:?}", e))
                             .and_then(move |(_, resp)| {
                                 let resp = resp.expect("Response missing");
+                                exit_on_error(&resp);
 
                                 if resp.get_delete().get_is_found() {
                                     println!("{}", resp.get_delete().get_value());
@@ -138,16 +245,26 @@ fn main() {
                             }),
                     );
                 }
-                ("scan", Some(_)) => {
+                ("scan", Some(sub)) => {
+                    let mut scan = request::Scan::new();
+                    scan.set_start(sub.value_of("start").unwrap_or("").to_string());
+                    scan.set_end(sub.value_of("end").unwrap_or("").to_string());
+                    scan.set_prefix(sub.value_of("prefix").unwrap_or("").to_string());
+                    scan.set_limit(sub.value_of("limit").unwrap_or("0").parse().unwrap());
+                    scan.set_reverse(sub.is_present("reverse"));
+                    scan.set_include_values(sub.is_present("values"));
+                    let (consistency, max_lag) = consistency(sub);
+                    scan.set_consistency(consistency);
+                    scan.set_max_lag(max_lag);
+
                     ::tokio::spawn(
                         client
-                            .scan()
-                            .map_err(|e| println!("err while setting: {:?}", e))
-                            .and_then(move |(_, resp)| {
-                                let resp = resp.expect("Response missing");
-
-                                for key in resp.get_scan().get_keys() {
-                                    println!("{}", key);
+                            .scan_stream(scan)
+                            .map_err(|e| println!("err while scanning: {:?}", e))
+                            .for_each(|(key, value)| {
+                                match value {
+                                    Some(value) => println!("{}\t{}", key, value),
+                                    None => println!("{}", key),
                                 }
                                 Ok(())
                             }),
@@ -160,12 +277,14 @@ fn main() {
                             .map_err(|e| println!("err while setting: {:?}", e))
                             .and_then(move |(_, resp)| {
                                 let resp = resp.unwrap();
+                                exit_on_error(&resp);
                                 let info = resp.get_info();
                                 println!("id:        {}", info.get_id());
                                 println!("leader_id: {}", info.get_leader_id());
                                 println!("term:      {}", info.get_term());
                                 println!("applied:   {}", info.get_applied());
                                 println!("peers:     {:?}", info.get_peers());
+                                println!("learners:  {:?}", info.get_learners());
                                 Ok(())
                             }),
                     );
@@ -240,3 +359,44 @@ fn main() {
 
     ::tokio::run(task);
 }
+
+fn consistency_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("consistency")
+            .long("consistency")
+            .takes_value(true)
+            .possible_values(&["linearizable", "lease", "stale"])
+            .help("How up to date the read must be"),
+        Arg::with_name("max-lag")
+            .long("max-lag")
+            .takes_value(true)
+            .help("The most entries a stale read may be behind"),
+    ]
+}
+
+fn consistency(sub: &ArgMatches) -> (request::Consistency, u64) {
+    let consistency = match sub.value_of("consistency") {
+        Some("lease") => request::Consistency::LEASE,
+        Some("stale") => request::Consistency::STALE,
+        _ => request::Consistency::LINEARIZABLE,
+    };
+    let max_lag = sub.value_of("max-lag").unwrap_or("0").parse().unwrap();
+    (consistency, max_lag)
+}
+
+fn exit_on_error(resp: &Response) {
+    if !resp.has_error() {
+        return;
+    }
+
+    let error = resp.get_error();
+    match error.get_code() {
+        response::ErrorCode::NOT_LEADER if error.get_leader_id() > 0 => println!(
+            "Not the leader, try node {} at {}",
+            error.get_leader_id(),
+            error.get_leader_addr()
+        ),
+        code => println!("{:?}: {}", code, error.get_message()),
+    }
+    ::std::process::exit(1);
+}
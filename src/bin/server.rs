@@ -1,9 +1,14 @@
 extern crate clap;
+extern crate futures;
 extern crate kv_raft;
+extern crate tokio_signal;
 
-use kv_raft::server::Server;
+use kv_raft::server::{Bootstrap, KeyValueMachine, Server};
 
 use clap::{App, Arg};
+use futures::{Future, Stream};
+use std::net::SocketAddr;
+use tokio_signal::unix::{Signal, SIGINT, SIGTERM};
 
 fn main() {
     let matches = App::new("Example KV")
@@ -14,12 +19,50 @@ fn main() {
                 .short("p")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("advertise")
+                .long("advertise")
+                .takes_value(true)
+                .help(
+                    "The address peers reach this node on, defaults to --peer-on unless that \
+                     listens on every interface",
+                ),
+        )
+        .arg(
+            Arg::with_name("http-on")
+                .long("http-on")
+                .takes_value(true)
+                .help("Also serves the public API as JSON over HTTP on this address"),
+        )
+        .arg(
+            Arg::with_name("redis-on")
+                .long("redis-on")
+                .takes_value(true)
+                .help("Also speaks the Redis protocol on this address"),
+        )
         .arg(
             Arg::with_name("data-file")
                 .long("data-file")
                 .short("f")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("initial-members")
+                .long("initial-members")
+                .takes_value(true)
+                .requires("cluster-token")
+                .help(
+                    "Bootstraps a new cluster of the members, as id=addr/public_addr,... where \
+                     the public address clients are pointed at may be left out",
+                ),
+        )
+        .arg(
+            Arg::with_name("cluster-token")
+                .long("cluster-token")
+                .takes_value(true)
+                .requires("initial-members")
+                .help("Names the cluster to bootstrap"),
+        )
         .get_matches();
 
     let id = matches.value_of("ID").unwrap_or("1").parse().unwrap();
@@ -27,7 +70,87 @@ fn main() {
         .value_of("peer-on")
         .unwrap_or("0.0.0.0:9001")
         .to_string();
+    let advertise = match matches.value_of("advertise") {
+        Some(addr) => addr.to_string(),
+        None if parse_addr(&peer_on).ip().is_unspecified() => {
+            eprintln!(
+                "Peers can't reach {}, pass --advertise with the address they should use",
+                peer_on
+            );
+            ::std::process::exit(1);
+        }
+        None => peer_on.clone(),
+    };
+
+    let bootstrap = matches
+        .value_of("initial-members")
+        .map(|members| Bootstrap {
+            token: matches.value_of("cluster-token").unwrap().to_string(),
+            members: members.split(',').map(parse_member).collect(),
+        });
 
     let file = matches.value_of("data-file").unwrap_or("/data/data");
-    Server::start(id, &file, peer_on).join();
+    let started = KeyValueMachine::open(format!("{}.engine", file))
+        .and_then(|machine| Server::start(id, &file, peer_on, advertise, machine, bootstrap));
+    match started {
+        Ok(mut server) => {
+            if let Some(addr) = matches.value_of("http-on") {
+                if let Err(e) = server.serve_http(&parse_addr(addr)) {
+                    eprintln!("Unable to serve HTTP on {}: {}", addr, e);
+                    server.shutdown();
+                    ::std::process::exit(1);
+                }
+            }
+            if let Some(addr) = matches.value_of("redis-on") {
+                if let Err(e) = server.serve_redis(&parse_addr(addr)) {
+                    eprintln!("Unable to serve Redis on {}: {}", addr, e);
+                    server.shutdown();
+                    ::std::process::exit(1);
+                }
+            }
+            wait_for_signal();
+            println!("Shutting down");
+            server.shutdown();
+        }
+        Err(e) => {
+            eprintln!("Unable to start the server: {}", e);
+            ::std::process::exit(1);
+        }
+    }
+}
+
+/// Blocks until the process is asked to stop with SIGINT or SIGTERM.
+fn wait_for_signal() {
+    let signals = Signal::new(SIGINT)
+        .flatten_stream()
+        .select(Signal::new(SIGTERM).flatten_stream());
+    if let Err((e, _)) = signals.into_future().wait() {
+        eprintln!("Unable to wait for a signal: {}", e);
+    }
+}
+
+fn parse_addr(addr: &str) -> SocketAddr {
+    addr.parse().unwrap_or_else(|_| {
+        eprintln!("Invalid address {:?}, expected ip:port", addr);
+        ::std::process::exit(1);
+    })
+}
+
+/// Parses a member given as `id=addr`, or `id=addr/public_addr`.
+fn parse_member(member: &str) -> (u64, String, String) {
+    let mut parts = member.splitn(2, '=');
+    let id = parts.next().and_then(|id| id.trim().parse().ok());
+    let mut addrs = parts.next().unwrap_or("").splitn(2, '/');
+    let addr = addrs.next().map_or("", str::trim).to_string();
+    let public_addr = addrs.next().map_or("", str::trim).to_string();
+    match id {
+        Some(id) if !addr.is_empty() => (id, addr, public_addr),
+        _ => {
+            eprintln!(
+                "Invalid member {:?}, expected id=addr or id=addr/public_addr",
+                member
+            );
+            ::std::process::exit(1);
+        }
+    }
 }
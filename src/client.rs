@@ -1,7 +1,11 @@
 use codec::{self, Proto};
-use futures::Future;
-use public::{self, Request, Response};
+use futures::sync::{mpsc, oneshot};
+use futures::{stream, Future};
+use public::{self, request, Request, Response};
+use std::collections::HashMap;
+use std::io;
 use std::net::SocketAddr;
+use tokio;
 use tokio::io::{AsyncRead, ReadHalf, WriteHalf};
 use tokio::net::TcpStream;
 use tokio::prelude::*;
@@ -9,23 +13,67 @@ use tokio_codec::{FramedRead, FramedWrite};
 
 /// The client with-which to access the key-value database. The client
 /// is a consuming struct that provides access back to itself in the future.
-/// Unless there's an error, then you should reconnect.
+/// A response of `None` means the connection has closed, and you should
+/// reconnect, unless the request was too large to send at all.
+///
+/// Clones of a client share its connection, and each can have a request in
+/// flight at the same time. The server may answer them in any order, so
+/// every request carries an id that its response is matched by.
+#[derive(Clone)]
 pub struct Client {
-    sink: FramedWrite<WriteHalf<TcpStream>, Proto<Request>>,
-    stream: FramedRead<ReadHalf<TcpStream>, Proto<Response>>,
+    requests: mpsc::UnboundedSender<(Request, oneshot::Sender<Response>)>,
 }
 
 type ClientResponse = (Client, Option<Response>);
 
 impl Client {
+    /// Connects to a server. The connection is driven by a task of its own,
+    /// so this must be called on a tokio runtime. The task finishes once
+    /// every clone of the client has been dropped and their requests have
+    /// been answered.
     pub fn connect(addr: &SocketAddr) -> impl Future<Item = Client, Error = ::std::io::Error> {
-        TcpStream::connect(&addr).map(move |sock| {
-            let (stream, sink) = sock.split();
+        Client::connect_to_cluster(addr, "")
+    }
+
+    /// Connects to a server, refusing it unless it belongs to `cluster` or
+    /// doesn't know its cluster. An empty `cluster` accepts any server.
+    ///
+    /// Either side fails the connection when the other speaks a protocol
+    /// version it doesn't, with an error saying so.
+    pub fn connect_to_cluster(
+        addr: &SocketAddr,
+        cluster: &str,
+    ) -> impl Future<Item = Client, Error = ::std::io::Error> {
+        let handshake = public::handshake_request(cluster);
+        let cluster = cluster.to_string();
 
+        TcpStream::connect(&addr).and_then(move |sock| {
+            let (stream, sink) = sock.split();
             let sink = FramedWrite::new(sink, Proto::<Request>::new());
             let stream = FramedRead::new(stream, Proto::<Response>::new());
 
-            Client { sink, stream }
+            sink.send(handshake)
+                .and_then(|sink| {
+                    stream
+                        .into_future()
+                        .map(|(response, stream)| (sink, stream, response))
+                        .map_err(|(e, _)| e)
+                })
+                .map_err(into_io)
+                .and_then(move |(sink, stream, response)| {
+                    check_server(response, &cluster)?;
+
+                    let (requests, rx) = mpsc::unbounded();
+                    tokio::spawn(Connection {
+                        requests: rx,
+                        sink,
+                        stream,
+                        unsent: None,
+                        pending: HashMap::new(),
+                        next_id: 0,
+                    });
+                    Ok(Client { requests })
+                })
         })
     }
 
@@ -33,6 +81,17 @@ impl Client {
         self.send(public::get_request(&key))
     }
 
+    /// Gets the key with the given consistency, where `max_lag` bounds how
+    /// far behind a stale read may be.
+    pub fn get_with(
+        self,
+        key: &str,
+        consistency: request::Consistency,
+        max_lag: u64,
+    ) -> impl Future<Item = ClientResponse, Error = codec::Error> {
+        self.send(public::get_request_with(&key, consistency, max_lag))
+    }
+
     pub fn ping(self) -> impl Future<Item = ClientResponse, Error = codec::Error> {
         self.send(public::ping_request())
     }
@@ -49,6 +108,69 @@ impl Client {
         self.send(public::scan_request())
     }
 
+    /// Scans a page of the keys within the bounds of `scan`, see
+    /// `request::Scan`.
+    pub fn range_scan(
+        self,
+        scan: request::Scan,
+    ) -> impl Future<Item = ClientResponse, Error = codec::Error> {
+        self.send(public::range_scan_request(scan))
+    }
+
+    /// Scans the keys within the bounds of `scan` as a stream of each key and,
+    /// when they are asked for, its value. A page is only requested once the
+    /// one before it has been consumed, so the scan is never held in memory
+    /// all at once.
+    pub fn scan_stream(
+        self,
+        scan: request::Scan,
+    ) -> impl Stream<Item = (String, Option<String>), Error = codec::Error> {
+        let remaining = scan.get_limit();
+
+        stream::unfold(Some((self, scan, remaining)), |state| {
+            let (client, mut scan, remaining) = match state {
+                Some(state) => state,
+                None => return None,
+            };
+
+            let page = client
+                .range_scan(scan.clone())
+                .and_then(move |(client, response)| -> Result<_, codec::Error> {
+                    let mut response = response.ok_or_else(|| {
+                        codec::Error::Io(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "Connection closed during a scan",
+                        ))
+                    })?;
+                    let mut page = response.take_scan();
+
+                    let mut values = page.take_values().into_vec().into_iter();
+                    let entries: Vec<(String, Option<String>)> = page
+                        .take_keys()
+                        .into_vec()
+                        .into_iter()
+                        .map(|key| (key, values.next()))
+                        .collect();
+
+                    // A zero limit is no limit, so it never runs out
+                    let remaining = remaining.saturating_sub(entries.len() as u64);
+                    let done = page.get_token().is_empty()
+                        || (scan.get_limit() > 0 && remaining == 0);
+
+                    let next = if done {
+                        None
+                    } else {
+                        scan.set_token(page.take_token());
+                        scan.set_limit(remaining);
+                        Some((client, scan, remaining))
+                    };
+                    Ok((entries, next))
+                });
+            Some(page)
+        }).map(stream::iter_ok::<_, codec::Error>)
+            .flatten()
+    }
+
     pub fn info(self) -> impl Future<Item = ClientResponse, Error = codec::Error> {
         self.send(public::info_request())
     }
@@ -61,24 +183,323 @@ impl Client {
         self,
         id: u64,
         addr: String,
+        public_addr: String,
         is_learner: bool,
     ) -> impl Future<Item = ClientResponse, Error = codec::Error> {
-        self.send(public::add_node_request(id, addr, is_learner))
+        self.send(public::add_node_request(id, addr, public_addr, is_learner))
     }
 
     pub fn remove_node(self, id: u64) -> impl Future<Item = ClientResponse, Error = codec::Error> {
         self.send(public::remove_node_request(id))
     }
 
+    /// Makes a learner added with `add_node` a voter.
+    pub fn promote_node(self, id: u64) -> impl Future<Item = ClientResponse, Error = codec::Error> {
+        self.send(public::promote_node_request(id))
+    }
+
+    /// Asks the leader to hand leadership to `target_id`. The reply comes
+    /// once the target leads, or with an error if it didn't in time.
+    pub fn transfer_leader(
+        self,
+        target_id: u64,
+    ) -> impl Future<Item = ClientResponse, Error = codec::Error> {
+        self.send(public::transfer_leader_request(target_id))
+    }
+
+    /// Moves a member to a new peer address, and public address unless it's
+    /// empty, without removing it from the cluster.
+    pub fn update_peer_address(
+        self,
+        id: u64,
+        addr: String,
+        public_addr: String,
+    ) -> impl Future<Item = ClientResponse, Error = codec::Error> {
+        self.send(public::update_peer_address_request(id, addr, public_addr))
+    }
+
+    /// Sends a write to a custom state machine.
+    pub fn propose(
+        self,
+        data: Vec<u8>,
+    ) -> impl Future<Item = ClientResponse, Error = codec::Error> {
+        self.send(public::propose_request(data))
+    }
+
+    /// Sends a read to a custom state machine.
+    pub fn query(self, data: Vec<u8>) -> impl Future<Item = ClientResponse, Error = codec::Error> {
+        self.send(public::query_request(data))
+    }
+
     fn send(self, request: Request) -> impl Future<Item = ClientResponse, Error = codec::Error> {
-        let sink = self.sink;
-        let stream = self.stream;
-
-        sink.send(request).and_then(|sink| {
-            stream
-                .into_future()
-                .map(|(item, stream)| (Client { sink, stream }, item))
-                .map_err(|(e, _)| e)
-        })
+        // If the connection has closed the reply is dropped along with the
+        // request, and the response is None.
+        let (reply, response) = oneshot::channel();
+        let _ = self.requests.unbounded_send((request, reply));
+        response.then(move |response| Ok((self, response.ok())))
+    }
+}
+
+/// Checks the server's answer to the handshake.
+fn check_server(response: Option<Response>, cluster: &str) -> io::Result<()> {
+    let refused = |reason: String| Err(io::Error::new(io::ErrorKind::InvalidData, reason));
+
+    let response = match response {
+        Some(response) => response,
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the server closed the connection during the handshake",
+            ))
+        }
+    };
+
+    if response.has_error() {
+        return refused(format!(
+            "the server refused the client: {}",
+            response.get_error().get_message()
+        ));
+    }
+    if !response.has_handshake() {
+        return refused(String::from(
+            "the server didn't answer the handshake, it may be an older build",
+        ));
+    }
+
+    let handshake = response.get_handshake();
+    public::check_handshake(
+        handshake.get_magic(),
+        handshake.get_version(),
+        handshake.get_min_version(),
+        handshake.get_cluster(),
+        cluster,
+    ).or_else(|reason| refused(format!("refusing the server: {}", reason)))
+}
+
+fn into_io(e: codec::Error) -> io::Error {
+    match e {
+        codec::Error::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)),
+    }
+}
+
+/// Writes the requests of every clone of a client to the server and routes
+/// each response back by its request id.
+struct Connection {
+    requests: mpsc::UnboundedReceiver<(Request, oneshot::Sender<Response>)>,
+    sink: FramedWrite<WriteHalf<TcpStream>, Proto<Request>>,
+    stream: FramedRead<ReadHalf<TcpStream>, Proto<Response>>,
+    /// A request the sink had no room for yet.
+    unsent: Option<Request>,
+    pending: HashMap<u64, oneshot::Sender<Response>>,
+    next_id: u64,
+}
+
+impl Connection {
+    /// Sends what it can, returning whether every client has been dropped.
+    fn send(&mut self) -> Result<bool, codec::Error> {
+        let mut dropped = false;
+        loop {
+            if let Some(request) = self.unsent.take() {
+                let id = request.get_request_id();
+                match self.sink.start_send(request) {
+                    Ok(AsyncSink::Ready) => (),
+                    Ok(AsyncSink::NotReady(request)) => {
+                        self.unsent = Some(request);
+                        break;
+                    }
+                    // Nothing of it was written, so only this request fails
+                    // and its response is None
+                    Err(codec::Error::FrameTooLarge(size, max)) => {
+                        println!(
+                            "Dropping a request of {} bytes, over the {} a server takes",
+                            size, max
+                        );
+                        self.pending.remove(&id);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            match self.requests.poll() {
+                Ok(Async::Ready(Some((mut request, reply)))) => {
+                    // Ids start at one, zero is a request without one
+                    self.next_id += 1;
+                    request.set_request_id(self.next_id);
+                    self.pending.insert(self.next_id, reply);
+                    self.unsent = Some(request);
+                }
+                Ok(Async::NotReady) => break,
+                Ok(Async::Ready(None)) | Err(_) => {
+                    dropped = true;
+                    break;
+                }
+            }
+        }
+        self.sink.poll_complete()?;
+        Ok(dropped && self.unsent.is_none())
+    }
+
+    /// Takes the reply for a request. A server that doesn't echo ids can
+    /// still answer a client with a single request in flight.
+    fn take_pending(&mut self, id: u64) -> Option<oneshot::Sender<Response>> {
+        if id == 0 && self.pending.len() == 1 {
+            let only = *self.pending.keys().next().unwrap();
+            return self.pending.remove(&only);
+        }
+        self.pending.remove(&id)
+    }
+
+    fn drive(&mut self) -> Poll<(), codec::Error> {
+        let dropped = self.send()?;
+
+        while let Async::Ready(response) = self.stream.poll()? {
+            let response = match response {
+                Some(response) => response,
+                // The server closed the connection, dropping what's pending
+                None => return Ok(Async::Ready(())),
+            };
+            if let Some(reply) = self.take_pending(response.get_request_id()) {
+                let _ = reply.send(response);
+            }
+        }
+
+        if dropped && self.pending.is_empty() {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+impl Future for Connection {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        self.drive()
+            .map_err(|e| println!("Error on the connection to the server: {:?}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_replies_are_matched_to_requests() {
+        let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Answers the handshake, then two gets with their keys, the second
+        // one first
+        let server = listener
+            .incoming()
+            .into_future()
+            .map_err(|_| ())
+            .and_then(|(sock, _)| {
+                let (stream, sink) = sock.unwrap().split();
+                let stream = FramedRead::new(stream, Proto::<Request>::new());
+                let sink = FramedWrite::new(sink, Proto::<Response>::new());
+                stream
+                    .into_future()
+                    .map_err(|_| ())
+                    .and_then(move |(handshake, stream)| {
+                        assert!(handshake.unwrap().has_handshake());
+                        sink.send(public::handshake_response("alpha", 1))
+                            .map_err(|_| ())
+                            .map(move |sink| (stream, sink))
+                    })
+                    .and_then(|(stream, sink)| {
+                        stream.take(2).collect().map_err(|_| ()).and_then(move |requests| {
+                            let responses = requests.into_iter().rev().map(|request| {
+                                let key = request.get_get().get_key().to_string();
+                                let mut response = public::get_response(Some(key));
+                                response.set_request_id(request.get_request_id());
+                                response
+                            });
+                            sink.send_all(stream::iter_ok::<_, codec::Error>(responses))
+                                .map(|_| ())
+                                .map_err(|_| ())
+                        })
+                    })
+            });
+
+        let values = future::lazy(move || {
+            tokio::spawn(server);
+            Client::connect(&addr).map_err(|_| ()).and_then(|client| {
+                client
+                    .clone()
+                    .get("a")
+                    .join(client.get("b"))
+                    .map(|((_, a), (_, b))| {
+                        (
+                            a.unwrap().get_get().get_value().to_string(),
+                            b.unwrap().get_get().get_value().to_string(),
+                        )
+                    })
+                    .map_err(|_| ())
+            })
+        });
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        assert_eq!(
+            runtime.block_on(values),
+            Ok((String::from("a"), String::from("b")))
+        );
+    }
+
+    #[test]
+    fn test_a_request_too_large_to_send_fails_alone() {
+        let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Answers the handshake, then the one get that makes it through
+        let server = listener
+            .incoming()
+            .into_future()
+            .map_err(|_| ())
+            .and_then(|(sock, _)| {
+                let (stream, sink) = sock.unwrap().split();
+                let stream = FramedRead::new(stream, Proto::<Request>::new());
+                let sink = FramedWrite::new(sink, Proto::<Response>::new());
+                stream
+                    .into_future()
+                    .map_err(|_| ())
+                    .and_then(move |(_, stream)| {
+                        sink.send(public::handshake_response("alpha", 1))
+                            .map_err(|_| ())
+                            .map(move |sink| (stream, sink))
+                    })
+                    .and_then(|(stream, sink)| {
+                        stream.into_future().map_err(|_| ()).and_then(move |(request, _)| {
+                            let request = request.unwrap();
+                            let key = request.get_get().get_key().to_string();
+                            let mut response = public::get_response(Some(key));
+                            response.set_request_id(request.get_request_id());
+                            sink.send(response).map(|_| ()).map_err(|_| ())
+                        })
+                    })
+            });
+
+        let responses = future::lazy(move || {
+            tokio::spawn(server);
+            Client::connect(&addr).map_err(|_| ()).and_then(|client| {
+                let value = String::from_utf8(vec![b'x'; codec::DEFAULT_MAX_FRAME]).unwrap();
+                client
+                    .clone()
+                    .set("big", &value)
+                    .join(client.get("a"))
+                    .map(|((_, big), (_, a))| {
+                        (big.is_none(), a.unwrap().get_get().get_value().to_string())
+                    })
+                    .map_err(|_| ())
+            })
+        });
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        assert_eq!(runtime.block_on(responses), Ok((true, String::from("a"))));
     }
 }
@@ -3,18 +3,32 @@ use protobuf::{parse_from_bytes, Message, ProtobufError};
 use std::marker::PhantomData;
 use tokio_codec::{Decoder, Encoder};
 
+/// The largest frame a codec takes unless it's given another limit.
+pub const DEFAULT_MAX_FRAME: usize = 16 * 1024 * 1024;
+
+const HEADER: usize = 4;
+
 /// A frame is a tuple of an integer frame for the first sequence
 /// followed by a run of bytes that is the length of the first sequence.
+///
+/// Frames longer than the codec's maximum are refused before any of them
+/// is buffered, so a bogus length can't make a connection hold gigabytes.
 #[derive(Debug)]
 pub struct Proto<T: Message> {
-    len: Option<u32>,
+    max_frame: usize,
     phantom: PhantomData<T>,
 }
 
 impl<T: Message> Proto<T> {
     pub fn new() -> Self {
+        Proto::with_max_frame(DEFAULT_MAX_FRAME)
+    }
+
+    /// A codec for frames of up to `max_frame` bytes, not counting the
+    /// length header.
+    pub fn with_max_frame(max_frame: usize) -> Self {
         Proto {
-            len: None,
+            max_frame,
             phantom: PhantomData,
         }
     }
@@ -23,8 +37,12 @@ impl<T: Message> Proto<T> {
 #[derive(Debug)]
 pub enum Error {
     Io(::std::io::Error),
+    /// A frame's contents didn't decode as the expected message.
     InvalidByteSequence,
     Proto(ProtobufError),
+    /// A frame is longer than the codec's maximum, which is given second.
+    /// The connection can't be read past it, so it should be closed.
+    FrameTooLarge(usize, usize),
 }
 
 impl From<::std::io::Error> for Error {
@@ -44,12 +62,13 @@ impl<T: Message> Decoder for Proto<T> {
     type Error = Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        const HEADER: usize = 4;
-
         // Input must be at least 4 bytes to include content length
         if src.len() >= HEADER {
             // Peek at the length without changing src.
             let content_len = Bytes::from(&src[0..HEADER]).into_buf().get_u32_be() as usize;
+            if content_len > self.max_frame {
+                return Err(Error::FrameTooLarge(content_len, self.max_frame));
+            }
             let frame_len = HEADER + content_len;
 
             if src.len() >= frame_len {
@@ -57,7 +76,7 @@ impl<T: Message> Decoder for Proto<T> {
 
                 return parse_from_bytes::<Self::Item>(&bytes[HEADER..])
                     .map(|p| Some(p))
-                    .map_err(Error::from);
+                    .map_err(|_| Error::InvalidByteSequence);
             }
         }
 
@@ -71,7 +90,11 @@ impl<T: Message> Encoder for Proto<T> {
 
     fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
         let data = item.write_to_bytes()?;
-        dst.reserve(4);
+        // The other side would refuse it, so it's better not to send it
+        if data.len() > self.max_frame || data.len() > u32::max_value() as usize {
+            return Err(Error::FrameTooLarge(data.len(), self.max_frame));
+        }
+        dst.reserve(HEADER + data.len());
         dst.put_u32_be(data.len() as u32);
         dst.extend_from_slice(&data);
         Ok(())
@@ -129,4 +152,32 @@ mod tests {
         Proto::<Request>::new().encode(req, &mut actual).unwrap();
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_oversized_and_malformed_frames() {
+        let mut req = Request::new();
+        req.mut_get().set_key(String::from("hello"));
+        let len = req.compute_size() as usize;
+
+        // Refused from the header alone, before the contents arrive
+        let mut oversized = BytesMut::new();
+        oversized.put_u32_be(len as u32);
+        let mut decoder = Proto::<Request>::with_max_frame(len - 1);
+        match decoder.decode(&mut oversized) {
+            Err(Error::FrameTooLarge(size, max)) => assert_eq!((size, max), (len, len - 1)),
+            other => panic!("expected the frame to be too large, got {:?}", other),
+        }
+        match decoder.encode(req.clone(), &mut BytesMut::new()) {
+            Err(Error::FrameTooLarge(..)) => (),
+            other => panic!("expected the frame to be too large, got {:?}", other),
+        }
+
+        let mut malformed = BytesMut::new();
+        malformed.put_u32_be(2);
+        malformed.put_slice(&[0xff, 0xff]);
+        match Proto::<Request>::new().decode(&mut malformed) {
+            Err(Error::InvalidByteSequence) => (),
+            other => panic!("expected invalid bytes, got {:?}", other),
+        }
+    }
 }
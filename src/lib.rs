@@ -1,8 +1,11 @@
 extern crate bytes;
 extern crate clap;
 extern crate futures;
+extern crate hyper;
 extern crate protobuf;
 extern crate raft;
+#[macro_use]
+extern crate serde_json;
 extern crate tokio;
 extern crate tokio_codec;
 
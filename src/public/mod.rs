@@ -2,6 +2,67 @@ mod proto;
 
 pub use self::proto::*;
 
+/// Opens every handshake, public or between peers.
+pub const MAGIC: &[u8] = b"KVRF";
+/// The newest protocol version this build speaks. A change to the protocol
+/// bumps it, and `MIN_PROTOCOL_VERSION` is only raised once no build still
+/// running speaks anything older, so a cluster can be upgraded node by node.
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// This build's side of the handshake.
+pub fn handshake(cluster: &str, node_id: u64) -> Handshake {
+    let mut handshake = Handshake::new();
+    handshake.set_magic(MAGIC.to_vec());
+    handshake.set_version(PROTOCOL_VERSION);
+    handshake.set_min_version(MIN_PROTOCOL_VERSION);
+    handshake.set_cluster(cluster.to_string());
+    handshake.set_node_id(node_id);
+    handshake
+}
+
+pub fn handshake_request(cluster: &str) -> Request {
+    let mut request = Request::new();
+    request.set_handshake(handshake(cluster, 0));
+    request
+}
+
+pub fn handshake_response(cluster: &str, node_id: u64) -> Response {
+    let mut response = Response::new();
+    response.set_handshake(handshake(cluster, node_id));
+    response
+}
+
+/// Checks the other side's handshake against this build and `cluster`,
+/// returning why it's refused if it is. The versions only need to overlap,
+/// and a cluster is only checked when both sides know theirs.
+pub fn check_handshake(
+    magic: &[u8],
+    version: u32,
+    min_version: u32,
+    their_cluster: &str,
+    cluster: &str,
+) -> Result<(), String> {
+    if magic != MAGIC {
+        return Err(String::from(
+            "the other side doesn't speak the kv-raft protocol",
+        ));
+    }
+    if min_version > PROTOCOL_VERSION || version < MIN_PROTOCOL_VERSION {
+        return Err(format!(
+            "the other side speaks protocol versions {} to {}, this build speaks {} to {}",
+            min_version, version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+        ));
+    }
+    if !their_cluster.is_empty() && !cluster.is_empty() && their_cluster != cluster {
+        return Err(format!(
+            "the other side belongs to cluster {:?}, not {:?}",
+            their_cluster, cluster
+        ));
+    }
+    Ok(())
+}
+
 pub fn get_request(key: &str) -> Request {
     let mut request = Request::new();
     let mut get = request::Get::new();
@@ -10,6 +71,15 @@ pub fn get_request(key: &str) -> Request {
     request
 }
 
+/// A get that's answered with the given consistency, see
+/// `request::Consistency`.
+pub fn get_request_with(key: &str, consistency: request::Consistency, max_lag: u64) -> Request {
+    let mut request = get_request(key);
+    request.mut_get().set_consistency(consistency);
+    request.mut_get().set_max_lag(max_lag);
+    request
+}
+
 pub fn get_response(value: Option<String>) -> Response {
     let mut response = Response::new();
     let mut get = response::Get::new();
@@ -32,6 +102,20 @@ pub fn set_request(key: &str, value: &str) -> Request {
     request
 }
 
+/// Sets every pair of key and value as one write.
+pub fn multi_set_request(pairs: &[(&str, &str)]) -> Request {
+    let mut request = Request::new();
+    let mut multi_set = request::MultiSet::new();
+    for &(key, value) in pairs {
+        let mut set = request::Set::new();
+        set.set_key(key.to_string());
+        set.set_value(value.to_string());
+        multi_set.mut_sets().push(set);
+    }
+    request.set_multi_set(multi_set);
+    request
+}
+
 pub fn delete_request(key: &str) -> Request {
     let mut request = Request::new();
     let mut delete = request::Delete::new();
@@ -59,6 +143,13 @@ pub fn scan_request() -> Request {
     request
 }
 
+/// A scan limited by the bounds and options in `scan`.
+pub fn range_scan_request(scan: request::Scan) -> Request {
+    let mut request = Request::new();
+    request.set_scan(scan);
+    request
+}
+
 pub fn info_request() -> Request {
     let mut request = Request::new();
     request.set_info(request::Info::new());
@@ -73,6 +164,26 @@ pub fn scan_response(v: Vec<String>) -> Response {
     response
 }
 
+pub fn scan_entries_response(
+    entries: Vec<(String, String)>,
+    include_values: bool,
+    token: Option<String>,
+) -> Response {
+    let mut response = Response::new();
+    let mut scan = response::Scan::new();
+    if let Some(token) = token {
+        scan.set_token(token);
+    }
+    for (key, value) in entries {
+        scan.mut_keys().push(key);
+        if include_values {
+            scan.mut_values().push(value);
+        }
+    }
+    response.set_scan(scan);
+    response
+}
+
 pub fn ping_request() -> Request {
     let mut request = Request::new();
     request.set_ping(true);
@@ -85,11 +196,12 @@ pub fn ping_response() -> Response {
     response
 }
 
-pub fn add_node_request(id: u64, addr: String, is_learner: bool) -> Request {
+pub fn add_node_request(id: u64, addr: String, public_addr: String, is_learner: bool) -> Request {
     let mut request = Request::new();
     let mut add_node = request::AddNode::new();
     add_node.set_id(id);
     add_node.set_addr(addr);
+    add_node.set_public_addr(public_addr);
     add_node.set_is_learner(is_learner);
     request.set_add_node(add_node);
     request
@@ -103,6 +215,50 @@ pub fn remove_node_request(id: u64) -> Request {
     request
 }
 
+pub fn promote_node_request(id: u64) -> Request {
+    let mut request = Request::new();
+    let mut promote_node = request::PromoteNode::new();
+    promote_node.set_id(id);
+    request.set_promote_node(promote_node);
+    request
+}
+
+pub fn transfer_leader_request(target_id: u64) -> Request {
+    let mut request = Request::new();
+    let mut transfer_leader = request::TransferLeader::new();
+    transfer_leader.set_target_id(target_id);
+    request.set_transfer_leader(transfer_leader);
+    request
+}
+
+pub fn update_peer_address_request(id: u64, addr: String, public_addr: String) -> Request {
+    let mut request = Request::new();
+    let mut update = request::UpdatePeerAddress::new();
+    update.set_id(id);
+    update.set_addr(addr);
+    update.set_public_addr(public_addr);
+    request.set_update_peer_address(update);
+    request
+}
+
+pub fn propose_request(data: Vec<u8>) -> Request {
+    let mut request = Request::new();
+    request.set_propose(data);
+    request
+}
+
+pub fn query_request(data: Vec<u8>) -> Request {
+    let mut request = Request::new();
+    request.set_query(data);
+    request
+}
+
+pub fn result_response(data: Vec<u8>) -> Response {
+    let mut response = Response::new();
+    response.set_result(data);
+    response
+}
+
 pub fn success_response() -> Response {
     let mut response = Response::new();
     response.set_success(true);
@@ -115,6 +271,27 @@ pub fn failure_response() -> Response {
     response
 }
 
+pub fn error_response(code: response::ErrorCode, message: &str) -> Response {
+    let mut response = Response::new();
+    let mut error = response::Error::new();
+    error.set_code(code);
+    error.set_message(message.to_string());
+    response.set_error(error);
+    response
+}
+
+/// The request must go to the leader, which is `leader_id` at `leader_addr`
+/// if they aren't empty.
+pub fn not_leader_response(leader_id: u64, leader_addr: &str) -> Response {
+    let mut response = error_response(response::ErrorCode::NOT_LEADER, "not the leader");
+    {
+        let error = response.mut_error();
+        error.set_leader_id(leader_id);
+        error.set_leader_addr(leader_addr.to_string());
+    }
+    response
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,4 +352,34 @@ mod tests {
         assert!(!request.has_delete());
         assert!(!request.has_scan());
     }
+
+    #[test]
+    fn test_handshakes() {
+        let check = |handshake: &Handshake, cluster: &str| {
+            check_handshake(
+                handshake.get_magic(),
+                handshake.get_version(),
+                handshake.get_min_version(),
+                handshake.get_cluster(),
+                cluster,
+            )
+        };
+
+        let ours = handshake("alpha", 1);
+        assert!(check(&ours, "alpha").is_ok());
+        assert!(check(&ours, "").is_ok());
+        assert!(check(&handshake("", 0), "alpha").is_ok());
+        assert!(check(&ours, "beta").is_err());
+
+        let mut other = ours.clone();
+        other.set_magic(b"HTTP".to_vec());
+        assert!(check(&other, "alpha").is_err());
+
+        // Only the ranges of versions need to overlap
+        let mut newer = ours.clone();
+        newer.set_version(PROTOCOL_VERSION + 1);
+        assert!(check(&newer, "alpha").is_ok());
+        newer.set_min_version(PROTOCOL_VERSION + 1);
+        assert!(check(&newer, "alpha").is_err());
+    }
 }
@@ -23,6 +23,8 @@ use protobuf::ProtobufEnum as ProtobufEnum_imported_for_functions;
 
 #[derive(PartialEq,Clone,Default)]
 pub struct Request {
+    // message fields
+    pub request_id: u64,
     // message oneof groups
     pub kind: ::std::option::Option<Request_oneof_kind>,
     // special fields
@@ -40,6 +42,13 @@ pub enum Request_oneof_kind {
     add_node(super::request::AddNode),
     remove_node(super::request::RemoveNode),
     info(super::request::Info),
+    propose(::std::vec::Vec<u8>),
+    query(::std::vec::Vec<u8>),
+    promote_node(super::request::PromoteNode),
+    transfer_leader(super::request::TransferLeader),
+    update_peer_address(super::request::UpdatePeerAddress),
+    handshake(Handshake),
+    multi_set(super::request::MultiSet),
 }
 
 impl Request {
@@ -414,6 +423,364 @@ impl Request {
             _ => super::request::Info::default_instance(),
         }
     }
+
+    // bytes propose = 9;
+
+    pub fn clear_propose(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_propose(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::propose(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_propose(&mut self, v: ::std::vec::Vec<u8>) {
+        self.kind = ::std::option::Option::Some(Request_oneof_kind::propose(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_propose(&mut self) -> &mut ::std::vec::Vec<u8> {
+        if let ::std::option::Option::Some(Request_oneof_kind::propose(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(Request_oneof_kind::propose(::std::vec::Vec::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::propose(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_propose(&mut self) -> ::std::vec::Vec<u8> {
+        if self.has_propose() {
+            match self.kind.take() {
+                ::std::option::Option::Some(Request_oneof_kind::propose(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            ::std::vec::Vec::new()
+        }
+    }
+
+    pub fn get_propose(&self) -> &[u8] {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::propose(ref v)) => v,
+            _ => &[],
+        }
+    }
+
+    // bytes query = 10;
+
+    pub fn clear_query(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_query(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::query(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_query(&mut self, v: ::std::vec::Vec<u8>) {
+        self.kind = ::std::option::Option::Some(Request_oneof_kind::query(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_query(&mut self) -> &mut ::std::vec::Vec<u8> {
+        if let ::std::option::Option::Some(Request_oneof_kind::query(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(Request_oneof_kind::query(::std::vec::Vec::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::query(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_query(&mut self) -> ::std::vec::Vec<u8> {
+        if self.has_query() {
+            match self.kind.take() {
+                ::std::option::Option::Some(Request_oneof_kind::query(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            ::std::vec::Vec::new()
+        }
+    }
+
+    pub fn get_query(&self) -> &[u8] {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::query(ref v)) => v,
+            _ => &[],
+        }
+    }
+
+    // .public.request.PromoteNode promote_node = 11;
+
+    pub fn clear_promote_node(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_promote_node(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::promote_node(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_promote_node(&mut self, v: super::request::PromoteNode) {
+        self.kind = ::std::option::Option::Some(Request_oneof_kind::promote_node(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_promote_node(&mut self) -> &mut super::request::PromoteNode {
+        if let ::std::option::Option::Some(Request_oneof_kind::promote_node(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(Request_oneof_kind::promote_node(super::request::PromoteNode::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::promote_node(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_promote_node(&mut self) -> super::request::PromoteNode {
+        if self.has_promote_node() {
+            match self.kind.take() {
+                ::std::option::Option::Some(Request_oneof_kind::promote_node(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            super::request::PromoteNode::new()
+        }
+    }
+
+    pub fn get_promote_node(&self) -> &super::request::PromoteNode {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::promote_node(ref v)) => v,
+            _ => super::request::PromoteNode::default_instance(),
+        }
+    }
+
+    // .public.request.TransferLeader transfer_leader = 12;
+
+    pub fn clear_transfer_leader(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_transfer_leader(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::transfer_leader(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_transfer_leader(&mut self, v: super::request::TransferLeader) {
+        self.kind = ::std::option::Option::Some(Request_oneof_kind::transfer_leader(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_transfer_leader(&mut self) -> &mut super::request::TransferLeader {
+        if let ::std::option::Option::Some(Request_oneof_kind::transfer_leader(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(Request_oneof_kind::transfer_leader(super::request::TransferLeader::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::transfer_leader(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_transfer_leader(&mut self) -> super::request::TransferLeader {
+        if self.has_transfer_leader() {
+            match self.kind.take() {
+                ::std::option::Option::Some(Request_oneof_kind::transfer_leader(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            super::request::TransferLeader::new()
+        }
+    }
+
+    pub fn get_transfer_leader(&self) -> &super::request::TransferLeader {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::transfer_leader(ref v)) => v,
+            _ => super::request::TransferLeader::default_instance(),
+        }
+    }
+
+    // .public.request.UpdatePeerAddress update_peer_address = 13;
+
+    pub fn clear_update_peer_address(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_update_peer_address(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::update_peer_address(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_update_peer_address(&mut self, v: super::request::UpdatePeerAddress) {
+        self.kind = ::std::option::Option::Some(Request_oneof_kind::update_peer_address(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_update_peer_address(&mut self) -> &mut super::request::UpdatePeerAddress {
+        if let ::std::option::Option::Some(Request_oneof_kind::update_peer_address(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(Request_oneof_kind::update_peer_address(super::request::UpdatePeerAddress::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::update_peer_address(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_update_peer_address(&mut self) -> super::request::UpdatePeerAddress {
+        if self.has_update_peer_address() {
+            match self.kind.take() {
+                ::std::option::Option::Some(Request_oneof_kind::update_peer_address(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            super::request::UpdatePeerAddress::new()
+        }
+    }
+
+    pub fn get_update_peer_address(&self) -> &super::request::UpdatePeerAddress {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::update_peer_address(ref v)) => v,
+            _ => super::request::UpdatePeerAddress::default_instance(),
+        }
+    }
+
+    // .public.Handshake handshake = 15;
+
+    pub fn clear_handshake(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_handshake(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::handshake(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_handshake(&mut self, v: Handshake) {
+        self.kind = ::std::option::Option::Some(Request_oneof_kind::handshake(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_handshake(&mut self) -> &mut Handshake {
+        if let ::std::option::Option::Some(Request_oneof_kind::handshake(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(Request_oneof_kind::handshake(Handshake::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::handshake(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_handshake(&mut self) -> Handshake {
+        if self.has_handshake() {
+            match self.kind.take() {
+                ::std::option::Option::Some(Request_oneof_kind::handshake(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Handshake::new()
+        }
+    }
+
+    pub fn get_handshake(&self) -> &Handshake {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::handshake(ref v)) => v,
+            _ => Handshake::default_instance(),
+        }
+    }
+
+    // .public.request.MultiSet multi_set = 16;
+
+    pub fn clear_multi_set(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_multi_set(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::multi_set(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_multi_set(&mut self, v: super::request::MultiSet) {
+        self.kind = ::std::option::Option::Some(Request_oneof_kind::multi_set(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_multi_set(&mut self) -> &mut super::request::MultiSet {
+        if let ::std::option::Option::Some(Request_oneof_kind::multi_set(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(Request_oneof_kind::multi_set(super::request::MultiSet::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::multi_set(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_multi_set(&mut self) -> super::request::MultiSet {
+        if self.has_multi_set() {
+            match self.kind.take() {
+                ::std::option::Option::Some(Request_oneof_kind::multi_set(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            super::request::MultiSet::new()
+        }
+    }
+
+    pub fn get_multi_set(&self) -> &super::request::MultiSet {
+        match self.kind {
+            ::std::option::Option::Some(Request_oneof_kind::multi_set(ref v)) => v,
+            _ => super::request::MultiSet::default_instance(),
+        }
+    }
+
+    // uint64 request_id = 14;
+
+    pub fn clear_request_id(&mut self) {
+        self.request_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_request_id(&mut self, v: u64) {
+        self.request_id = v;
+    }
+
+    pub fn get_request_id(&self) -> u64 {
+        self.request_id
+    }
 }
 
 impl ::protobuf::Message for Request {
@@ -453,6 +820,31 @@ impl ::protobuf::Message for Request {
                 return false;
             }
         }
+        if let Some(Request_oneof_kind::promote_node(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Request_oneof_kind::transfer_leader(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Request_oneof_kind::update_peer_address(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Request_oneof_kind::handshake(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Request_oneof_kind::multi_set(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
         true
     }
 
@@ -508,10 +900,59 @@ impl ::protobuf::Message for Request {
                     }
                     self.kind = ::std::option::Option::Some(Request_oneof_kind::info(is.read_message()?));
                 },
-                _ => {
-                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
-                },
-            };
+                9 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(Request_oneof_kind::propose(is.read_bytes()?));
+                },
+                10 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(Request_oneof_kind::query(is.read_bytes()?));
+                },
+                11 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(Request_oneof_kind::promote_node(is.read_message()?));
+                },
+                12 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(Request_oneof_kind::transfer_leader(is.read_message()?));
+                },
+                13 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(Request_oneof_kind::update_peer_address(is.read_message()?));
+                },
+                15 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(Request_oneof_kind::handshake(is.read_message()?));
+                },
+                16 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(Request_oneof_kind::multi_set(is.read_message()?));
+                },
+                14 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.request_id = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
         }
         ::std::result::Result::Ok(())
     }
@@ -520,6 +961,9 @@ impl ::protobuf::Message for Request {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
+        if self.request_id != 0 {
+            my_size += ::protobuf::rt::value_size(14, self.request_id, ::protobuf::wire_format::WireTypeVarint);
+        }
         if let ::std::option::Option::Some(ref v) = self.kind {
             match v {
                 &Request_oneof_kind::get(ref v) => {
@@ -553,6 +997,32 @@ impl ::protobuf::Message for Request {
                     let len = v.compute_size();
                     my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
                 },
+                &Request_oneof_kind::propose(ref v) => {
+                    my_size += ::protobuf::rt::bytes_size(9, &v);
+                },
+                &Request_oneof_kind::query(ref v) => {
+                    my_size += ::protobuf::rt::bytes_size(10, &v);
+                },
+                &Request_oneof_kind::promote_node(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &Request_oneof_kind::transfer_leader(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &Request_oneof_kind::update_peer_address(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &Request_oneof_kind::handshake(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &Request_oneof_kind::multi_set(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 2 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
             };
         }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
@@ -561,47 +1031,457 @@ impl ::protobuf::Message for Request {
     }
 
     fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
-        if let ::std::option::Option::Some(ref v) = self.kind {
-            match v {
-                &Request_oneof_kind::get(ref v) => {
-                    os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-                &Request_oneof_kind::set(ref v) => {
-                    os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-                &Request_oneof_kind::delete(ref v) => {
-                    os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-                &Request_oneof_kind::scan(ref v) => {
-                    os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-                &Request_oneof_kind::ping(v) => {
-                    os.write_bool(5, v)?;
-                },
-                &Request_oneof_kind::add_node(ref v) => {
-                    os.write_tag(6, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-                &Request_oneof_kind::remove_node(ref v) => {
-                    os.write_tag(7, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-                &Request_oneof_kind::info(ref v) => {
-                    os.write_tag(8, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-            };
+        if self.request_id != 0 {
+            os.write_uint64(14, self.request_id)?;
+        }
+        if let ::std::option::Option::Some(ref v) = self.kind {
+            match v {
+                &Request_oneof_kind::get(ref v) => {
+                    os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Request_oneof_kind::set(ref v) => {
+                    os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Request_oneof_kind::delete(ref v) => {
+                    os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Request_oneof_kind::scan(ref v) => {
+                    os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Request_oneof_kind::ping(v) => {
+                    os.write_bool(5, v)?;
+                },
+                &Request_oneof_kind::add_node(ref v) => {
+                    os.write_tag(6, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Request_oneof_kind::remove_node(ref v) => {
+                    os.write_tag(7, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Request_oneof_kind::info(ref v) => {
+                    os.write_tag(8, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Request_oneof_kind::propose(ref v) => {
+                    os.write_bytes(9, v)?;
+                },
+                &Request_oneof_kind::query(ref v) => {
+                    os.write_bytes(10, v)?;
+                },
+                &Request_oneof_kind::promote_node(ref v) => {
+                    os.write_tag(11, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Request_oneof_kind::transfer_leader(ref v) => {
+                    os.write_tag(12, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Request_oneof_kind::update_peer_address(ref v) => {
+                    os.write_tag(13, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Request_oneof_kind::handshake(ref v) => {
+                    os.write_tag(15, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Request_oneof_kind::multi_set(ref v) => {
+                    os.write_tag(16, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+            };
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Request {
+        Request::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::Get>(
+                    "get",
+                    Request::has_get,
+                    Request::get_get,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::Set>(
+                    "set",
+                    Request::has_set,
+                    Request::get_set,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::Delete>(
+                    "delete",
+                    Request::has_delete,
+                    Request::get_delete,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::Scan>(
+                    "scan",
+                    Request::has_scan,
+                    Request::get_scan,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_bool_accessor::<_>(
+                    "ping",
+                    Request::has_ping,
+                    Request::get_ping,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::AddNode>(
+                    "add_node",
+                    Request::has_add_node,
+                    Request::get_add_node,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::RemoveNode>(
+                    "remove_node",
+                    Request::has_remove_node,
+                    Request::get_remove_node,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::Info>(
+                    "info",
+                    Request::has_info,
+                    Request::get_info,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_bytes_accessor::<_>(
+                    "propose",
+                    Request::has_propose,
+                    Request::get_propose,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_bytes_accessor::<_>(
+                    "query",
+                    Request::has_query,
+                    Request::get_query,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::PromoteNode>(
+                    "promote_node",
+                    Request::has_promote_node,
+                    Request::get_promote_node,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::TransferLeader>(
+                    "transfer_leader",
+                    Request::has_transfer_leader,
+                    Request::get_transfer_leader,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::UpdatePeerAddress>(
+                    "update_peer_address",
+                    Request::has_update_peer_address,
+                    Request::get_update_peer_address,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Handshake>(
+                    "handshake",
+                    Request::has_handshake,
+                    Request::get_handshake,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::MultiSet>(
+                    "multi_set",
+                    Request::has_multi_set,
+                    Request::get_multi_set,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "request_id",
+                    |m: &Request| { &m.request_id },
+                    |m: &mut Request| { &mut m.request_id },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<Request>(
+                    "Request",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static Request {
+        static mut instance: ::protobuf::lazy::Lazy<Request> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const Request,
+        };
+        unsafe {
+            instance.get(Request::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for Request {
+    fn clear(&mut self) {
+        self.clear_get();
+        self.clear_set();
+        self.clear_delete();
+        self.clear_scan();
+        self.clear_ping();
+        self.clear_add_node();
+        self.clear_remove_node();
+        self.clear_info();
+        self.clear_propose();
+        self.clear_query();
+        self.clear_promote_node();
+        self.clear_transfer_leader();
+        self.clear_update_peer_address();
+        self.clear_handshake();
+        self.clear_multi_set();
+        self.clear_request_id();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Request {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Request {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Handshake {
+    // message fields
+    pub magic: ::std::vec::Vec<u8>,
+    pub version: u32,
+    pub min_version: u32,
+    pub cluster: ::std::string::String,
+    pub node_id: u64,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl Handshake {
+    pub fn new() -> Handshake {
+        ::std::default::Default::default()
+    }
+
+    // bytes magic = 1;
+
+    pub fn clear_magic(&mut self) {
+        self.magic.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_magic(&mut self, v: ::std::vec::Vec<u8>) {
+        self.magic = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_magic(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.magic
+    }
+
+    // Take field
+    pub fn take_magic(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.magic, ::std::vec::Vec::new())
+    }
+
+    pub fn get_magic(&self) -> &[u8] {
+        &self.magic
+    }
+
+    // uint32 version = 2;
+
+    pub fn clear_version(&mut self) {
+        self.version = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_version(&mut self, v: u32) {
+        self.version = v;
+    }
+
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+
+    // uint32 min_version = 3;
+
+    pub fn clear_min_version(&mut self) {
+        self.min_version = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_min_version(&mut self, v: u32) {
+        self.min_version = v;
+    }
+
+    pub fn get_min_version(&self) -> u32 {
+        self.min_version
+    }
+
+    // string cluster = 4;
+
+    pub fn clear_cluster(&mut self) {
+        self.cluster.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_cluster(&mut self, v: ::std::string::String) {
+        self.cluster = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_cluster(&mut self) -> &mut ::std::string::String {
+        &mut self.cluster
+    }
+
+    // Take field
+    pub fn take_cluster(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.cluster, ::std::string::String::new())
+    }
+
+    pub fn get_cluster(&self) -> &str {
+        &self.cluster
+    }
+
+    // uint64 node_id = 5;
+
+    pub fn clear_node_id(&mut self) {
+        self.node_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_node_id(&mut self, v: u64) {
+        self.node_id = v;
+    }
+
+    pub fn get_node_id(&self) -> u64 {
+        self.node_id
+    }
+}
+
+impl ::protobuf::Message for Handshake {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.magic)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.version = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.min_version = tmp;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.cluster)?;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.node_id = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.magic.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(1, &self.magic);
+        }
+        if self.version != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.version, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.min_version != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.min_version, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.cluster.is_empty() {
+            my_size += ::protobuf::rt::string_size(4, &self.cluster);
+        }
+        if self.node_id != 0 {
+            my_size += ::protobuf::rt::value_size(5, self.node_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if !self.magic.is_empty() {
+            os.write_bytes(1, &self.magic)?;
+        }
+        if self.version != 0 {
+            os.write_uint32(2, self.version)?;
+        }
+        if self.min_version != 0 {
+            os.write_uint32(3, self.min_version)?;
+        }
+        if !self.cluster.is_empty() {
+            os.write_string(4, &self.cluster)?;
+        }
+        if self.node_id != 0 {
+            os.write_uint64(5, self.node_id)?;
         }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
@@ -633,8 +1513,8 @@ impl ::protobuf::Message for Request {
         Self::descriptor_static()
     }
 
-    fn new() -> Request {
-        Request::new()
+    fn new() -> Handshake {
+        Handshake::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
@@ -645,48 +1525,33 @@ impl ::protobuf::Message for Request {
         unsafe {
             descriptor.get(|| {
                 let mut fields = ::std::vec::Vec::new();
-                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::Get>(
-                    "get",
-                    Request::has_get,
-                    Request::get_get,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::Set>(
-                    "set",
-                    Request::has_set,
-                    Request::get_set,
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                    "magic",
+                    |m: &Handshake| { &m.magic },
+                    |m: &mut Handshake| { &mut m.magic },
                 ));
-                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::Delete>(
-                    "delete",
-                    Request::has_delete,
-                    Request::get_delete,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::Scan>(
-                    "scan",
-                    Request::has_scan,
-                    Request::get_scan,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_bool_accessor::<_>(
-                    "ping",
-                    Request::has_ping,
-                    Request::get_ping,
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                    "version",
+                    |m: &Handshake| { &m.version },
+                    |m: &mut Handshake| { &mut m.version },
                 ));
-                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::AddNode>(
-                    "add_node",
-                    Request::has_add_node,
-                    Request::get_add_node,
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                    "min_version",
+                    |m: &Handshake| { &m.min_version },
+                    |m: &mut Handshake| { &mut m.min_version },
                 ));
-                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::RemoveNode>(
-                    "remove_node",
-                    Request::has_remove_node,
-                    Request::get_remove_node,
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "cluster",
+                    |m: &Handshake| { &m.cluster },
+                    |m: &mut Handshake| { &mut m.cluster },
                 ));
-                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::request::Info>(
-                    "info",
-                    Request::has_info,
-                    Request::get_info,
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "node_id",
+                    |m: &Handshake| { &m.node_id },
+                    |m: &mut Handshake| { &mut m.node_id },
                 ));
-                ::protobuf::reflect::MessageDescriptor::new::<Request>(
-                    "Request",
+                ::protobuf::reflect::MessageDescriptor::new::<Handshake>(
+                    "Handshake",
                     fields,
                     file_descriptor_proto()
                 )
@@ -694,38 +1559,35 @@ impl ::protobuf::Message for Request {
         }
     }
 
-    fn default_instance() -> &'static Request {
-        static mut instance: ::protobuf::lazy::Lazy<Request> = ::protobuf::lazy::Lazy {
+    fn default_instance() -> &'static Handshake {
+        static mut instance: ::protobuf::lazy::Lazy<Handshake> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const Request,
+            ptr: 0 as *const Handshake,
         };
         unsafe {
-            instance.get(Request::new)
+            instance.get(Handshake::new)
         }
     }
 }
 
-impl ::protobuf::Clear for Request {
+impl ::protobuf::Clear for Handshake {
     fn clear(&mut self) {
-        self.clear_get();
-        self.clear_set();
-        self.clear_delete();
-        self.clear_scan();
-        self.clear_ping();
-        self.clear_add_node();
-        self.clear_remove_node();
-        self.clear_info();
+        self.clear_magic();
+        self.clear_version();
+        self.clear_min_version();
+        self.clear_cluster();
+        self.clear_node_id();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for Request {
+impl ::std::fmt::Debug for Handshake {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for Request {
+impl ::protobuf::reflect::ProtobufValue for Handshake {
     fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
         ::protobuf::reflect::ProtobufValueRef::Message(self)
     }
@@ -733,6 +1595,8 @@ impl ::protobuf::reflect::ProtobufValue for Request {
 
 #[derive(PartialEq,Clone,Default)]
 pub struct Response {
+    // message fields
+    pub request_id: u64,
     // message oneof groups
     pub kind: ::std::option::Option<Response_oneof_kind>,
     // special fields
@@ -748,6 +1612,9 @@ pub enum Response_oneof_kind {
     pong(bool),
     success(bool),
     info(super::response::Info),
+    result(::std::vec::Vec<u8>),
+    error(super::response::Error),
+    handshake(Handshake),
 }
 
 impl Response {
@@ -1000,6 +1867,168 @@ impl Response {
             _ => super::response::Info::default_instance(),
         }
     }
+
+    // bytes result = 8;
+
+    pub fn clear_result(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_result(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(Response_oneof_kind::result(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_result(&mut self, v: ::std::vec::Vec<u8>) {
+        self.kind = ::std::option::Option::Some(Response_oneof_kind::result(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_result(&mut self) -> &mut ::std::vec::Vec<u8> {
+        if let ::std::option::Option::Some(Response_oneof_kind::result(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(Response_oneof_kind::result(::std::vec::Vec::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(Response_oneof_kind::result(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_result(&mut self) -> ::std::vec::Vec<u8> {
+        if self.has_result() {
+            match self.kind.take() {
+                ::std::option::Option::Some(Response_oneof_kind::result(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            ::std::vec::Vec::new()
+        }
+    }
+
+    pub fn get_result(&self) -> &[u8] {
+        match self.kind {
+            ::std::option::Option::Some(Response_oneof_kind::result(ref v)) => v,
+            _ => &[],
+        }
+    }
+
+    // .public.response.Error error = 9;
+
+    pub fn clear_error(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_error(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(Response_oneof_kind::error(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_error(&mut self, v: super::response::Error) {
+        self.kind = ::std::option::Option::Some(Response_oneof_kind::error(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_error(&mut self) -> &mut super::response::Error {
+        if let ::std::option::Option::Some(Response_oneof_kind::error(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(Response_oneof_kind::error(super::response::Error::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(Response_oneof_kind::error(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_error(&mut self) -> super::response::Error {
+        if self.has_error() {
+            match self.kind.take() {
+                ::std::option::Option::Some(Response_oneof_kind::error(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            super::response::Error::new()
+        }
+    }
+
+    pub fn get_error(&self) -> &super::response::Error {
+        match self.kind {
+            ::std::option::Option::Some(Response_oneof_kind::error(ref v)) => v,
+            _ => super::response::Error::default_instance(),
+        }
+    }
+
+    // .public.Handshake handshake = 11;
+
+    pub fn clear_handshake(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_handshake(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(Response_oneof_kind::handshake(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_handshake(&mut self, v: Handshake) {
+        self.kind = ::std::option::Option::Some(Response_oneof_kind::handshake(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_handshake(&mut self) -> &mut Handshake {
+        if let ::std::option::Option::Some(Response_oneof_kind::handshake(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(Response_oneof_kind::handshake(Handshake::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(Response_oneof_kind::handshake(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_handshake(&mut self) -> Handshake {
+        if self.has_handshake() {
+            match self.kind.take() {
+                ::std::option::Option::Some(Response_oneof_kind::handshake(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Handshake::new()
+        }
+    }
+
+    pub fn get_handshake(&self) -> &Handshake {
+        match self.kind {
+            ::std::option::Option::Some(Response_oneof_kind::handshake(ref v)) => v,
+            _ => Handshake::default_instance(),
+        }
+    }
+
+    // uint64 request_id = 10;
+
+    pub fn clear_request_id(&mut self) {
+        self.request_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_request_id(&mut self, v: u64) {
+        self.request_id = v;
+    }
+
+    pub fn get_request_id(&self) -> u64 {
+        self.request_id
+    }
 }
 
 impl ::protobuf::Message for Response {
@@ -1024,6 +2053,16 @@ impl ::protobuf::Message for Response {
                 return false;
             }
         }
+        if let Some(Response_oneof_kind::error(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Response_oneof_kind::handshake(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
         true
     }
 
@@ -1067,6 +2106,31 @@ impl ::protobuf::Message for Response {
                     }
                     self.kind = ::std::option::Option::Some(Response_oneof_kind::info(is.read_message()?));
                 },
+                8 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(Response_oneof_kind::result(is.read_bytes()?));
+                },
+                9 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(Response_oneof_kind::error(is.read_message()?));
+                },
+                11 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(Response_oneof_kind::handshake(is.read_message()?));
+                },
+                10 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.request_id = tmp;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -1079,6 +2143,9 @@ impl ::protobuf::Message for Response {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
+        if self.request_id != 0 {
+            my_size += ::protobuf::rt::value_size(10, self.request_id, ::protobuf::wire_format::WireTypeVarint);
+        }
         if let ::std::option::Option::Some(ref v) = self.kind {
             match v {
                 &Response_oneof_kind::get(ref v) => {
@@ -1103,6 +2170,17 @@ impl ::protobuf::Message for Response {
                     let len = v.compute_size();
                     my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
                 },
+                &Response_oneof_kind::result(ref v) => {
+                    my_size += ::protobuf::rt::bytes_size(8, &v);
+                },
+                &Response_oneof_kind::error(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &Response_oneof_kind::handshake(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
             };
         }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
@@ -1111,6 +2189,9 @@ impl ::protobuf::Message for Response {
     }
 
     fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.request_id != 0 {
+            os.write_uint64(10, self.request_id)?;
+        }
         if let ::std::option::Option::Some(ref v) = self.kind {
             match v {
                 &Response_oneof_kind::get(ref v) => {
@@ -1139,6 +2220,19 @@ impl ::protobuf::Message for Response {
                     os.write_raw_varint32(v.get_cached_size())?;
                     v.write_to_with_cached_sizes(os)?;
                 },
+                &Response_oneof_kind::result(ref v) => {
+                    os.write_bytes(8, v)?;
+                },
+                &Response_oneof_kind::error(ref v) => {
+                    os.write_tag(9, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Response_oneof_kind::handshake(ref v) => {
+                    os.write_tag(11, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
             };
         }
         os.write_unknown_fields(self.get_unknown_fields())?;
@@ -1213,6 +2307,26 @@ impl ::protobuf::Message for Response {
                     Response::has_info,
                     Response::get_info,
                 ));
+                fields.push(::protobuf::reflect::accessor::make_singular_bytes_accessor::<_>(
+                    "result",
+                    Response::has_result,
+                    Response::get_result,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, super::response::Error>(
+                    "error",
+                    Response::has_error,
+                    Response::get_error,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Handshake>(
+                    "handshake",
+                    Response::has_handshake,
+                    Response::get_handshake,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "request_id",
+                    |m: &Response| { &m.request_id },
+                    |m: &mut Response| { &mut m.request_id },
+                ));
                 ::protobuf::reflect::MessageDescriptor::new::<Response>(
                     "Response",
                     fields,
@@ -1241,6 +2355,10 @@ impl ::protobuf::Clear for Response {
         self.clear_pong();
         self.clear_success();
         self.clear_info();
+        self.clear_result();
+        self.clear_error();
+        self.clear_handshake();
+        self.clear_request_id();
         self.unknown_fields.clear();
     }
 }
@@ -1259,7 +2377,7 @@ impl ::protobuf::reflect::ProtobufValue for Response {
 
 static file_descriptor_proto_data: &'static [u8] = b"\
     \n\x0cpublic.proto\x12\x06public\x1a\rrequest.proto\x1a\x0eresponse.prot\
-    o\"\xf8\x02\n\x07Request\x12'\n\x03get\x18\x01\x20\x01(\x0b2\x13.public.\
+    o\"\x99\x06\n\x07Request\x12'\n\x03get\x18\x01\x20\x01(\x0b2\x13.public.\
     request.GetH\0R\x03get\x12'\n\x03set\x18\x02\x20\x01(\x0b2\x13.public.re\
     quest.SetH\0R\x03set\x120\n\x06delete\x18\x03\x20\x01(\x0b2\x16.public.r\
     equest.DeleteH\0R\x06delete\x12*\n\x04scan\x18\x04\x20\x01(\x0b2\x14.pub\
@@ -1267,14 +2385,30 @@ static file_descriptor_proto_data: &'static [u8] = b"\
     \x04ping\x124\n\x08add_node\x18\x06\x20\x01(\x0b2\x17.public.request.Add\
     NodeH\0R\x07addNode\x12=\n\x0bremove_node\x18\x07\x20\x01(\x0b2\x1a.publ\
     ic.request.RemoveNodeH\0R\nremoveNode\x12*\n\x04info\x18\x08\x20\x01(\
-    \x0b2\x14.public.request.InfoH\0R\x04infoB\x06\n\x04kind\"\xfb\x01\n\x08\
-    Response\x12(\n\x03get\x18\x01\x20\x01(\x0b2\x14.public.response.GetH\0R\
-    \x03get\x121\n\x06delete\x18\x03\x20\x01(\x0b2\x17.public.response.Delet\
-    eH\0R\x06delete\x12+\n\x04scan\x18\x04\x20\x01(\x0b2\x15.public.response\
-    .ScanH\0R\x04scan\x12\x14\n\x04pong\x18\x05\x20\x01(\x08H\0R\x04pong\x12\
-    \x1a\n\x07success\x18\x06\x20\x01(\x08H\0R\x07success\x12+\n\x04info\x18\
-    \x07\x20\x01(\x0b2\x15.public.response.InfoH\0R\x04infoB\x06\n\x04kindb\
-    \x06proto3\
+    \x0b2\x14.public.request.InfoH\0R\x04info\x12\x1a\n\x07propose\x18\t\x20\
+    \x01(\x0cH\0R\x07propose\x12\x16\n\x05query\x18\n\x20\x01(\x0cH\0R\x05qu\
+    ery\x12@\n\x0cpromote_node\x18\x0b\x20\x01(\x0b2\x1b.public.request.Prom\
+    oteNodeH\0R\x0bpromoteNode\x12I\n\x0ftransfer_leader\x18\x0c\x20\x01(\
+    \x0b2\x1e.public.request.TransferLeaderH\0R\x0etransferLeader\x12S\n\x13\
+    update_peer_address\x18\r\x20\x01(\x0b2!.public.request.UpdatePeerAddres\
+    sH\0R\x11updatePeerAddress\x121\n\thandshake\x18\x0f\x20\x01(\x0b2\x11.p\
+    ublic.HandshakeH\0R\thandshake\x127\n\tmulti_set\x18\x10\x20\x01(\x0b2\
+    \x18.public.request.MultiSetH\0R\x08multiSet\x12\x1d\n\nrequest_id\x18\
+    \x0e\x20\x01(\x04R\trequestIdB\x06\n\x04kind\"\x8f\x01\n\tHandshake\x12\
+    \x14\n\x05magic\x18\x01\x20\x01(\x0cR\x05magic\x12\x18\n\x07version\x18\
+    \x02\x20\x01(\rR\x07version\x12\x1f\n\x0bmin_version\x18\x03\x20\x01(\rR\
+    \nminVersion\x12\x18\n\x07cluster\x18\x04\x20\x01(\tR\x07cluster\x12\x17\
+    \n\x07node_id\x18\x05\x20\x01(\x04R\x06nodeId\"\x97\x03\n\x08Response\
+    \x12(\n\x03get\x18\x01\x20\x01(\x0b2\x14.public.response.GetH\0R\x03get\
+    \x121\n\x06delete\x18\x03\x20\x01(\x0b2\x17.public.response.DeleteH\0R\
+    \x06delete\x12+\n\x04scan\x18\x04\x20\x01(\x0b2\x15.public.response.Scan\
+    H\0R\x04scan\x12\x14\n\x04pong\x18\x05\x20\x01(\x08H\0R\x04pong\x12\x1a\
+    \n\x07success\x18\x06\x20\x01(\x08H\0R\x07success\x12+\n\x04info\x18\x07\
+    \x20\x01(\x0b2\x15.public.response.InfoH\0R\x04info\x12\x18\n\x06result\
+    \x18\x08\x20\x01(\x0cH\0R\x06result\x12.\n\x05error\x18\t\x20\x01(\x0b2\
+    \x16.public.response.ErrorH\0R\x05error\x121\n\thandshake\x18\x0b\x20\
+    \x01(\x0b2\x11.public.HandshakeH\0R\thandshake\x12\x1d\n\nrequest_id\x18\
+    \n\x20\x01(\x04R\trequestIdB\x06\n\x04kindb\x06proto3\
 ";
 
 static mut file_descriptor_proto_lazy: ::protobuf::lazy::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::lazy::Lazy {
@@ -25,6 +25,8 @@ use protobuf::ProtobufEnum as ProtobufEnum_imported_for_functions;
 pub struct Get {
     // message fields
     pub key: ::std::string::String,
+    pub consistency: Consistency,
+    pub max_lag: u64,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::protobuf::CachedSize,
@@ -60,6 +62,36 @@ impl Get {
     pub fn get_key(&self) -> &str {
         &self.key
     }
+
+    // .public.request.Consistency consistency = 2;
+
+    pub fn clear_consistency(&mut self) {
+        self.consistency = Consistency::LINEARIZABLE;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_consistency(&mut self, v: Consistency) {
+        self.consistency = v;
+    }
+
+    pub fn get_consistency(&self) -> Consistency {
+        self.consistency
+    }
+
+    // uint64 max_lag = 3;
+
+    pub fn clear_max_lag(&mut self) {
+        self.max_lag = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_max_lag(&mut self, v: u64) {
+        self.max_lag = v;
+    }
+
+    pub fn get_max_lag(&self) -> u64 {
+        self.max_lag
+    }
 }
 
 impl ::protobuf::Message for Get {
@@ -74,6 +106,16 @@ impl ::protobuf::Message for Get {
                 1 => {
                     ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.key)?;
                 },
+                2 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.consistency, 2, &mut self.unknown_fields)?
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.max_lag = tmp;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -89,6 +131,12 @@ impl ::protobuf::Message for Get {
         if !self.key.is_empty() {
             my_size += ::protobuf::rt::string_size(1, &self.key);
         }
+        if self.consistency != Consistency::LINEARIZABLE {
+            my_size += ::protobuf::rt::enum_size(2, self.consistency);
+        }
+        if self.max_lag != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.max_lag, ::protobuf::wire_format::WireTypeVarint);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -98,6 +146,12 @@ impl ::protobuf::Message for Get {
         if !self.key.is_empty() {
             os.write_string(1, &self.key)?;
         }
+        if self.consistency != Consistency::LINEARIZABLE {
+            os.write_enum(2, self.consistency.value())?;
+        }
+        if self.max_lag != 0 {
+            os.write_uint64(3, self.max_lag)?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -145,6 +199,16 @@ impl ::protobuf::Message for Get {
                     |m: &Get| { &m.key },
                     |m: &mut Get| { &mut m.key },
                 ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<Consistency>>(
+                    "consistency",
+                    |m: &Get| { &m.consistency },
+                    |m: &mut Get| { &mut m.consistency },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "max_lag",
+                    |m: &Get| { &m.max_lag },
+                    |m: &mut Get| { &mut m.max_lag },
+                ));
                 ::protobuf::reflect::MessageDescriptor::new::<Get>(
                     "Get",
                     fields,
@@ -168,6 +232,8 @@ impl ::protobuf::Message for Get {
 impl ::protobuf::Clear for Get {
     fn clear(&mut self) {
         self.clear_key();
+        self.clear_consistency();
+        self.clear_max_lag();
         self.unknown_fields.clear();
     }
 }
@@ -553,20 +619,52 @@ impl ::protobuf::reflect::ProtobufValue for Delete {
 }
 
 #[derive(PartialEq,Clone,Default)]
-pub struct Scan {
+pub struct MultiSet {
+    // message fields
+    pub sets: ::protobuf::RepeatedField<Set>,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::protobuf::CachedSize,
 }
 
-impl Scan {
-    pub fn new() -> Scan {
+impl MultiSet {
+    pub fn new() -> MultiSet {
         ::std::default::Default::default()
     }
+
+    // repeated .public.request.Set sets = 1;
+
+    pub fn clear_sets(&mut self) {
+        self.sets.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_sets(&mut self, v: ::protobuf::RepeatedField<Set>) {
+        self.sets = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_sets(&mut self) -> &mut ::protobuf::RepeatedField<Set> {
+        &mut self.sets
+    }
+
+    // Take field
+    pub fn take_sets(&mut self) -> ::protobuf::RepeatedField<Set> {
+        ::std::mem::replace(&mut self.sets, ::protobuf::RepeatedField::new())
+    }
+
+    pub fn get_sets(&self) -> &[Set] {
+        &self.sets
+    }
 }
 
-impl ::protobuf::Message for Scan {
+impl ::protobuf::Message for MultiSet {
     fn is_initialized(&self) -> bool {
+        for v in &self.sets {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
         true
     }
 
@@ -574,6 +672,9 @@ impl ::protobuf::Message for Scan {
         while !is.eof()? {
             let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.sets)?;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -586,12 +687,21 @@ impl ::protobuf::Message for Scan {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
+        for value in &self.sets {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
     }
 
     fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        for v in &self.sets {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -622,8 +732,8 @@ impl ::protobuf::Message for Scan {
         Self::descriptor_static()
     }
 
-    fn new() -> Scan {
-        Scan::new()
+    fn new() -> MultiSet {
+        MultiSet::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
@@ -633,9 +743,14 @@ impl ::protobuf::Message for Scan {
         };
         unsafe {
             descriptor.get(|| {
-                let fields = ::std::vec::Vec::new();
-                ::protobuf::reflect::MessageDescriptor::new::<Scan>(
-                    "Scan",
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Set>>(
+                    "sets",
+                    |m: &MultiSet| { &m.sets },
+                    |m: &mut MultiSet| { &mut m.sets },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<MultiSet>(
+                    "MultiSet",
                     fields,
                     file_descriptor_proto()
                 )
@@ -643,109 +758,239 @@ impl ::protobuf::Message for Scan {
         }
     }
 
-    fn default_instance() -> &'static Scan {
-        static mut instance: ::protobuf::lazy::Lazy<Scan> = ::protobuf::lazy::Lazy {
+    fn default_instance() -> &'static MultiSet {
+        static mut instance: ::protobuf::lazy::Lazy<MultiSet> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const Scan,
+            ptr: 0 as *const MultiSet,
         };
         unsafe {
-            instance.get(Scan::new)
+            instance.get(MultiSet::new)
         }
     }
 }
 
-impl ::protobuf::Clear for Scan {
+impl ::protobuf::Clear for MultiSet {
     fn clear(&mut self) {
+        self.clear_sets();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for Scan {
+impl ::std::fmt::Debug for MultiSet {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for Scan {
+impl ::protobuf::reflect::ProtobufValue for MultiSet {
     fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
         ::protobuf::reflect::ProtobufValueRef::Message(self)
     }
 }
 
 #[derive(PartialEq,Clone,Default)]
-pub struct AddNode {
+pub struct Scan {
     // message fields
-    pub id: u64,
-    pub addr: ::std::string::String,
-    pub is_learner: bool,
+    pub start: ::std::string::String,
+    pub end: ::std::string::String,
+    pub prefix: ::std::string::String,
+    pub limit: u64,
+    pub reverse: bool,
+    pub include_values: bool,
+    pub token: ::std::string::String,
+    pub consistency: Consistency,
+    pub max_lag: u64,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::protobuf::CachedSize,
 }
 
-impl AddNode {
-    pub fn new() -> AddNode {
+impl Scan {
+    pub fn new() -> Scan {
         ::std::default::Default::default()
     }
 
-    // uint64 id = 1;
+    // string start = 1;
 
-    pub fn clear_id(&mut self) {
-        self.id = 0;
+    pub fn clear_start(&mut self) {
+        self.start.clear();
     }
 
     // Param is passed by value, moved
-    pub fn set_id(&mut self, v: u64) {
-        self.id = v;
+    pub fn set_start(&mut self, v: ::std::string::String) {
+        self.start = v;
     }
 
-    pub fn get_id(&self) -> u64 {
-        self.id
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_start(&mut self) -> &mut ::std::string::String {
+        &mut self.start
     }
 
-    // string addr = 2;
+    // Take field
+    pub fn take_start(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.start, ::std::string::String::new())
+    }
 
-    pub fn clear_addr(&mut self) {
-        self.addr.clear();
+    pub fn get_start(&self) -> &str {
+        &self.start
+    }
+
+    // string end = 2;
+
+    pub fn clear_end(&mut self) {
+        self.end.clear();
     }
 
     // Param is passed by value, moved
-    pub fn set_addr(&mut self, v: ::std::string::String) {
-        self.addr = v;
+    pub fn set_end(&mut self, v: ::std::string::String) {
+        self.end = v;
     }
 
     // Mutable pointer to the field.
     // If field is not initialized, it is initialized with default value first.
-    pub fn mut_addr(&mut self) -> &mut ::std::string::String {
-        &mut self.addr
+    pub fn mut_end(&mut self) -> &mut ::std::string::String {
+        &mut self.end
     }
 
     // Take field
-    pub fn take_addr(&mut self) -> ::std::string::String {
-        ::std::mem::replace(&mut self.addr, ::std::string::String::new())
+    pub fn take_end(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.end, ::std::string::String::new())
     }
 
-    pub fn get_addr(&self) -> &str {
-        &self.addr
+    pub fn get_end(&self) -> &str {
+        &self.end
     }
 
-    // bool is_learner = 3;
+    // string prefix = 3;
 
-    pub fn clear_is_learner(&mut self) {
-        self.is_learner = false;
+    pub fn clear_prefix(&mut self) {
+        self.prefix.clear();
     }
 
     // Param is passed by value, moved
-    pub fn set_is_learner(&mut self, v: bool) {
-        self.is_learner = v;
+    pub fn set_prefix(&mut self, v: ::std::string::String) {
+        self.prefix = v;
     }
 
-    pub fn get_is_learner(&self) -> bool {
-        self.is_learner
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_prefix(&mut self) -> &mut ::std::string::String {
+        &mut self.prefix
+    }
+
+    // Take field
+    pub fn take_prefix(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.prefix, ::std::string::String::new())
+    }
+
+    pub fn get_prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    // uint64 limit = 4;
+
+    pub fn clear_limit(&mut self) {
+        self.limit = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_limit(&mut self, v: u64) {
+        self.limit = v;
+    }
+
+    pub fn get_limit(&self) -> u64 {
+        self.limit
+    }
+
+    // bool reverse = 5;
+
+    pub fn clear_reverse(&mut self) {
+        self.reverse = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_reverse(&mut self, v: bool) {
+        self.reverse = v;
+    }
+
+    pub fn get_reverse(&self) -> bool {
+        self.reverse
+    }
+
+    // bool include_values = 6;
+
+    pub fn clear_include_values(&mut self) {
+        self.include_values = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_include_values(&mut self, v: bool) {
+        self.include_values = v;
+    }
+
+    pub fn get_include_values(&self) -> bool {
+        self.include_values
+    }
+
+    // string token = 7;
+
+    pub fn clear_token(&mut self) {
+        self.token.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_token(&mut self, v: ::std::string::String) {
+        self.token = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_token(&mut self) -> &mut ::std::string::String {
+        &mut self.token
+    }
+
+    // Take field
+    pub fn take_token(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.token, ::std::string::String::new())
+    }
+
+    pub fn get_token(&self) -> &str {
+        &self.token
+    }
+
+    // .public.request.Consistency consistency = 8;
+
+    pub fn clear_consistency(&mut self) {
+        self.consistency = Consistency::LINEARIZABLE;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_consistency(&mut self, v: Consistency) {
+        self.consistency = v;
+    }
+
+    pub fn get_consistency(&self) -> Consistency {
+        self.consistency
+    }
+
+    // uint64 max_lag = 9;
+
+    pub fn clear_max_lag(&mut self) {
+        self.max_lag = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_max_lag(&mut self, v: u64) {
+        self.max_lag = v;
+    }
+
+    pub fn get_max_lag(&self) -> u64 {
+        self.max_lag
     }
 }
 
-impl ::protobuf::Message for AddNode {
+impl ::protobuf::Message for Scan {
     fn is_initialized(&self) -> bool {
         true
     }
@@ -755,21 +1000,47 @@ impl ::protobuf::Message for AddNode {
             let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
                 1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.start)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.end)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.prefix)?;
+                },
+                4 => {
                     if wire_type != ::protobuf::wire_format::WireTypeVarint {
                         return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
                     }
                     let tmp = is.read_uint64()?;
-                    self.id = tmp;
+                    self.limit = tmp;
                 },
-                2 => {
-                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.addr)?;
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.reverse = tmp;
                 },
-                3 => {
+                6 => {
                     if wire_type != ::protobuf::wire_format::WireTypeVarint {
                         return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
                     }
                     let tmp = is.read_bool()?;
-                    self.is_learner = tmp;
+                    self.include_values = tmp;
+                },
+                7 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.token)?;
+                },
+                8 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.consistency, 8, &mut self.unknown_fields)?
+                },
+                9 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.max_lag = tmp;
                 },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
@@ -783,29 +1054,65 @@ impl ::protobuf::Message for AddNode {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
-        if self.id != 0 {
-            my_size += ::protobuf::rt::value_size(1, self.id, ::protobuf::wire_format::WireTypeVarint);
+        if !self.start.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.start);
         }
-        if !self.addr.is_empty() {
-            my_size += ::protobuf::rt::string_size(2, &self.addr);
+        if !self.end.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.end);
         }
-        if self.is_learner != false {
+        if !self.prefix.is_empty() {
+            my_size += ::protobuf::rt::string_size(3, &self.prefix);
+        }
+        if self.limit != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.limit, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.reverse != false {
+            my_size += 2;
+        }
+        if self.include_values != false {
             my_size += 2;
         }
+        if !self.token.is_empty() {
+            my_size += ::protobuf::rt::string_size(7, &self.token);
+        }
+        if self.consistency != Consistency::LINEARIZABLE {
+            my_size += ::protobuf::rt::enum_size(8, self.consistency);
+        }
+        if self.max_lag != 0 {
+            my_size += ::protobuf::rt::value_size(9, self.max_lag, ::protobuf::wire_format::WireTypeVarint);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
     }
 
     fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
-        if self.id != 0 {
-            os.write_uint64(1, self.id)?;
+        if !self.start.is_empty() {
+            os.write_string(1, &self.start)?;
         }
-        if !self.addr.is_empty() {
-            os.write_string(2, &self.addr)?;
+        if !self.end.is_empty() {
+            os.write_string(2, &self.end)?;
         }
-        if self.is_learner != false {
-            os.write_bool(3, self.is_learner)?;
+        if !self.prefix.is_empty() {
+            os.write_string(3, &self.prefix)?;
+        }
+        if self.limit != 0 {
+            os.write_uint64(4, self.limit)?;
+        }
+        if self.reverse != false {
+            os.write_bool(5, self.reverse)?;
+        }
+        if self.include_values != false {
+            os.write_bool(6, self.include_values)?;
+        }
+        if !self.token.is_empty() {
+            os.write_string(7, &self.token)?;
+        }
+        if self.consistency != Consistency::LINEARIZABLE {
+            os.write_enum(8, self.consistency.value())?;
+        }
+        if self.max_lag != 0 {
+            os.write_uint64(9, self.max_lag)?;
         }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
@@ -837,8 +1144,8 @@ impl ::protobuf::Message for AddNode {
         Self::descriptor_static()
     }
 
-    fn new() -> AddNode {
-        AddNode::new()
+    fn new() -> Scan {
+        Scan::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
@@ -849,23 +1156,53 @@ impl ::protobuf::Message for AddNode {
         unsafe {
             descriptor.get(|| {
                 let mut fields = ::std::vec::Vec::new();
-                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
-                    "id",
-                    |m: &AddNode| { &m.id },
-                    |m: &mut AddNode| { &mut m.id },
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "start",
+                    |m: &Scan| { &m.start },
+                    |m: &mut Scan| { &mut m.start },
                 ));
                 fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
-                    "addr",
-                    |m: &AddNode| { &m.addr },
-                    |m: &mut AddNode| { &mut m.addr },
+                    "end",
+                    |m: &Scan| { &m.end },
+                    |m: &mut Scan| { &mut m.end },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "prefix",
+                    |m: &Scan| { &m.prefix },
+                    |m: &mut Scan| { &mut m.prefix },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "limit",
+                    |m: &Scan| { &m.limit },
+                    |m: &mut Scan| { &mut m.limit },
                 ));
                 fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
-                    "is_learner",
-                    |m: &AddNode| { &m.is_learner },
-                    |m: &mut AddNode| { &mut m.is_learner },
+                    "reverse",
+                    |m: &Scan| { &m.reverse },
+                    |m: &mut Scan| { &mut m.reverse },
                 ));
-                ::protobuf::reflect::MessageDescriptor::new::<AddNode>(
-                    "AddNode",
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                    "include_values",
+                    |m: &Scan| { &m.include_values },
+                    |m: &mut Scan| { &mut m.include_values },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "token",
+                    |m: &Scan| { &m.token },
+                    |m: &mut Scan| { &mut m.token },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<Consistency>>(
+                    "consistency",
+                    |m: &Scan| { &m.consistency },
+                    |m: &mut Scan| { &mut m.consistency },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "max_lag",
+                    |m: &Scan| { &m.max_lag },
+                    |m: &mut Scan| { &mut m.max_lag },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<Scan>(
+                    "Scan",
                     fields,
                     file_descriptor_proto()
                 )
@@ -873,49 +1210,58 @@ impl ::protobuf::Message for AddNode {
         }
     }
 
-    fn default_instance() -> &'static AddNode {
-        static mut instance: ::protobuf::lazy::Lazy<AddNode> = ::protobuf::lazy::Lazy {
+    fn default_instance() -> &'static Scan {
+        static mut instance: ::protobuf::lazy::Lazy<Scan> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const AddNode,
+            ptr: 0 as *const Scan,
         };
         unsafe {
-            instance.get(AddNode::new)
+            instance.get(Scan::new)
         }
     }
 }
 
-impl ::protobuf::Clear for AddNode {
+impl ::protobuf::Clear for Scan {
     fn clear(&mut self) {
-        self.clear_id();
-        self.clear_addr();
-        self.clear_is_learner();
+        self.clear_start();
+        self.clear_end();
+        self.clear_prefix();
+        self.clear_limit();
+        self.clear_reverse();
+        self.clear_include_values();
+        self.clear_token();
+        self.clear_consistency();
+        self.clear_max_lag();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for AddNode {
+impl ::std::fmt::Debug for Scan {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for AddNode {
+impl ::protobuf::reflect::ProtobufValue for Scan {
     fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
         ::protobuf::reflect::ProtobufValueRef::Message(self)
     }
 }
 
 #[derive(PartialEq,Clone,Default)]
-pub struct RemoveNode {
+pub struct AddNode {
     // message fields
     pub id: u64,
+    pub addr: ::std::string::String,
+    pub is_learner: bool,
+    pub public_addr: ::std::string::String,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::protobuf::CachedSize,
 }
 
-impl RemoveNode {
-    pub fn new() -> RemoveNode {
+impl AddNode {
+    pub fn new() -> AddNode {
         ::std::default::Default::default()
     }
 
@@ -933,9 +1279,76 @@ impl RemoveNode {
     pub fn get_id(&self) -> u64 {
         self.id
     }
+
+    // string addr = 2;
+
+    pub fn clear_addr(&mut self) {
+        self.addr.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_addr(&mut self, v: ::std::string::String) {
+        self.addr = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_addr(&mut self) -> &mut ::std::string::String {
+        &mut self.addr
+    }
+
+    // Take field
+    pub fn take_addr(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.addr, ::std::string::String::new())
+    }
+
+    pub fn get_addr(&self) -> &str {
+        &self.addr
+    }
+
+    // bool is_learner = 3;
+
+    pub fn clear_is_learner(&mut self) {
+        self.is_learner = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_is_learner(&mut self, v: bool) {
+        self.is_learner = v;
+    }
+
+    pub fn get_is_learner(&self) -> bool {
+        self.is_learner
+    }
+
+    // string public_addr = 4;
+
+    pub fn clear_public_addr(&mut self) {
+        self.public_addr.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_public_addr(&mut self, v: ::std::string::String) {
+        self.public_addr = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_public_addr(&mut self) -> &mut ::std::string::String {
+        &mut self.public_addr
+    }
+
+    // Take field
+    pub fn take_public_addr(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.public_addr, ::std::string::String::new())
+    }
+
+    pub fn get_public_addr(&self) -> &str {
+        &self.public_addr
+    }
 }
 
-impl ::protobuf::Message for RemoveNode {
+impl ::protobuf::Message for AddNode {
     fn is_initialized(&self) -> bool {
         true
     }
@@ -951,6 +1364,19 @@ impl ::protobuf::Message for RemoveNode {
                     let tmp = is.read_uint64()?;
                     self.id = tmp;
                 },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.addr)?;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.is_learner = tmp;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.public_addr)?;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -966,6 +1392,15 @@ impl ::protobuf::Message for RemoveNode {
         if self.id != 0 {
             my_size += ::protobuf::rt::value_size(1, self.id, ::protobuf::wire_format::WireTypeVarint);
         }
+        if !self.addr.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.addr);
+        }
+        if self.is_learner != false {
+            my_size += 2;
+        }
+        if !self.public_addr.is_empty() {
+            my_size += ::protobuf::rt::string_size(4, &self.public_addr);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -975,6 +1410,15 @@ impl ::protobuf::Message for RemoveNode {
         if self.id != 0 {
             os.write_uint64(1, self.id)?;
         }
+        if !self.addr.is_empty() {
+            os.write_string(2, &self.addr)?;
+        }
+        if self.is_learner != false {
+            os.write_bool(3, self.is_learner)?;
+        }
+        if !self.public_addr.is_empty() {
+            os.write_string(4, &self.public_addr)?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -1005,8 +1449,8 @@ impl ::protobuf::Message for RemoveNode {
         Self::descriptor_static()
     }
 
-    fn new() -> RemoveNode {
-        RemoveNode::new()
+    fn new() -> AddNode {
+        AddNode::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
@@ -1019,11 +1463,26 @@ impl ::protobuf::Message for RemoveNode {
                 let mut fields = ::std::vec::Vec::new();
                 fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
                     "id",
-                    |m: &RemoveNode| { &m.id },
-                    |m: &mut RemoveNode| { &mut m.id },
+                    |m: &AddNode| { &m.id },
+                    |m: &mut AddNode| { &mut m.id },
                 ));
-                ::protobuf::reflect::MessageDescriptor::new::<RemoveNode>(
-                    "RemoveNode",
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "addr",
+                    |m: &AddNode| { &m.addr },
+                    |m: &mut AddNode| { &mut m.addr },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                    "is_learner",
+                    |m: &AddNode| { &m.is_learner },
+                    |m: &mut AddNode| { &mut m.is_learner },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "public_addr",
+                    |m: &AddNode| { &m.public_addr },
+                    |m: &mut AddNode| { &mut m.public_addr },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<AddNode>(
+                    "AddNode",
                     fields,
                     file_descriptor_proto()
                 )
@@ -1031,50 +1490,70 @@ impl ::protobuf::Message for RemoveNode {
         }
     }
 
-    fn default_instance() -> &'static RemoveNode {
-        static mut instance: ::protobuf::lazy::Lazy<RemoveNode> = ::protobuf::lazy::Lazy {
+    fn default_instance() -> &'static AddNode {
+        static mut instance: ::protobuf::lazy::Lazy<AddNode> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const RemoveNode,
+            ptr: 0 as *const AddNode,
         };
         unsafe {
-            instance.get(RemoveNode::new)
+            instance.get(AddNode::new)
         }
     }
 }
 
-impl ::protobuf::Clear for RemoveNode {
+impl ::protobuf::Clear for AddNode {
     fn clear(&mut self) {
         self.clear_id();
+        self.clear_addr();
+        self.clear_is_learner();
+        self.clear_public_addr();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for RemoveNode {
+impl ::std::fmt::Debug for AddNode {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for RemoveNode {
+impl ::protobuf::reflect::ProtobufValue for AddNode {
     fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
         ::protobuf::reflect::ProtobufValueRef::Message(self)
     }
 }
 
 #[derive(PartialEq,Clone,Default)]
-pub struct Info {
+pub struct RemoveNode {
+    // message fields
+    pub id: u64,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::protobuf::CachedSize,
 }
 
-impl Info {
-    pub fn new() -> Info {
+impl RemoveNode {
+    pub fn new() -> RemoveNode {
         ::std::default::Default::default()
     }
+
+    // uint64 id = 1;
+
+    pub fn clear_id(&mut self) {
+        self.id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_id(&mut self, v: u64) {
+        self.id = v;
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
 }
 
-impl ::protobuf::Message for Info {
+impl ::protobuf::Message for RemoveNode {
     fn is_initialized(&self) -> bool {
         true
     }
@@ -1083,6 +1562,13 @@ impl ::protobuf::Message for Info {
         while !is.eof()? {
             let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.id = tmp;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -1095,12 +1581,18 @@ impl ::protobuf::Message for Info {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
+        if self.id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.id, ::protobuf::wire_format::WireTypeVarint);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
     }
 
     fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.id != 0 {
+            os.write_uint64(1, self.id)?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -1131,8 +1623,8 @@ impl ::protobuf::Message for Info {
         Self::descriptor_static()
     }
 
-    fn new() -> Info {
-        Info::new()
+    fn new() -> RemoveNode {
+        RemoveNode::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
@@ -1142,9 +1634,14 @@ impl ::protobuf::Message for Info {
         };
         unsafe {
             descriptor.get(|| {
-                let fields = ::std::vec::Vec::new();
-                ::protobuf::reflect::MessageDescriptor::new::<Info>(
-                    "Info",
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "id",
+                    |m: &RemoveNode| { &m.id },
+                    |m: &mut RemoveNode| { &mut m.id },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<RemoveNode>(
+                    "RemoveNode",
                     fields,
                     file_descriptor_proto()
                 )
@@ -1152,44 +1649,792 @@ impl ::protobuf::Message for Info {
         }
     }
 
-    fn default_instance() -> &'static Info {
-        static mut instance: ::protobuf::lazy::Lazy<Info> = ::protobuf::lazy::Lazy {
+    fn default_instance() -> &'static RemoveNode {
+        static mut instance: ::protobuf::lazy::Lazy<RemoveNode> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const Info,
+            ptr: 0 as *const RemoveNode,
         };
         unsafe {
-            instance.get(Info::new)
+            instance.get(RemoveNode::new)
         }
     }
 }
 
-impl ::protobuf::Clear for Info {
+impl ::protobuf::Clear for RemoveNode {
     fn clear(&mut self) {
+        self.clear_id();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for Info {
+impl ::std::fmt::Debug for RemoveNode {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for Info {
+impl ::protobuf::reflect::ProtobufValue for RemoveNode {
     fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
         ::protobuf::reflect::ProtobufValueRef::Message(self)
     }
 }
 
+#[derive(PartialEq,Clone,Default)]
+pub struct PromoteNode {
+    // message fields
+    pub id: u64,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl PromoteNode {
+    pub fn new() -> PromoteNode {
+        ::std::default::Default::default()
+    }
+
+    // uint64 id = 1;
+
+    pub fn clear_id(&mut self) {
+        self.id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_id(&mut self, v: u64) {
+        self.id = v;
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl ::protobuf::Message for PromoteNode {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.id = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.id != 0 {
+            os.write_uint64(1, self.id)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> PromoteNode {
+        PromoteNode::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "id",
+                    |m: &PromoteNode| { &m.id },
+                    |m: &mut PromoteNode| { &mut m.id },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<PromoteNode>(
+                    "PromoteNode",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static PromoteNode {
+        static mut instance: ::protobuf::lazy::Lazy<PromoteNode> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const PromoteNode,
+        };
+        unsafe {
+            instance.get(PromoteNode::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for PromoteNode {
+    fn clear(&mut self) {
+        self.clear_id();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for PromoteNode {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for PromoteNode {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Info {
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl Info {
+    pub fn new() -> Info {
+        ::std::default::Default::default()
+    }
+}
+
+impl ::protobuf::Message for Info {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Info {
+        Info::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let fields = ::std::vec::Vec::new();
+                ::protobuf::reflect::MessageDescriptor::new::<Info>(
+                    "Info",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static Info {
+        static mut instance: ::protobuf::lazy::Lazy<Info> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const Info,
+        };
+        unsafe {
+            instance.get(Info::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for Info {
+    fn clear(&mut self) {
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Info {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Info {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct TransferLeader {
+    // message fields
+    pub target_id: u64,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl TransferLeader {
+    pub fn new() -> TransferLeader {
+        ::std::default::Default::default()
+    }
+
+    // uint64 target_id = 1;
+
+    pub fn clear_target_id(&mut self) {
+        self.target_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_target_id(&mut self, v: u64) {
+        self.target_id = v;
+    }
+
+    pub fn get_target_id(&self) -> u64 {
+        self.target_id
+    }
+}
+
+impl ::protobuf::Message for TransferLeader {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.target_id = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.target_id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.target_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.target_id != 0 {
+            os.write_uint64(1, self.target_id)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> TransferLeader {
+        TransferLeader::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "target_id",
+                    |m: &TransferLeader| { &m.target_id },
+                    |m: &mut TransferLeader| { &mut m.target_id },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<TransferLeader>(
+                    "TransferLeader",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static TransferLeader {
+        static mut instance: ::protobuf::lazy::Lazy<TransferLeader> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const TransferLeader,
+        };
+        unsafe {
+            instance.get(TransferLeader::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for TransferLeader {
+    fn clear(&mut self) {
+        self.clear_target_id();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for TransferLeader {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for TransferLeader {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct UpdatePeerAddress {
+    // message fields
+    pub id: u64,
+    pub addr: ::std::string::String,
+    pub public_addr: ::std::string::String,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl UpdatePeerAddress {
+    pub fn new() -> UpdatePeerAddress {
+        ::std::default::Default::default()
+    }
+
+    // uint64 id = 1;
+
+    pub fn clear_id(&mut self) {
+        self.id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_id(&mut self, v: u64) {
+        self.id = v;
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    // string addr = 2;
+
+    pub fn clear_addr(&mut self) {
+        self.addr.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_addr(&mut self, v: ::std::string::String) {
+        self.addr = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_addr(&mut self) -> &mut ::std::string::String {
+        &mut self.addr
+    }
+
+    // Take field
+    pub fn take_addr(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.addr, ::std::string::String::new())
+    }
+
+    pub fn get_addr(&self) -> &str {
+        &self.addr
+    }
+
+    // string public_addr = 3;
+
+    pub fn clear_public_addr(&mut self) {
+        self.public_addr.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_public_addr(&mut self, v: ::std::string::String) {
+        self.public_addr = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_public_addr(&mut self) -> &mut ::std::string::String {
+        &mut self.public_addr
+    }
+
+    // Take field
+    pub fn take_public_addr(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.public_addr, ::std::string::String::new())
+    }
+
+    pub fn get_public_addr(&self) -> &str {
+        &self.public_addr
+    }
+}
+
+impl ::protobuf::Message for UpdatePeerAddress {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.id = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.addr)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.public_addr)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.addr.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.addr);
+        }
+        if !self.public_addr.is_empty() {
+            my_size += ::protobuf::rt::string_size(3, &self.public_addr);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.id != 0 {
+            os.write_uint64(1, self.id)?;
+        }
+        if !self.addr.is_empty() {
+            os.write_string(2, &self.addr)?;
+        }
+        if !self.public_addr.is_empty() {
+            os.write_string(3, &self.public_addr)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> UpdatePeerAddress {
+        UpdatePeerAddress::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "id",
+                    |m: &UpdatePeerAddress| { &m.id },
+                    |m: &mut UpdatePeerAddress| { &mut m.id },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "addr",
+                    |m: &UpdatePeerAddress| { &m.addr },
+                    |m: &mut UpdatePeerAddress| { &mut m.addr },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "public_addr",
+                    |m: &UpdatePeerAddress| { &m.public_addr },
+                    |m: &mut UpdatePeerAddress| { &mut m.public_addr },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<UpdatePeerAddress>(
+                    "UpdatePeerAddress",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static UpdatePeerAddress {
+        static mut instance: ::protobuf::lazy::Lazy<UpdatePeerAddress> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const UpdatePeerAddress,
+        };
+        unsafe {
+            instance.get(UpdatePeerAddress::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for UpdatePeerAddress {
+    fn clear(&mut self) {
+        self.clear_id();
+        self.clear_addr();
+        self.clear_public_addr();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for UpdatePeerAddress {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for UpdatePeerAddress {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum Consistency {
+    LINEARIZABLE = 0,
+    LEASE = 1,
+    STALE = 2,
+}
+
+impl ::protobuf::ProtobufEnum for Consistency {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<Consistency> {
+        match value {
+            0 => ::std::option::Option::Some(Consistency::LINEARIZABLE),
+            1 => ::std::option::Option::Some(Consistency::LEASE),
+            2 => ::std::option::Option::Some(Consistency::STALE),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [Consistency] = &[
+            Consistency::LINEARIZABLE,
+            Consistency::LEASE,
+            Consistency::STALE,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::EnumDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::EnumDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                ::protobuf::reflect::EnumDescriptor::new("Consistency", file_descriptor_proto())
+            })
+        }
+    }
+}
+
+impl ::std::marker::Copy for Consistency {
+}
+
+impl ::std::default::Default for Consistency {
+    fn default() -> Self {
+        Consistency::LINEARIZABLE
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Consistency {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Enum(self.descriptor())
+    }
+}
+
 static file_descriptor_proto_data: &'static [u8] = b"\
-    \n\rrequest.proto\x12\x0epublic.request\"\x17\n\x03Get\x12\x10\n\x03key\
-    \x18\x01\x20\x01(\tR\x03key\"-\n\x03Set\x12\x10\n\x03key\x18\x01\x20\x01\
-    (\tR\x03key\x12\x14\n\x05value\x18\x02\x20\x01(\tR\x05value\"\x1a\n\x06D\
-    elete\x12\x10\n\x03key\x18\x01\x20\x01(\tR\x03key\"\x06\n\x04Scan\"L\n\
-    \x07AddNode\x12\x0e\n\x02id\x18\x01\x20\x01(\x04R\x02id\x12\x12\n\x04add\
-    r\x18\x02\x20\x01(\tR\x04addr\x12\x1d\n\nis_learner\x18\x03\x20\x01(\x08\
-    R\tisLearner\"\x1c\n\nRemoveNode\x12\x0e\n\x02id\x18\x01\x20\x01(\x04R\
-    \x02id\"\x06\n\x04Infob\x06proto3\
+    \n\rrequest.proto\x12\x0epublic.request\"o\n\x03Get\x12\x10\n\x03key\x18\
+    \x01\x20\x01(\tR\x03key\x12=\n\x0bconsistency\x18\x02\x20\x01(\x0e2\x1b.\
+    public.request.ConsistencyR\x0bconsistency\x12\x17\n\x07max_lag\x18\x03\
+    \x20\x01(\x04R\x06maxLag\"-\n\x03Set\x12\x10\n\x03key\x18\x01\x20\x01(\t\
+    R\x03key\x12\x14\n\x05value\x18\x02\x20\x01(\tR\x05value\"\x1a\n\x06Dele\
+    te\x12\x10\n\x03key\x18\x01\x20\x01(\tR\x03key\"3\n\x08MultiSet\x12'\n\
+    \x04sets\x18\x01\x20\x03(\x0b2\x13.public.request.SetR\x04sets\"\x8b\x02\
+    \n\x04Scan\x12\x14\n\x05start\x18\x01\x20\x01(\tR\x05start\x12\x10\n\x03\
+    end\x18\x02\x20\x01(\tR\x03end\x12\x16\n\x06prefix\x18\x03\x20\x01(\tR\
+    \x06prefix\x12\x14\n\x05limit\x18\x04\x20\x01(\x04R\x05limit\x12\x18\n\
+    \x07reverse\x18\x05\x20\x01(\x08R\x07reverse\x12%\n\x0einclude_values\
+    \x18\x06\x20\x01(\x08R\rincludeValues\x12\x14\n\x05token\x18\x07\x20\x01\
+    (\tR\x05token\x12=\n\x0bconsistency\x18\x08\x20\x01(\x0e2\x1b.public.req\
+    uest.ConsistencyR\x0bconsistency\x12\x17\n\x07max_lag\x18\t\x20\x01(\x04\
+    R\x06maxLag\"m\n\x07AddNode\x12\x0e\n\x02id\x18\x01\x20\x01(\x04R\x02id\
+    \x12\x12\n\x04addr\x18\x02\x20\x01(\tR\x04addr\x12\x1d\n\nis_learner\x18\
+    \x03\x20\x01(\x08R\tisLearner\x12\x1f\n\x0bpublic_addr\x18\x04\x20\x01(\
+    \tR\npublicAddr\"\x1c\n\nRemoveNode\x12\x0e\n\x02id\x18\x01\x20\x01(\x04\
+    R\x02id\"\x1d\n\x0bPromoteNode\x12\x0e\n\x02id\x18\x01\x20\x01(\x04R\x02\
+    id\"\x06\n\x04Info\"-\n\x0eTransferLeader\x12\x1b\n\ttarget_id\x18\x01\
+    \x20\x01(\x04R\x08targetId\"X\n\x11UpdatePeerAddress\x12\x0e\n\x02id\x18\
+    \x01\x20\x01(\x04R\x02id\x12\x12\n\x04addr\x18\x02\x20\x01(\tR\x04addr\
+    \x12\x1f\n\x0bpublic_addr\x18\x03\x20\x01(\tR\npublicAddr*5\n\x0bConsist\
+    ency\x12\x10\n\x0cLINEARIZABLE\x10\0\x12\t\n\x05LEASE\x10\x01\x12\t\n\
+    \x05STALE\x10\x02b\x06proto3\
 ";
 
 static mut file_descriptor_proto_lazy: ::protobuf::lazy::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::lazy::Lazy {
@@ -421,6 +421,8 @@ impl ::protobuf::reflect::ProtobufValue for Delete {
 pub struct Scan {
     // message fields
     pub keys: ::protobuf::RepeatedField<::std::string::String>,
+    pub values: ::protobuf::RepeatedField<::std::string::String>,
+    pub token: ::std::string::String,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::protobuf::CachedSize,
@@ -455,6 +457,57 @@ impl Scan {
     pub fn get_keys(&self) -> &[::std::string::String] {
         &self.keys
     }
+
+    // repeated string values = 2;
+
+    pub fn clear_values(&mut self) {
+        self.values.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_values(&mut self, v: ::protobuf::RepeatedField<::std::string::String>) {
+        self.values = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_values(&mut self) -> &mut ::protobuf::RepeatedField<::std::string::String> {
+        &mut self.values
+    }
+
+    // Take field
+    pub fn take_values(&mut self) -> ::protobuf::RepeatedField<::std::string::String> {
+        ::std::mem::replace(&mut self.values, ::protobuf::RepeatedField::new())
+    }
+
+    pub fn get_values(&self) -> &[::std::string::String] {
+        &self.values
+    }
+
+    // string token = 3;
+
+    pub fn clear_token(&mut self) {
+        self.token.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_token(&mut self, v: ::std::string::String) {
+        self.token = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_token(&mut self) -> &mut ::std::string::String {
+        &mut self.token
+    }
+
+    // Take field
+    pub fn take_token(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.token, ::std::string::String::new())
+    }
+
+    pub fn get_token(&self) -> &str {
+        &self.token
+    }
 }
 
 impl ::protobuf::Message for Scan {
@@ -469,6 +522,12 @@ impl ::protobuf::Message for Scan {
                 1 => {
                     ::protobuf::rt::read_repeated_string_into(wire_type, is, &mut self.keys)?;
                 },
+                2 => {
+                    ::protobuf::rt::read_repeated_string_into(wire_type, is, &mut self.values)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.token)?;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -484,6 +543,12 @@ impl ::protobuf::Message for Scan {
         for value in &self.keys {
             my_size += ::protobuf::rt::string_size(1, &value);
         };
+        for value in &self.values {
+            my_size += ::protobuf::rt::string_size(2, &value);
+        };
+        if !self.token.is_empty() {
+            my_size += ::protobuf::rt::string_size(3, &self.token);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -493,6 +558,12 @@ impl ::protobuf::Message for Scan {
         for v in &self.keys {
             os.write_string(1, &v)?;
         };
+        for v in &self.values {
+            os.write_string(2, &v)?;
+        };
+        if !self.token.is_empty() {
+            os.write_string(3, &self.token)?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -540,6 +611,16 @@ impl ::protobuf::Message for Scan {
                     |m: &Scan| { &m.keys },
                     |m: &mut Scan| { &mut m.keys },
                 ));
+                fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "values",
+                    |m: &Scan| { &m.values },
+                    |m: &mut Scan| { &mut m.values },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "token",
+                    |m: &Scan| { &m.token },
+                    |m: &mut Scan| { &mut m.token },
+                ));
                 ::protobuf::reflect::MessageDescriptor::new::<Scan>(
                     "Scan",
                     fields,
@@ -563,6 +644,8 @@ impl ::protobuf::Message for Scan {
 impl ::protobuf::Clear for Scan {
     fn clear(&mut self) {
         self.clear_keys();
+        self.clear_values();
+        self.clear_token();
         self.unknown_fields.clear();
     }
 }
@@ -587,6 +670,7 @@ pub struct Info {
     pub term: u64,
     pub applied: u64,
     pub peers: ::std::vec::Vec<u64>,
+    pub learners: ::std::vec::Vec<u64>,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::protobuf::CachedSize,
@@ -681,6 +765,31 @@ impl Info {
     pub fn get_peers(&self) -> &[u64] {
         &self.peers
     }
+
+    // repeated uint64 learners = 6;
+
+    pub fn clear_learners(&mut self) {
+        self.learners.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_learners(&mut self, v: ::std::vec::Vec<u64>) {
+        self.learners = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_learners(&mut self) -> &mut ::std::vec::Vec<u64> {
+        &mut self.learners
+    }
+
+    // Take field
+    pub fn take_learners(&mut self) -> ::std::vec::Vec<u64> {
+        ::std::mem::replace(&mut self.learners, ::std::vec::Vec::new())
+    }
+
+    pub fn get_learners(&self) -> &[u64] {
+        &self.learners
+    }
 }
 
 impl ::protobuf::Message for Info {
@@ -723,6 +832,9 @@ impl ::protobuf::Message for Info {
                 5 => {
                     ::protobuf::rt::read_repeated_uint64_into(wire_type, is, &mut self.peers)?;
                 },
+                6 => {
+                    ::protobuf::rt::read_repeated_uint64_into(wire_type, is, &mut self.learners)?;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -750,6 +862,9 @@ impl ::protobuf::Message for Info {
         for value in &self.peers {
             my_size += ::protobuf::rt::value_size(5, *value, ::protobuf::wire_format::WireTypeVarint);
         };
+        for value in &self.learners {
+            my_size += ::protobuf::rt::value_size(6, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -771,6 +886,9 @@ impl ::protobuf::Message for Info {
         for v in &self.peers {
             os.write_uint64(5, *v)?;
         };
+        for v in &self.learners {
+            os.write_uint64(6, *v)?;
+        };
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -838,6 +956,11 @@ impl ::protobuf::Message for Info {
                     |m: &Info| { &m.peers },
                     |m: &mut Info| { &mut m.peers },
                 ));
+                fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "learners",
+                    |m: &Info| { &m.learners },
+                    |m: &mut Info| { &mut m.learners },
+                ));
                 ::protobuf::reflect::MessageDescriptor::new::<Info>(
                     "Info",
                     fields,
@@ -865,6 +988,7 @@ impl ::protobuf::Clear for Info {
         self.clear_term();
         self.clear_applied();
         self.clear_peers();
+        self.clear_learners();
         self.unknown_fields.clear();
     }
 }
@@ -881,16 +1005,373 @@ impl ::protobuf::reflect::ProtobufValue for Info {
     }
 }
 
+#[derive(PartialEq,Clone,Default)]
+pub struct Error {
+    // message fields
+    pub code: ErrorCode,
+    pub message: ::std::string::String,
+    pub leader_id: u64,
+    pub leader_addr: ::std::string::String,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl Error {
+    pub fn new() -> Error {
+        ::std::default::Default::default()
+    }
+
+    // .public.response.ErrorCode code = 1;
+
+    pub fn clear_code(&mut self) {
+        self.code = ErrorCode::UNKNOWN;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_code(&mut self, v: ErrorCode) {
+        self.code = v;
+    }
+
+    pub fn get_code(&self) -> ErrorCode {
+        self.code
+    }
+
+    // string message = 2;
+
+    pub fn clear_message(&mut self) {
+        self.message.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_message(&mut self, v: ::std::string::String) {
+        self.message = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_message(&mut self) -> &mut ::std::string::String {
+        &mut self.message
+    }
+
+    // Take field
+    pub fn take_message(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.message, ::std::string::String::new())
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    // uint64 leader_id = 3;
+
+    pub fn clear_leader_id(&mut self) {
+        self.leader_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_leader_id(&mut self, v: u64) {
+        self.leader_id = v;
+    }
+
+    pub fn get_leader_id(&self) -> u64 {
+        self.leader_id
+    }
+
+    // string leader_addr = 4;
+
+    pub fn clear_leader_addr(&mut self) {
+        self.leader_addr.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_leader_addr(&mut self, v: ::std::string::String) {
+        self.leader_addr = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_leader_addr(&mut self) -> &mut ::std::string::String {
+        &mut self.leader_addr
+    }
+
+    // Take field
+    pub fn take_leader_addr(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.leader_addr, ::std::string::String::new())
+    }
+
+    pub fn get_leader_addr(&self) -> &str {
+        &self.leader_addr
+    }
+}
+
+impl ::protobuf::Message for Error {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.code, 1, &mut self.unknown_fields)?
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.message)?;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.leader_id = tmp;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.leader_addr)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.code != ErrorCode::UNKNOWN {
+            my_size += ::protobuf::rt::enum_size(1, self.code);
+        }
+        if !self.message.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.message);
+        }
+        if self.leader_id != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.leader_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.leader_addr.is_empty() {
+            my_size += ::protobuf::rt::string_size(4, &self.leader_addr);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.code != ErrorCode::UNKNOWN {
+            os.write_enum(1, self.code.value())?;
+        }
+        if !self.message.is_empty() {
+            os.write_string(2, &self.message)?;
+        }
+        if self.leader_id != 0 {
+            os.write_uint64(3, self.leader_id)?;
+        }
+        if !self.leader_addr.is_empty() {
+            os.write_string(4, &self.leader_addr)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Error {
+        Error::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<ErrorCode>>(
+                    "code",
+                    |m: &Error| { &m.code },
+                    |m: &mut Error| { &mut m.code },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "message",
+                    |m: &Error| { &m.message },
+                    |m: &mut Error| { &mut m.message },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "leader_id",
+                    |m: &Error| { &m.leader_id },
+                    |m: &mut Error| { &mut m.leader_id },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "leader_addr",
+                    |m: &Error| { &m.leader_addr },
+                    |m: &mut Error| { &mut m.leader_addr },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<Error>(
+                    "Error",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static Error {
+        static mut instance: ::protobuf::lazy::Lazy<Error> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const Error,
+        };
+        unsafe {
+            instance.get(Error::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for Error {
+    fn clear(&mut self) {
+        self.clear_code();
+        self.clear_message();
+        self.clear_leader_id();
+        self.clear_leader_addr();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Error {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Error {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum ErrorCode {
+    UNKNOWN = 0,
+    NOT_LEADER = 1,
+    TIMEOUT = 2,
+    PROPOSAL_DROPPED = 3,
+    INVALID_REQUEST = 4,
+    SHUTTING_DOWN = 5,
+    LAGGING = 6,
+    LEADER_CHANGED = 7,
+    INCOMPATIBLE = 8,
+}
+
+impl ::protobuf::ProtobufEnum for ErrorCode {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<ErrorCode> {
+        match value {
+            0 => ::std::option::Option::Some(ErrorCode::UNKNOWN),
+            1 => ::std::option::Option::Some(ErrorCode::NOT_LEADER),
+            2 => ::std::option::Option::Some(ErrorCode::TIMEOUT),
+            3 => ::std::option::Option::Some(ErrorCode::PROPOSAL_DROPPED),
+            4 => ::std::option::Option::Some(ErrorCode::INVALID_REQUEST),
+            5 => ::std::option::Option::Some(ErrorCode::SHUTTING_DOWN),
+            6 => ::std::option::Option::Some(ErrorCode::LAGGING),
+            7 => ::std::option::Option::Some(ErrorCode::LEADER_CHANGED),
+            8 => ::std::option::Option::Some(ErrorCode::INCOMPATIBLE),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [ErrorCode] = &[
+            ErrorCode::UNKNOWN,
+            ErrorCode::NOT_LEADER,
+            ErrorCode::TIMEOUT,
+            ErrorCode::PROPOSAL_DROPPED,
+            ErrorCode::INVALID_REQUEST,
+            ErrorCode::SHUTTING_DOWN,
+            ErrorCode::LAGGING,
+            ErrorCode::LEADER_CHANGED,
+            ErrorCode::INCOMPATIBLE,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::EnumDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::EnumDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                ::protobuf::reflect::EnumDescriptor::new("ErrorCode", file_descriptor_proto())
+            })
+        }
+    }
+}
+
+impl ::std::marker::Copy for ErrorCode {
+}
+
+impl ::std::default::Default for ErrorCode {
+    fn default() -> Self {
+        ErrorCode::UNKNOWN
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ErrorCode {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Enum(self.descriptor())
+    }
+}
+
 static file_descriptor_proto_data: &'static [u8] = b"\
     \n\x0eresponse.proto\x12\x0fpublic.response\"6\n\x03Get\x12\x14\n\x05val\
     ue\x18\x01\x20\x01(\tR\x05value\x12\x19\n\x08is_found\x18\x02\x20\x01(\
     \x08R\x07isFound\"9\n\x06Delete\x12\x14\n\x05value\x18\x01\x20\x01(\tR\
-    \x05value\x12\x19\n\x08is_found\x18\x02\x20\x01(\x08R\x07isFound\"\x1a\n\
-    \x04Scan\x12\x12\n\x04keys\x18\x01\x20\x03(\tR\x04keys\"w\n\x04Info\x12\
-    \x0e\n\x02id\x18\x01\x20\x01(\x04R\x02id\x12\x1b\n\tleader_id\x18\x02\
-    \x20\x01(\x04R\x08leaderId\x12\x12\n\x04term\x18\x03\x20\x01(\x04R\x04te\
-    rm\x12\x18\n\x07applied\x18\x04\x20\x01(\x04R\x07applied\x12\x14\n\x05pe\
-    ers\x18\x05\x20\x03(\x04R\x05peersb\x06proto3\
+    \x05value\x12\x19\n\x08is_found\x18\x02\x20\x01(\x08R\x07isFound\"H\n\
+    \x04Scan\x12\x12\n\x04keys\x18\x01\x20\x03(\tR\x04keys\x12\x16\n\x06valu\
+    es\x18\x02\x20\x03(\tR\x06values\x12\x14\n\x05token\x18\x03\x20\x01(\tR\
+    \x05token\"\x93\x01\n\x04Info\x12\x0e\n\x02id\x18\x01\x20\x01(\x04R\x02i\
+    d\x12\x1b\n\tleader_id\x18\x02\x20\x01(\x04R\x08leaderId\x12\x12\n\x04te\
+    rm\x18\x03\x20\x01(\x04R\x04term\x12\x18\n\x07applied\x18\x04\x20\x01(\
+    \x04R\x07applied\x12\x14\n\x05peers\x18\x05\x20\x03(\x04R\x05peers\x12\
+    \x1a\n\x08learners\x18\x06\x20\x03(\x04R\x08learners\"\x8f\x01\n\x05Erro\
+    r\x12.\n\x04code\x18\x01\x20\x01(\x0e2\x1a.public.response.ErrorCodeR\
+    \x04code\x12\x18\n\x07message\x18\x02\x20\x01(\tR\x07message\x12\x1b\n\t\
+    leader_id\x18\x03\x20\x01(\x04R\x08leaderId\x12\x1f\n\x0bleader_addr\x18\
+    \x04\x20\x01(\tR\nleaderAddr*\xa6\x01\n\tErrorCode\x12\x0b\n\x07UNKNOWN\
+    \x10\0\x12\x0e\n\nNOT_LEADER\x10\x01\x12\x0b\n\x07TIMEOUT\x10\x02\x12\
+    \x14\n\x10PROPOSAL_DROPPED\x10\x03\x12\x13\n\x0fINVALID_REQUEST\x10\x04\
+    \x12\x11\n\rSHUTTING_DOWN\x10\x05\x12\x0b\n\x07LAGGING\x10\x06\x12\x12\n\
+    \x0eLEADER_CHANGED\x10\x07\x12\x10\n\x0cINCOMPATIBLE\x10\x08b\x06proto3\
 ";
 
 static mut file_descriptor_proto_lazy: ::protobuf::lazy::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::lazy::Lazy {
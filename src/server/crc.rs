@@ -0,0 +1,65 @@
+//! A small CRC-32 (IEEE) implementation. It's used to detect torn or
+//! corrupted records in the files the server writes to disk.
+
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+pub struct Crc32 {
+    table: [u32; 256],
+    value: u32,
+}
+
+impl Crc32 {
+    pub fn new() -> Crc32 {
+        let mut table = [0u32; 256];
+        for (n, slot) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 == 1 {
+                    POLYNOMIAL ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            *slot = c;
+        }
+
+        Crc32 { table, value: !0 }
+    }
+
+    pub fn update(&mut self, buf: &[u8]) {
+        for byte in buf {
+            let idx = ((self.value ^ u32::from(*byte)) & 0xFF) as usize;
+            self.value = self.table[idx] ^ (self.value >> 8);
+        }
+    }
+
+    pub fn finish(&self) -> u32 {
+        !self.value
+    }
+}
+
+/// Checksums a single buffer.
+pub fn checksum(buf: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(buf);
+    crc.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_values() {
+        assert_eq!(checksum(b""), 0);
+        assert_eq!(checksum(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_incremental_matches_whole() {
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finish(), checksum(b"123456789"));
+    }
+}
@@ -1,10 +1,15 @@
-use super::{network, proto, public::Command, storage::KeyValue, Message};
+use super::machine::StateMachine;
+use super::storage::{self, KeyValue, KeyValueCore};
+use super::transport::{self, Transport};
+use super::{proto, public::Command, Bootstrap, Message};
+use bytes::{Buf, BufMut, IntoBuf};
 use futures::sync::mpsc;
-use futures::Stream;
+use futures::{Future, Sink, Stream};
 use protobuf::parse_from_bytes;
-use public;
-use raft::{self, prelude::*};
-use std::collections::HashMap;
+use public::{self, request::Consistency, response::ErrorCode};
+use raft::raw_node::Peer as RaftPeer;
+use raft::{self, prelude::*, StateRole};
+use std::collections::{HashMap, HashSet};
 use std::num::Wrapping;
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
@@ -21,70 +26,162 @@ impl Handle {
         self.handle.join().expect("Failed to join db server");
     }
 
+    /// Asks the db to stop. It hands off leadership first if it can, so
+    /// `join` may wait a moment longer.
+    pub fn stop(&self) {
+        if self.tx.clone().send(Message::Stop).wait().is_err() {
+            println!("The db has already stopped");
+        }
+    }
+
     pub fn channel(&self) -> mpsc::Sender<Message> {
         self.tx.clone()
     }
 }
 
+/// How long a proposal may wait to be applied before it's failed. It's
+/// under `FORWARD_TIMEOUT` so a forwarded write hears why it failed.
+const PROPOSAL_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// A command waiting for its entry to be applied.
+struct Pending {
+    command: Command,
+    deadline: Instant,
+    /// The index and term the entry was proposed at, once it's in the log.
+    proposed: Option<(u64, u64)>,
+}
+
 struct Callbacks {
-    commands: HashMap<Wrapping<u64>, Command>,
+    commands: HashMap<Wrapping<u64>, Pending>,
     curr_id: Wrapping<u64>,
+    /// This node's id, entries are tagged with it so that only this node
+    /// looks up their ids.
+    node: u64,
 }
 
 impl Callbacks {
-    fn new() -> Callbacks {
+    fn new(node: u64) -> Callbacks {
         Callbacks {
             commands: HashMap::new(),
             curr_id: Wrapping(0),
+            node,
         }
     }
 
-    fn store_delete(&mut self, command: Command) -> proto::Entry {
-        self.curr_id += Wrapping(1);
+    /// Stores the command and builds an entry that carries its request
+    /// through the log to the state machine.
+    fn store_request(&mut self, command: Command) -> proto::Entry {
+        use protobuf::Message;
 
-        let key = {
-            let delete = command.request().get_delete();
-            delete.get_key().to_string()
-        };
+        let request = command
+            .request()
+            .write_to_bytes()
+            .expect("Request should serialize");
 
-        self.commands.insert(self.curr_id, command);
+        let mut entry = self.store(command);
+        entry.set_request(request);
+        entry.set_kind(proto::EntryKind::REQUEST);
+        entry
+    }
 
+    fn store(&mut self, command: Command) -> proto::Entry {
+        self.curr_id += Wrapping(1);
+        self.commands.insert(
+            self.curr_id,
+            Pending {
+                command,
+                deadline: Instant::now() + PROPOSAL_TIMEOUT,
+                proposed: None,
+            },
+        );
         let mut entry = proto::Entry::new();
         entry.set_id(self.curr_id.0);
-        entry.set_key(key);
-        entry.set_kind(proto::EntryKind::DELETE);
+        entry.set_node(self.node);
+        entry.set_kind(proto::EntryKind::CALLBACK);
         entry
     }
 
-    fn store_set(&mut self, command: Command) -> proto::Entry {
-        self.curr_id += Wrapping(1);
+    /// Records where raft put the entry for a stored command.
+    fn proposed(&mut self, id: u64, index: u64, term: u64) {
+        if let Some(pending) = self.commands.get_mut(&Wrapping(id)) {
+            pending.proposed = Some((index, term));
+        }
+    }
 
-        let (key, value) = {
-            let set = command.request().get_set();
-            (set.get_key().to_string(), set.get_value().to_string())
-        };
+    fn get(&mut self, id: u64) -> Option<Command> {
+        self.commands
+            .remove(&Wrapping(id))
+            .map(|pending| pending.command)
+    }
 
-        self.commands.insert(self.curr_id, command);
+    fn drain(&mut self) -> Vec<Command> {
+        self.commands
+            .drain()
+            .map(|(_, pending)| pending.command)
+            .collect()
+    }
 
-        let mut entry = proto::Entry::new();
-        entry.set_id(self.curr_id.0);
-        entry.set_key(key);
-        entry.set_value(value);
-        entry.set_kind(proto::EntryKind::SET);
-        entry
+    /// Takes the command for an entry applied at `index` in `term`, if this
+    /// node proposed it there. Ids start over when a node restarts, so an
+    /// entry from before then may carry the id of a newer command.
+    fn applied(&mut self, entry: &proto::Entry, index: u64, term: u64) -> Option<Command> {
+        if entry.get_node() != self.node {
+            return None;
+        }
+
+        let matches = self.commands
+            .get(&Wrapping(entry.get_id()))
+            .map_or(false, |pending| pending.proposed == Some((index, term)));
+        if matches {
+            self.get(entry.get_id())
+        } else {
+            None
+        }
     }
 
-    fn store(&mut self, command: Command) -> proto::Entry {
-        self.curr_id += Wrapping(1);
-        self.commands.insert(self.curr_id, command);
-        let mut entry = proto::Entry::new();
-        entry.set_id(self.curr_id.0);
-        entry.set_kind(proto::EntryKind::CALLBACK);
-        entry
+    /// Takes the commands whose entries were overwritten. Once everything up
+    /// to `applied` has been applied, any command proposed at or before it
+    /// that's still waiting lost its place in the log to another entry.
+    fn overwritten(&mut self, applied: u64) -> Vec<Pending> {
+        self.take_pending_where(|pending| {
+            pending
+                .proposed
+                .map_or(false, |(index, _)| index <= applied)
+        })
     }
 
-    fn get(&mut self, id: u64) -> Option<Command> {
-        self.commands.remove(&Wrapping(id))
+    /// Takes the commands proposed in a term before `term`.
+    fn before_term(&mut self, term: u64) -> Vec<Command> {
+        self.take_where(|pending| pending.proposed.map_or(false, |(_, t)| t < term))
+    }
+
+    /// Takes the commands that have waited past their deadline.
+    fn expire(&mut self, now: Instant) -> Vec<Command> {
+        self.take_where(|pending| pending.deadline <= now)
+    }
+
+    fn take_where<F>(&mut self, stale: F) -> Vec<Command>
+    where
+        F: Fn(&Pending) -> bool,
+    {
+        self.take_pending_where(stale)
+            .into_iter()
+            .map(|pending| pending.command)
+            .collect()
+    }
+
+    fn take_pending_where<F>(&mut self, stale: F) -> Vec<Pending>
+    where
+        F: Fn(&Pending) -> bool,
+    {
+        let ids: Vec<Wrapping<u64>> = self.commands
+            .iter()
+            .filter(|&(_, pending)| stale(pending))
+            .map(|(id, _)| *id)
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| self.commands.remove(&id))
+            .collect()
     }
 }
 
@@ -96,65 +193,495 @@ mod callback_tests {
     fn test_set_command() {
         let (tx, _) = mpsc::channel(1024);
         let cmd = Command::new(tx, public::set_request("hello", "world"));
-        let mut cbs = Callbacks::new();
-        let entry = cbs.store_set(cmd);
+        let mut cbs = Callbacks::new(1);
+        let entry = cbs.store_request(cmd);
         assert_eq!(entry.id, 1);
+        assert_eq!(
+            parse_from_bytes::<public::Request>(&entry.request).unwrap(),
+            public::set_request("hello", "world")
+        );
         let cmd = cbs.get(entry.id).unwrap();
 
-        let entry = cbs.store_set(cmd);
+        let entry = cbs.store_request(cmd);
         assert_eq!(entry.id, 2);
+
+        // Another node's entry with the same id isn't ours to answer
+        cbs.proposed(entry.id, 5, 1);
+        let mut other = entry.clone();
+        other.set_node(2);
+        assert!(cbs.applied(&other, 5, 1).is_none());
+        // Nor is our own from another index or term
+        assert!(cbs.applied(&entry, 4, 1).is_none());
+        assert!(cbs.applied(&entry, 5, 2).is_none());
+        assert!(cbs.applied(&entry, 5, 1).is_some());
     }
 
     #[test]
     fn test_delete_command() {
         let (tx, _) = mpsc::channel(1024);
         let cmd = Command::new(tx, public::delete_request("hello"));
-        let mut cbs = Callbacks::new();
-        let entry = cbs.store_delete(cmd);
+        let mut cbs = Callbacks::new(1);
+        let entry = cbs.store_request(cmd);
         assert_eq!(entry.id, 1);
+        assert_eq!(
+            parse_from_bytes::<public::Request>(&entry.request).unwrap(),
+            public::delete_request("hello")
+        );
         let cmd = cbs.get(entry.id).unwrap();
 
-        let entry = cbs.store_delete(cmd);
+        let entry = cbs.store_request(cmd);
         assert_eq!(entry.id, 2);
     }
+
+    #[test]
+    fn test_stale_commands_are_taken() {
+        let (tx, _) = mpsc::channel(1024);
+        let mut cbs = Callbacks::new(1);
+        for &(index, term) in &[(3, 1), (4, 2), (5, 2), (6, 3)] {
+            let entry = cbs.store(Command::new(tx.clone(), public::set_request("a", "1")));
+            cbs.proposed(entry.id, index, term);
+        }
+
+        assert_eq!(cbs.before_term(2).len(), 1);
+        assert_eq!(cbs.overwritten(4).len(), 1);
+        assert!(cbs.expire(Instant::now()).is_empty());
+        assert_eq!(cbs.expire(Instant::now() + PROPOSAL_TIMEOUT).len(), 2);
+        assert!(cbs.commands.is_empty());
+    }
+}
+
+/// How long a write forwarded to the leader may wait for its reply.
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Writes this node forwarded to the leader, waiting for the leader's reply.
+struct Forwards {
+    pending: HashMap<u64, (Command, Instant)>,
+    curr_id: Wrapping<u64>,
+}
+
+impl Forwards {
+    fn new() -> Forwards {
+        Forwards {
+            pending: HashMap::new(),
+            curr_id: Wrapping(0),
+        }
+    }
+
+    /// Stores the command and returns the id to forward it with.
+    fn wait(&mut self, command: Command, now: Instant) -> u64 {
+        self.curr_id += Wrapping(1);
+        self.pending
+            .insert(self.curr_id.0, (command, now + FORWARD_TIMEOUT));
+        self.curr_id.0
+    }
+
+    fn take(&mut self, id: u64) -> Option<Command> {
+        self.pending.remove(&id).map(|(command, _)| command)
+    }
+
+    fn drain(&mut self) -> Vec<Command> {
+        self.pending.drain().map(|(_, (command, _))| command).collect()
+    }
+
+    /// Takes the writes whose reply never arrived in time.
+    fn expire(&mut self, now: Instant) -> Vec<Command> {
+        let expired: Vec<u64> = self.pending
+            .iter()
+            .filter(|&(_, &(_, deadline))| deadline <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        expired.into_iter().filter_map(|id| self.take(id)).collect()
+    }
+}
+
+#[cfg(test)]
+mod forward_tests {
+    use super::*;
+
+    #[test]
+    fn test_forwards_are_answered_or_expire() {
+        let (tx, _) = mpsc::channel(1024);
+        let now = Instant::now();
+        let mut forwards = Forwards::new();
+
+        let answered = forwards.wait(Command::new(tx.clone(), public::set_request("a", "1")), now);
+        let lost = forwards.wait(Command::new(tx, public::set_request("b", "2")), now);
+        assert!(forwards.take(answered).is_some());
+        assert!(forwards.take(answered).is_none());
+
+        assert!(forwards.expire(now).is_empty());
+        assert_eq!(forwards.expire(now + FORWARD_TIMEOUT).len(), 1);
+        assert!(forwards.take(lost).is_none());
+    }
+}
+
+/// How long a leadership transfer may take before it's reported as failed.
+/// Raft gives up on a transfer after an election timeout, so this is well
+/// past that but under `FORWARD_TIMEOUT`.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Requests to transfer leadership, waiting for their target to lead.
+struct Transfers {
+    pending: Vec<(u64, Command, Instant)>,
+}
+
+impl Transfers {
+    fn new() -> Transfers {
+        Transfers { pending: vec![] }
+    }
+
+    fn wait(&mut self, target: u64, command: Command, now: Instant) {
+        self.pending.push((target, command, now + TRANSFER_TIMEOUT));
+    }
+
+    /// Takes the transfers to `leader`, which are done now that it leads.
+    fn done(&mut self, leader: u64) -> Vec<Command> {
+        let (done, pending): (Vec<_>, Vec<_>) = self.pending
+            .drain(..)
+            .partition(|&(target, _, _)| target == leader);
+        self.pending = pending;
+        done.into_iter().map(|(_, command, _)| command).collect()
+    }
+
+    fn drain(&mut self) -> Vec<Command> {
+        self.pending
+            .drain(..)
+            .map(|(_, command, _)| command)
+            .collect()
+    }
+
+    /// Takes the transfers that haven't finished in time, along with their
+    /// targets.
+    fn expire(&mut self, now: Instant) -> Vec<(u64, Command)> {
+        let (expired, pending): (Vec<_>, Vec<_>) = self.pending
+            .drain(..)
+            .partition(|&(_, _, deadline)| deadline <= now);
+        self.pending = pending;
+        expired
+            .into_iter()
+            .map(|(target, command, _)| (target, command))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod transfer_tests {
+    use super::*;
+
+    #[test]
+    fn test_transfers_finish_or_expire() {
+        let (tx, _) = mpsc::channel(1024);
+        let now = Instant::now();
+        let mut transfers = Transfers::new();
+
+        let request = public::transfer_leader_request;
+        transfers.wait(2, Command::new(tx.clone(), request(2)), now);
+        transfers.wait(3, Command::new(tx, request(3)), now);
+        assert!(transfers.done(1).is_empty());
+        assert_eq!(transfers.done(2).len(), 1);
+
+        assert!(transfers.expire(now).is_empty());
+        let expired = transfers.expire(now + TRANSFER_TIMEOUT);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, 3);
+        assert!(transfers.drain().is_empty());
+    }
+}
+
+/// How long a read may wait for raft to confirm it before it's asked for
+/// again. Raft drops a read index request when there's no leader to ask.
+const READ_RETRY: Duration = Duration::from_millis(500);
+/// How long a read may wait in all before it's failed.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct PendingRead {
+    command: Command,
+    issued: Instant,
+    deadline: Instant,
+}
+
+/// Reads wait here for raft to confirm them with a read index, and then for
+/// that index to be applied, so they never see older state than a write
+/// that was already acknowledged.
+struct Reads {
+    waiting: HashMap<u64, PendingRead>,
+    confirmed: Vec<(u64, Command)>,
+    curr_id: Wrapping<u64>,
+}
+
+impl Reads {
+    fn new() -> Reads {
+        Reads {
+            waiting: HashMap::new(),
+            confirmed: Vec::new(),
+            curr_id: Wrapping(0),
+        }
+    }
+
+    /// Stores the command and returns the context to ask raft for a read
+    /// index with.
+    fn wait(&mut self, command: Command, now: Instant) -> Vec<u8> {
+        self.curr_id += Wrapping(1);
+        self.waiting.insert(
+            self.curr_id.0,
+            PendingRead {
+                command,
+                issued: now,
+                deadline: now + READ_TIMEOUT,
+            },
+        );
+        read_context(self.curr_id.0)
+    }
+
+    /// Moves a read raft confirmed at `index` along to wait for the index to
+    /// be applied.
+    fn confirm(&mut self, ctx: &[u8], index: u64) {
+        if ctx.len() != 8 {
+            return;
+        }
+        let id = ctx.into_buf().get_u64_be();
+        if let Some(read) = self.waiting.remove(&id) {
+            self.confirmed.push((index, read.command));
+        }
+    }
+
+    /// Takes the reads whose index has been applied.
+    fn ready(&mut self, applied: u64) -> Vec<Command> {
+        let (ready, confirmed): (Vec<_>, Vec<_>) = self.confirmed
+            .drain(..)
+            .partition(|&(index, _)| index <= applied);
+        self.confirmed = confirmed;
+        ready.into_iter().map(|(_, command)| command).collect()
+    }
+
+    fn drain(&mut self) -> Vec<Command> {
+        let mut commands: Vec<Command> = self.waiting
+            .drain()
+            .map(|(_, read)| read.command)
+            .collect();
+        commands.extend(self.confirmed.drain(..).map(|(_, command)| command));
+        commands
+    }
+
+    /// Takes the reads that have waited past their deadline and returns them
+    /// along with the contexts of reads to ask raft about again.
+    fn expire(&mut self, now: Instant) -> (Vec<Command>, Vec<Vec<u8>>) {
+        let expired: Vec<u64> = self.waiting
+            .iter()
+            .filter(|&(_, read)| read.deadline <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        let expired = expired
+            .into_iter()
+            .filter_map(|id| self.waiting.remove(&id))
+            .map(|read| read.command)
+            .collect();
+
+        let mut retry = vec![];
+        for (id, read) in self.waiting.iter_mut() {
+            if now.duration_since(read.issued) >= READ_RETRY {
+                read.issued = now;
+                retry.push(read_context(*id));
+            }
+        }
+        (expired, retry)
+    }
+}
+
+fn shutting_down() -> public::Response {
+    public::error_response(ErrorCode::SHUTTING_DOWN, "the node is shutting down")
+}
+
+fn proposal_dropped() -> public::Response {
+    public::error_response(ErrorCode::PROPOSAL_DROPPED, "raft refused the proposal")
+}
+
+/// Whether the request changes the state, and so must be proposed by the
+/// leader. Transferring leadership is left to the leader as well.
+fn is_proposal(request: &public::Request) -> bool {
+    request.has_set()
+        || request.has_multi_set()
+        || request.has_delete()
+        || request.has_propose()
+        || request.has_add_node()
+        || request.has_remove_node()
+        || request.has_promote_node()
+        || request.has_update_peer_address()
+        || request.has_transfer_leader()
+}
+
+/// The consistency a read asks for and the most it may lag by.
+fn read_consistency(request: &public::Request) -> (Consistency, u64) {
+    if request.has_get() {
+        let get = request.get_get();
+        (get.get_consistency(), get.get_max_lag())
+    } else if request.has_scan() {
+        let scan = request.get_scan();
+        (scan.get_consistency(), scan.get_max_lag())
+    } else {
+        (Consistency::LINEARIZABLE, 0)
+    }
+}
+
+fn read_context(id: u64) -> Vec<u8> {
+    let mut ctx = Vec::with_capacity(8);
+    ctx.put_u64_be(id);
+    ctx
+}
+
+#[cfg(test)]
+mod read_tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_wait_to_be_confirmed_and_applied() {
+        let (tx, _) = mpsc::channel(1024);
+        let now = Instant::now();
+        let mut reads = Reads::new();
+
+        let first = reads.wait(Command::new(tx.clone(), public::get_request("a")), now);
+        let second = reads.wait(Command::new(tx, public::get_request("b")), now);
+        assert_ne!(first, second);
+
+        reads.confirm(&first, 5);
+        reads.confirm(&second, 7);
+        reads.confirm(&second, 7);
+        assert!(reads.waiting.is_empty());
+
+        assert!(reads.ready(4).is_empty());
+        assert_eq!(reads.ready(6).len(), 1);
+        assert_eq!(reads.ready(7).len(), 1);
+        assert!(reads.confirmed.is_empty());
+    }
+
+    #[test]
+    fn test_reads_are_retried_and_expire() {
+        let (tx, _) = mpsc::channel(1024);
+        let now = Instant::now();
+        let mut reads = Reads::new();
+        let ctx = reads.wait(Command::new(tx, public::get_request("a")), now);
+
+        let (expired, retry) = reads.expire(now);
+        assert!(expired.is_empty());
+        assert!(retry.is_empty());
+
+        let (expired, retry) = reads.expire(now + READ_RETRY);
+        assert!(expired.is_empty());
+        assert_eq!(retry, vec![ctx]);
+
+        let (expired, _) = reads.expire(now + READ_TIMEOUT);
+        assert_eq!(expired.len(), 1);
+        assert!(reads.waiting.is_empty());
+    }
 }
 
 /// The database does not communicate on a network but instead uses
 /// a set of channels to communicate.
-pub struct Db {
+///
+/// Writes are replicated through raft and applied to the state machine, in
+/// order, once they are committed. Reads go straight to the state machine.
+pub struct Db<S: StateMachine, T: Transport> {
     node: RawNode<KeyValue>,
-    network: network::Handle,
+    transport: T,
     callbacks: Callbacks,
+    forwards: Forwards,
+    reads: Reads,
+    transfers: Transfers,
+    machine: S,
+    /// The term pending proposals were last checked against.
+    term: u64,
+    /// Once asked to stop, the time at which the node gives up waiting for
+    /// another to take over as leader.
+    stopping: Option<Instant>,
+    /// Peers removed from the cluster, which a hello never routes again.
+    removed: HashSet<u64>,
+    /// Cluster tokens from the hellos of routed peers, kept until the node
+    /// knows its own. Only a node added to a cluster it didn't bootstrap has
+    /// to learn the token, from the leader that added it.
+    tokens: Option<HashMap<u64, String>>,
 }
 
-impl Db {
-    pub fn new(id: u64, file: &str, mut network: network::Handle) -> Db {
+impl<S: StateMachine, T: Transport> Db<S, T> {
+    pub fn new(
+        id: u64,
+        file: &str,
+        mut transport: T,
+        mut machine: S,
+        bootstrap: Option<Bootstrap>,
+    ) -> Result<Db<S, T>, storage::Error> {
         let config = Config {
             id,
             heartbeat_tick: 3,
             election_tick: 10,
             max_inflight_msgs: 1024,
+            check_quorum: true,
             ..Config::default()
         };
         config.validate().unwrap();
 
-        let store = KeyValue::new(file);
+        let store = KeyValue::new(file)?;
+        {
+            let mut core = store.wl();
+            if let Some(snapshot) = core.take_interrupted() {
+                println!("Resuming snapshot left by a restart");
+                restore_snapshot(&mut machine, &mut core, snapshot)?;
+            }
+            core.catch_up(machine.checkpointed())?;
+        }
+
+        let mut peers = vec![RaftPeer { id, context: None }];
         for peer in store.rl().peers() {
-            tokio::run(network.add_peer(&peer));
+            if peer.get_id() != id {
+                transport.add_peer(&peer);
+                peers.push(RaftPeer {
+                    id: peer.get_id(),
+                    context: None,
+                });
+            }
         }
 
-        let node = RawNode::new(&config, store, network.peers()).unwrap();
-        let callbacks = Callbacks::new();
+        let bootstrapping = match bootstrap {
+            Some(ref bootstrap) => store.wl().bootstrap(&bootstrap.token)?,
+            None => false,
+        };
+        let peers = match bootstrap {
+            Some(ref bootstrap) if bootstrapping => {
+                println!("Bootstrapping cluster {}", bootstrap.token);
+                initial_members(id, bootstrap, &mut transport)?
+            }
+            _ => peers,
+        };
+
+        let tokens = match storage::cluster_token(file)? {
+            Some(_) => None,
+            None => Some(HashMap::new()),
+        };
+
+        let node = RawNode::new(&config, store, peers).unwrap();
+        let callbacks = Callbacks::new(id);
+        let forwards = Forwards::new();
+        let reads = Reads::new();
+        let transfers = Transfers::new();
 
-        Db {
-            network,
+        Ok(Db {
+            transport,
             node,
             callbacks,
-        }
+            forwards,
+            reads,
+            transfers,
+            machine,
+            term: 0,
+            stopping: None,
+            removed: HashSet::new(),
+            tokens,
+        })
     }
 
     pub fn start(mut self) -> Handle {
         let (tx, rx) = mpsc::channel(1024);
+        let transport = self.transport.take_thread();
+        let unreachable = self.transport.unreachable();
         let handle = thread::spawn(move || {
             const HEARTBEAT: Duration = Duration::from_millis(100);
 
@@ -162,127 +689,553 @@ impl Db {
                 .map(|_| Message::Timeout)
                 .map_err(|_| ())
                 .select(rx.map_err(|e| println!("error: {:?}", e)))
+                .select(unreachable.map(Message::Unreachable))
                 .for_each(move |msg| {
-                    // The db is receiving commands/messages here which are then
-                    // worked on and possibly generate other return values.
-                    match msg {
-                        Message::Timeout => {
-                            self.node.tick();
-                        }
-                        Message::Cmd(command) => self.handle(command),
-                        Message::Raft(message) => {
-                            self.node.step(message).unwrap();
-                        }
-                        Message::Ping => {
-                            println!("PING");
-                        }
-                        Message::Stop => {
-                            println!("requested to stop");
-                            return Err(());
-                        }
-                    }
-
-                    self.check_ready();
+                    self.receive(msg);
 
+                    if self.can_stop() {
+                        self.fail_pending();
+                        self.flush();
+                        return Err(());
+                    }
                     Ok(())
                 });
             tokio::run(timer);
+
+            // The db held the transport, so it's done once the messages
+            // already queued have been sent.
+            if let Some(transport) = transport {
+                transport.join().expect("Failed to join the transport");
+            }
         });
         Handle { handle, tx }
     }
 
+    /// Works on a message sent to the db, which may go on to generate
+    /// replies and messages for peers.
+    fn receive(&mut self, msg: Message) {
+        match msg {
+            Message::Timeout => {
+                self.node.tick();
+                self.retry_reads();
+                self.expire_forwards();
+                self.expire_proposals();
+                self.expire_transfers();
+            }
+            Message::Cmd(command) => self.handle(command),
+            Message::Raft(message) => {
+                if self.accept_snapshot(&message) {
+                    self.node.step(message).unwrap();
+                }
+            }
+            Message::Chunk(chunk) => {
+                if let Err(e) = self.node.mut_store().wl().receive_chunk(&chunk) {
+                    println!("Unable to write a snapshot chunk: {}", e);
+                }
+            }
+            Message::SnapshotFailed(failed) => self.snapshot_failed(failed.get_from()),
+            Message::Forward(forward) => self.handle_forward(forward),
+            Message::ForwardReply(reply) => self.handle_forward_reply(reply),
+            Message::Unreachable(id) => {
+                self.node.report_unreachable(id);
+                // A snapshot may have been cut off with the connection
+                self.snapshot_failed(id);
+            }
+            Message::Hello(hello) => self.handle_hello(hello),
+            Message::Ping => {
+                println!("PING");
+            }
+            Message::Stop => {
+                println!("requested to stop");
+                self.begin_stop();
+            }
+        }
+
+        self.check_ready();
+        self.finish_transfers();
+        self.generate_snapshot();
+    }
+
     fn handle(&mut self, command: Command) {
+        if self.stopping.is_some() {
+            command.reply(shutting_down());
+            return;
+        }
+
+        let command = match self.forward_to_leader(command) {
+            Some(command) => command,
+            None => return,
+        };
+
         if command.request().has_ping() {
             self.handle_ping(command);
-        } else if command.request().has_get() {
-            self.handle_get(command);
-        } else if command.request().has_scan() {
-            self.handle_scan(command);
-        } else if command.request().has_delete() {
-            self.handle_delete(command);
-        } else if command.request().has_set() {
-            self.handle_set(command);
+        } else if command.request().has_get()
+            || command.request().has_scan()
+            || command.request().has_query()
+        {
+            self.handle_read(command);
+        } else if command.request().has_set()
+            || command.request().has_multi_set()
+            || command.request().has_delete()
+            || command.request().has_propose()
+        {
+            self.handle_write(command);
         } else if command.request().has_add_node() {
             self.handle_add_node(command);
         } else if command.request().has_remove_node() {
             self.handle_remove_node(command);
+        } else if command.request().has_promote_node() {
+            self.handle_promote_node(command);
+        } else if command.request().has_update_peer_address() {
+            self.handle_update_peer_address(command);
+        } else if command.request().has_transfer_leader() {
+            self.handle_transfer_leader(command);
         } else if command.request().has_info() {
             self.handle_info(command);
+        } else {
+            command.reply(public::error_response(
+                ErrorCode::INVALID_REQUEST,
+                "empty or unknown request",
+            ));
         }
     }
 
-    fn handle_get(&self, command: Command) {
-        let value = {
-            let get = command.request().get_get();
-            self.node.get_store().rl().get(get.get_key())
-        };
-        command.reply(public::get_response(value));
+    /// The error for a request that only the leader can serve, pointing at
+    /// the leader if it's known.
+    fn not_leader(&self) -> public::Response {
+        let leader = self.node.raft.leader_id;
+        let store = self.node.get_store().rl();
+        let addr = store
+            .peers()
+            .iter()
+            .find(|peer| peer.get_id() == leader)
+            .map_or("", |peer| peer.get_public_addr());
+        public::not_leader_response(leader, addr)
     }
 
-    fn handle_scan(&self, command: Command) {
-        let keys = self.node.get_store().rl().scan();
-        command.reply(public::scan_response(keys));
+    /// Routes to a peer that connected and said where it listens, if it's a
+    /// member. Anyone else is ignored, so neither a removed node nor a stray
+    /// connection can put itself in the routes. A node on its own is the
+    /// exception, since the leader adding it to a cluster is a stranger to it
+    /// until the conf change arrives.
+    fn handle_hello(&mut self, hello: proto::Hello) {
+        let id = hello.get_id();
+        if id == self.node.raft.id || self.removed.contains(&id) {
+            return;
+        }
+
+        let routed = {
+            let prs = self.node.raft.prs();
+            let alone = prs.voters().len() == 1 && prs.learners().is_empty();
+            alone || prs.get(id).is_some()
+        };
+        if routed {
+            self.transport.discovered(id, hello.get_addr());
+            if let Some(ref mut tokens) = self.tokens {
+                if !hello.get_cluster().is_empty() {
+                    tokens.insert(id, hello.get_cluster().to_string());
+                }
+            }
+        }
     }
 
-    fn handle_info(&self, command: Command) {
-        let mut response = public::Response::new();
-        let mut info = public::response::Info::new();
-        info.set_id(self.node.raft.id);
-        info.set_leader_id(self.node.raft.leader_id);
-        info.set_term(self.node.raft.term);
-        info.set_applied(self.node.raft.raft_log.get_applied());
+    /// Records the cluster the node was added to, once it's a member along
+    /// with others and has heard the token from its leader. Clients and
+    /// peers are held to that cluster from the next start.
+    fn maybe_join(&mut self) {
+        let token = {
+            let tokens = match self.tokens {
+                Some(ref tokens) => tokens,
+                None => return,
+            };
+            let raft = &self.node.raft;
+            let prs = raft.prs();
+            let alone = prs.voters().len() == 1 && prs.learners().is_empty();
+            let joined = !alone && prs.get(raft.id).is_some();
+            match tokens.get(&raft.leader_id) {
+                Some(token) if joined => token.clone(),
+                _ => return,
+            }
+        };
 
-        let peers: Vec<u64> = self.node
-            .get_store()
-            .rl()
-            .peers()
-            .iter()
-            .map(|p| p.id)
-            .collect();
+        match self.node.mut_store().wl().join(&token) {
+            Ok(_) => {
+                println!("Joined cluster {}", token);
+                self.tokens = None;
+            }
+            Err(e) => println!("Unable to record the cluster the node joined: {}", e),
+        }
+    }
 
-        info.set_peers(peers.into());
+    /// Fails everything still waiting for an answer, as the node is stopping.
+    fn fail_pending(&mut self) {
+        let mut pending = self.callbacks.drain();
+        pending.extend(self.reads.drain());
+        pending.extend(self.forwards.drain());
+        pending.extend(self.transfers.drain());
+        for command in pending {
+            command.reply(shutting_down());
+        }
+    }
 
-        response.set_info(info);
+    /// Starts stopping the node. New requests are refused from here on, while
+    /// those already proposed may still finish. A leader first hands
+    /// leadership to the voter furthest along, so the cluster doesn't wait
+    /// out an election timeout to replace it.
+    fn begin_stop(&mut self) {
+        if self.stopping.is_some() {
+            return;
+        }
+        let now = Instant::now();
+        self.stopping = Some(now);
 
-        command.reply(response);
+        if self.node.raft.state != StateRole::Leader {
+            return;
+        }
+        let id = self.node.raft.id;
+        let target = self.node
+            .raft
+            .prs()
+            .voters()
+            .iter()
+            .filter(|&(voter, _)| *voter != id)
+            .max_by_key(|&(_, progress)| progress.matched)
+            .map(|(voter, _)| *voter);
+        if let Some(target) = target {
+            println!("Handing leadership to {} before stopping", target);
+            self.node.transfer_leader(target);
+            self.stopping = Some(now + TRANSFER_TIMEOUT);
+        }
     }
 
-    fn handle_set(&mut self, command: Command) {
-        use protobuf::Message;
+    /// Whether a stopping node is done handing off leadership, or has given
+    /// up on it.
+    fn can_stop(&self) -> bool {
+        match self.stopping {
+            Some(deadline) => {
+                self.node.raft.state != StateRole::Leader || Instant::now() >= deadline
+            }
+            None => false,
+        }
+    }
 
-        let entry = self.callbacks.store_set(command);
+    /// Checkpoints everything applied, so a restart doesn't need to replay
+    /// the log.
+    fn flush(&mut self) {
+        let applied = self.node.raft.raft_log.get_applied();
+        if applied <= self.machine.checkpointed() {
+            return;
+        }
 
+        self.machine
+            .checkpoint(applied)
+            .expect("Unable to checkpoint the state machine");
         self.node
-            .propose(Vec::new(), entry.write_to_bytes().unwrap())
-            .unwrap();
+            .mut_store()
+            .wl()
+            .create_snapshot(applied)
+            .expect("Unable to write snapshot");
     }
 
-    fn handle_delete(&mut self, command: Command) {
+    /// Writes sent to a follower are forwarded to the leader, which replies
+    /// by way of this node. Raft would pass a proposal along by itself, but
+    /// the reply would never find its way back to the client. Returns the
+    /// command if this node should handle it.
+    fn forward_to_leader(&mut self, command: Command) -> Option<Command> {
         use protobuf::Message;
 
-        let entry = self.callbacks.store_delete(command);
+        if !is_proposal(command.request()) {
+            return Some(command);
+        }
 
-        self.node
-            .propose(Vec::new(), entry.write_to_bytes().unwrap())
-            .unwrap();
+        let leader = self.node.raft.leader_id;
+        if leader == self.node.raft.id {
+            return Some(command);
+        }
+        if leader == raft::INVALID_ID {
+            // Raft would drop the proposal, so fail it now rather than never
+            command.reply(self.not_leader());
+            return None;
+        }
+
+        let request = command
+            .request()
+            .write_to_bytes()
+            .expect("Request should serialize");
+        let mut forward = proto::Forward::new();
+        forward.set_from(self.node.raft.id);
+        forward.set_id(self.forwards.wait(command, Instant::now()));
+        forward.set_request(request);
+
+        let mut msg = proto::PeerMessage::new();
+        msg.set_forward(forward);
+        self.transport.send(leader, msg);
+        None
     }
 
-    fn handle_add_node(&mut self, command: Command) {
+    /// Handles a write forwarded by a follower, relaying the reply back to it.
+    fn handle_forward(&mut self, forward: proto::Forward) {
         use protobuf::Message;
 
-        let peer = {
-            let request = command.request().get_add_node();
-            let mut peer = proto::Peer::new();
+        let id = forward.get_id();
+        let request = match parse_from_bytes::<public::Request>(forward.get_request()) {
+            Ok(request) => request,
+            Err(e) => {
+                println!("Unable to decode a forwarded request: {:?}", e);
+                return;
+            }
+        };
+
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(
+            rx.take(1)
+                .map(move |response: public::Response| {
+                    let response = response
+                        .write_to_bytes()
+                        .expect("Response should serialize");
+                    let mut reply = proto::ForwardReply::new();
+                    reply.set_id(id);
+                    reply.set_response(response);
+
+                    let mut msg = proto::PeerMessage::new();
+                    msg.set_reply(reply);
+                    msg
+                })
+                .forward(self.transport.sink(forward.get_from()))
+                .then(|_| Ok(())),
+        );
+
+        let command = Command::new(tx, request);
+        if self.node.raft.leader_id == self.node.raft.id {
+            self.handle(command);
+        } else {
+            // Leadership moved while the write was on its way, it isn't
+            // passed along a second time.
+            let response = self.not_leader();
+            command.reply(response);
+        }
+    }
+
+    fn handle_forward_reply(&mut self, reply: proto::ForwardReply) {
+        if let Some(command) = self.forwards.take(reply.get_id()) {
+            match parse_from_bytes::<public::Response>(reply.get_response()) {
+                Ok(response) => command.reply(response),
+                Err(e) => {
+                    println!("Unable to decode a forwarded reply: {:?}", e);
+                    command.reply(public::failure_response());
+                }
+            }
+        }
+    }
+
+    fn expire_forwards(&mut self) {
+        for command in self.forwards.expire(Instant::now()) {
+            command.reply(public::error_response(
+                ErrorCode::TIMEOUT,
+                "the leader didn't reply in time",
+            ));
+        }
+    }
+
+    /// Reads are answered once raft confirms this node is up to date, see
+    /// `Reads`, unless the request asks for less.
+    fn handle_read(&mut self, command: Command) {
+        match read_consistency(command.request()) {
+            (Consistency::STALE, max_lag) => self.handle_stale_read(command, max_lag),
+            (Consistency::LEASE, _) if self.has_lease() => {
+                // The lease stands in for the heartbeat round trip, but the
+                // read must still wait for the commit index to be applied.
+                let committed = self.node.raft.raft_log.committed;
+                self.reads.confirmed.push((committed, command));
+                self.answer_reads();
+            }
+            _ => {
+                let ctx = self.reads.wait(command, Instant::now());
+                self.node.read_index(ctx);
+            }
+        }
+    }
+
+    fn handle_stale_read(&self, command: Command, max_lag: u64) {
+        let raft_log = &self.node.raft.raft_log;
+        let lag = raft_log.committed - raft_log.get_applied();
+
+        // Without a leader there's no telling how far behind the commit
+        // index is, so a bounded read can't be served.
+        if max_lag > 0 && self.node.raft.leader_id == raft::INVALID_ID {
+            command.reply(self.not_leader());
+            return;
+        }
+        if max_lag > 0 && lag > max_lag {
+            command.reply(public::error_response(
+                ErrorCode::LAGGING,
+                &format!("{} entries behind", lag),
+            ));
+            return;
+        }
+
+        let response = self.machine.query(command.request());
+        command.reply(response);
+    }
+
+    /// Whether this node is a leader that can serve reads on its lease.
+    /// Check-quorum steps a leader down once it stops hearing from a quorum,
+    /// which is what makes the lease safe, and a new leader can't serve
+    /// reads until it has committed an entry in its own term.
+    fn has_lease(&self) -> bool {
+        let raft = &self.node.raft;
+        raft.state == StateRole::Leader
+            && raft.check_quorum
+            && raft.raft_log.term(raft.raft_log.committed).unwrap_or(0) == raft.term
+    }
+
+    fn retry_reads(&mut self) {
+        let (expired, retry) = self.reads.expire(Instant::now());
+        for command in expired {
+            command.reply(public::error_response(
+                ErrorCode::TIMEOUT,
+                "the read index wasn't confirmed in time",
+            ));
+        }
+        for ctx in retry {
+            self.node.read_index(ctx);
+        }
+    }
+
+    fn answer_reads(&mut self) {
+        let applied = self.node.raft.raft_log.get_applied();
+        for command in self.reads.ready(applied) {
+            let response = self.machine.query(command.request());
+            command.reply(response);
+        }
+    }
+
+    fn handle_info(&self, command: Command) {
+        let mut response = public::Response::new();
+        let mut info = public::response::Info::new();
+        info.set_id(self.node.raft.id);
+        info.set_leader_id(self.node.raft.leader_id);
+        info.set_term(self.node.raft.term);
+        info.set_applied(self.node.raft.raft_log.get_applied());
+
+        let (peers, learners) = {
+            let store = self.node.get_store().rl();
+            let peers: Vec<u64> = store.peers().iter().map(|p| p.id).collect();
+            let learners: Vec<u64> = store
+                .peers()
+                .iter()
+                .filter(|p| p.get_is_learner())
+                .map(|p| p.id)
+                .collect();
+            (peers, learners)
+        };
+
+        info.set_peers(peers.into());
+        info.set_learners(learners.into());
+
+        response.set_info(info);
+
+        command.reply(response);
+    }
+
+    fn handle_write(&mut self, command: Command) {
+        use protobuf::Message;
+
+        let entry = self.callbacks.store_request(command);
+
+        let result = self.node
+            .propose(Vec::new(), entry.write_to_bytes().unwrap());
+        self.track_proposal(entry.id, result);
+    }
+
+    /// Records where the entry for a stored command was proposed, or fails
+    /// the command if raft refused it.
+    fn track_proposal(&mut self, id: u64, result: raft::Result<()>) {
+        if result.is_err() {
+            self.callbacks
+                .get(id)
+                .map(|cb| cb.reply(proposal_dropped()));
+            return;
+        }
+
+        // Only the leader proposes, so the entry is the last in its log
+        let raft = &self.node.raft;
+        self.callbacks
+            .proposed(id, raft.raft_log.last_index(), raft.term);
+    }
+
+    fn expire_proposals(&mut self) {
+        for command in self.callbacks.expire(Instant::now()) {
+            command.reply(public::error_response(
+                ErrorCode::TIMEOUT,
+                "the proposal wasn't applied in time",
+            ));
+        }
+    }
+
+    /// Fails the commands whose entries can no longer be applied as proposed.
+    /// Once the log is applied past an entry that wasn't answered, another
+    /// leader's entry took its place. A proposal from an older term may
+    /// still commit, but nothing is waiting on this node to say so.
+    /// Fails the proposals that can no longer be applied. `emptied` holds the
+    /// index and term of each empty entry just applied: raft empties a
+    /// membership change in place when another one is still pending, so a
+    /// proposal whose entry is one of them was refused rather than replaced.
+    fn fail_stale_proposals(&mut self, applied: u64, emptied: &[(u64, u64)]) {
+        for pending in self.callbacks.overwritten(applied) {
+            let refused = pending
+                .proposed
+                .map_or(false, |proposed| emptied.contains(&proposed));
+            let message = if refused {
+                "another membership change is pending"
+            } else {
+                "the entry was replaced by another leader"
+            };
+            pending
+                .command
+                .reply(public::error_response(ErrorCode::PROPOSAL_DROPPED, message));
+        }
+
+        let term = self.node.raft.term;
+        if term != self.term {
+            self.term = term;
+            for command in self.callbacks.before_term(term) {
+                command.reply(public::error_response(
+                    ErrorCode::LEADER_CHANGED,
+                    "the leader changed before the proposal was applied",
+                ));
+            }
+        }
+    }
+
+    fn handle_add_node(&mut self, command: Command) {
+        let peer = {
+            let request = command.request().get_add_node();
+            let mut peer = proto::Peer::new();
             peer.set_id(request.get_id());
             peer.set_addr(request.get_addr().to_string());
+            peer.set_public_addr(request.get_public_addr().to_string());
+            peer.set_is_learner(request.get_is_learner());
             peer
         };
+        self.propose_peer(command, peer);
+    }
 
-        tokio::spawn(self.network.add_peer(&peer));
+    /// Proposes adding the peer, or replacing what's known of it when it's
+    /// already a member. The transport only routes to the peer once the
+    /// change is applied.
+    fn propose_peer(&mut self, command: Command, peer: proto::Peer) {
+        use protobuf::Message;
 
+        // A learner gets the log without voting, so it can catch up before
+        // it's promoted and counts towards a quorum.
         let mut cc = ConfChange::new();
         cc.set_id(self.node.raft.id);
-        cc.set_change_type(ConfChangeType::AddNode);
+        if peer.get_is_learner() {
+            cc.set_change_type(ConfChangeType::AddLearnerNode);
+        } else {
+            cc.set_change_type(ConfChangeType::AddNode);
+        }
         cc.set_node_id(peer.get_id());
 
         cc.set_context(peer.write_to_bytes().expect("Peer should have serialize"));
@@ -290,14 +1243,9 @@ impl Db {
         let entry = self.callbacks.store(command);
 
         // Context stores the callback entry, but also the ConfChange
-        if self.node
-            .propose_conf_change(entry.write_to_bytes().unwrap(), cc)
-            .is_err()
-        {
-            self.callbacks
-                .get(entry.id)
-                .map(|cb| cb.reply(public::failure_response()));
-        }
+        let result = self.node
+            .propose_conf_change(entry.write_to_bytes().unwrap(), cc);
+        self.track_proposal(entry.id, result);
     }
 
     fn handle_remove_node(&mut self, command: Command) {
@@ -313,13 +1261,111 @@ impl Db {
 
         let entry = self.callbacks.store(command);
         // Context stores the callback entry, but also the ConfChange
-        if self.node
-            .propose_conf_change(entry.write_to_bytes().unwrap(), cc)
-            .is_err()
-        {
-            self.callbacks
-                .get(entry.id)
-                .map(|cb| cb.reply(public::failure_response()));
+        let result = self.node
+            .propose_conf_change(entry.write_to_bytes().unwrap(), cc);
+        self.track_proposal(entry.id, result);
+    }
+
+    /// Promoting a learner is adding it again as a voter, which raft does in
+    /// place without resetting its progress.
+    fn handle_promote_node(&mut self, command: Command) {
+        let peer_id = command.request().get_promote_node().get_id();
+        let peer = self.node
+            .get_store()
+            .rl()
+            .peers()
+            .iter()
+            .find(|p| p.get_id() == peer_id)
+            .cloned();
+
+        let mut peer = match peer {
+            Some(ref peer) if peer.get_is_learner() => peer.clone(),
+            _ => {
+                command.reply(public::error_response(
+                    ErrorCode::INVALID_REQUEST,
+                    "the node isn't a learner",
+                ));
+                return;
+            }
+        };
+        peer.set_is_learner(false);
+        self.propose_peer(command, peer);
+    }
+
+    /// Moving a member is adding it again with its new addresses, which raft
+    /// does in place like a promotion.
+    fn handle_update_peer_address(&mut self, command: Command) {
+        let peer = {
+            let request = command.request().get_update_peer_address();
+            self.node
+                .get_store()
+                .rl()
+                .peers()
+                .iter()
+                .find(|p| p.get_id() == request.get_id())
+                .map(|peer| {
+                    let mut peer = peer.clone();
+                    peer.set_addr(request.get_addr().to_string());
+                    if !request.get_public_addr().is_empty() {
+                        peer.set_public_addr(request.get_public_addr().to_string());
+                    }
+                    peer
+                })
+        };
+
+        match peer {
+            Some(peer) => self.propose_peer(command, peer),
+            None => command.reply(public::error_response(
+                ErrorCode::INVALID_REQUEST,
+                "the node isn't a member",
+            )),
+        }
+    }
+
+    /// Hands leadership to another voter, answering once it leads. Raft
+    /// brings the target's log up to date before telling it to campaign.
+    fn handle_transfer_leader(&mut self, command: Command) {
+        let target = command.request().get_transfer_leader().get_target_id();
+        if target == self.node.raft.id {
+            command.reply(public::success_response());
+            return;
+        }
+
+        let is_voter = self.node
+            .get_store()
+            .rl()
+            .peers()
+            .iter()
+            .any(|p| p.get_id() == target && !p.get_is_learner());
+        if !is_voter {
+            command.reply(public::error_response(
+                ErrorCode::INVALID_REQUEST,
+                "the target isn't a voter",
+            ));
+            return;
+        }
+
+        self.transfers.wait(target, command, Instant::now());
+        self.node.transfer_leader(target);
+    }
+
+    fn finish_transfers(&mut self) {
+        let leader = self.node.raft.leader_id;
+        for command in self.transfers.done(leader) {
+            command.reply(public::success_response());
+        }
+    }
+
+    fn expire_transfers(&mut self) {
+        let leader = self.node.raft.leader_id;
+        for (target, command) in self.transfers.expire(Instant::now()) {
+            command.reply(public::error_response(
+                ErrorCode::TIMEOUT,
+                &format!(
+                    "node {} didn't take over in time, node {} leads",
+                    target, leader
+                ),
+            ));
         }
     }
 
@@ -335,22 +1381,14 @@ impl Db {
         // The Raft is ready, we can do something now.
         let mut ready = self.node.ready();
 
-        let is_leader = self.is_leader();
-
-        // Leaders should send messages right away
-        if is_leader {
-            let msgs = ready.messages.drain(..);
-            for msg in msgs {
-                ::tokio::spawn(self.network.send(msg.to, msg));
-            }
-        }
-
         if !raft::is_empty_snap(&ready.snapshot) {
-            self.node
-                .mut_store()
-                .wl()
-                .apply_snapshot(ready.snapshot.clone())
-                .unwrap();
+            let before = self.node.get_store().rl().peers().to_vec();
+            {
+                let mut core = self.node.mut_store().wl();
+                core.begin_snapshot(&ready.snapshot).unwrap();
+                restore_snapshot(&mut self.machine, &mut core, ready.snapshot.clone()).unwrap();
+            }
+            self.route_peers(&before);
         }
 
         if !ready.entries.is_empty() {
@@ -359,28 +1397,47 @@ impl Db {
 
         if let Some(ref hs) = ready.hs {
             // Raft HardState changed, and we need to persist it.
-            self.node.mut_store().wl().set_hardstate(hs.clone());
+            self.node
+                .mut_store()
+                .wl()
+                .set_hardstate(hs.clone())
+                .unwrap();
         }
 
-        // Followers should reply messages
-        if !is_leader {
-            let msgs = ready.messages.drain(..);
-            for msg in msgs {
-                ::tokio::spawn(self.network.send(msg.to, msg));
+        // Messages can carry votes and acknowledgements, so they may only
+        // leave once the state above is synced to disk.
+        let msgs = ready.messages.drain(..);
+        let mut unsent = vec![];
+        for msg in msgs {
+            if msg.get_msg_type() == MessageType::MsgSnapshot {
+                if !self.send_snapshot(&msg) {
+                    unsent.push(msg.to);
+                }
+            } else {
+                self.transport.send(msg.to, transport::raft_message(&msg));
             }
         }
 
+        // Read states are left in the ready, advancing it clears them from raft
+        for rs in &ready.read_states {
+            self.reads.confirm(&rs.request_ctx, rs.index);
+        }
+
+        let mut emptied = vec![];
         if let Some(committed_entries) = ready.committed_entries.take() {
             let mut last_apply_index = 0;
             let mut conf_state: Option<ConfState> = None;
             for entry in committed_entries {
                 last_apply_index = entry.get_index();
+                let term = entry.get_term();
 
                 let data = entry.get_data();
                 let context = entry.get_context();
 
                 if data.is_empty() {
                     // Emtpy entry, when the peer becomes Leader it will send an empty entry.
+                    // A membership change refused by raft is also left empty.
+                    emptied.push((last_apply_index, term));
                     continue;
                 }
 
@@ -389,18 +1446,19 @@ impl Db {
                     EntryType::EntryNormal => {
                         let entry = parse_from_bytes::<proto::Entry>(data).expect("Valid protobuf");
 
-                        let response = match entry.kind {
-                            proto::EntryKind::SET => {
-                                self.node.mut_store().wl().set(&entry.key, &entry.value);
-                                public::success_response()
+                        let request = match entry.kind {
+                            proto::EntryKind::REQUEST => {
+                                parse_from_bytes::<public::Request>(&entry.request)
+                                    .expect("Valid request")
                             }
-                            proto::EntryKind::DELETE => public::delete_response(
-                                self.node.mut_store().wl().delete(&entry.key),
-                            ),
+                            // Logs written before requests were carried whole
+                            proto::EntryKind::SET => public::set_request(&entry.key, &entry.value),
+                            proto::EntryKind::DELETE => public::delete_request(&entry.key),
                             _ => panic!("An invalid entry kind was detected"),
                         };
+                        let response = self.machine.apply(&request);
 
-                        if let Some(cmd) = self.callbacks.get(entry.id) {
+                        if let Some(cmd) = self.callbacks.applied(&entry, last_apply_index, term) {
                             cmd.reply(response);
                         }
                     }
@@ -408,22 +1466,28 @@ impl Db {
                         let cc = parse_from_bytes::<ConfChange>(data).expect("Valid protobuf");
 
                         match cc.get_change_type() {
-                            ConfChangeType::AddNode => {
+                            ConfChangeType::AddNode | ConfChangeType::AddLearnerNode => {
                                 let peer = parse_from_bytes::<proto::Peer>(cc.get_context())
                                     .expect("Valid peer");
+                                if peer.get_id() != self.node.raft.id {
+                                    self.transport.add_peer(&peer);
+                                }
                                 self.node.mut_store().wl().add_node(peer);
                             }
                             ConfChangeType::RemoveNode => {
+                                self.removed.insert(cc.node_id);
+                                self.transport.remove_peer(cc.node_id);
                                 self.node.mut_store().wl().remove_node(cc.node_id);
                             }
-                            _ => (), // no learners right now
                         }
 
                         conf_state = Some(self.node.apply_conf_change(&cc));
 
                         // Conf changes have their callback entry saved in the context
                         if let Ok(entry) = parse_from_bytes::<proto::Entry>(context) {
-                            if let Some(cmd) = self.callbacks.get(entry.id) {
+                            if let Some(cmd) =
+                                self.callbacks.applied(&entry, last_apply_index, term)
+                            {
                                 cmd.reply(public::success_response());
                             }
                         }
@@ -432,32 +1496,195 @@ impl Db {
             }
 
             if last_apply_index > 0 {
-                let mut mem = self.node.mut_store().wl();
-                mem.create_snapshot(last_apply_index, conf_state);
+                self.maybe_snapshot(last_apply_index, conf_state);
             }
         }
 
         self.node.advance(ready);
-        let raft_applied = self.node.raft.raft_log.get_applied();
-        let _ = self.node.mut_store().wl().compact(raft_applied);
+        for id in unsent {
+            self.snapshot_failed(id);
+        }
+        let applied = self.node.raft.raft_log.get_applied();
+        self.fail_stale_proposals(applied, &emptied);
+        self.answer_reads();
+        self.maybe_join();
+    }
+
+    /// Hands a snapshot to the network along with the data built for it.
+    /// Returns false if the data can't be opened.
+    fn send_snapshot(&mut self, msg: &raft::eraftpb::Message) -> bool {
+        let (index, term) = {
+            let metadata = msg.get_snapshot().get_metadata();
+            (metadata.get_index(), metadata.get_term())
+        };
+        let data = self.node.get_store().rl().open_generated(index, term);
+        match data {
+            Ok(Some(data)) => {
+                self.transport.send_snapshot(msg.to, msg, data);
+                return true;
+            }
+            Ok(None) => println!("The data for snapshot {} was replaced", index),
+            Err(e) => println!("Unable to open the data for snapshot {}: {}", index, e),
+        }
+        false
+    }
+
+    /// A snapshot from the leader may only be stepped once all of its data
+    /// has arrived, unless raft already has everything it covers and will
+    /// ignore it. Otherwise the leader is told to send it again.
+    fn accept_snapshot(&mut self, msg: &raft::eraftpb::Message) -> bool {
+        if msg.get_msg_type() != MessageType::MsgSnapshot {
+            return true;
+        }
+        let index = msg.get_snapshot().get_metadata().get_index();
+        if index <= self.node.raft.raft_log.committed {
+            return true;
+        }
+
+        let term = msg.get_snapshot().get_metadata().get_term();
+        match self.node.mut_store().wl().take_received(index, term) {
+            Ok(true) => return true,
+            Ok(false) => println!("Snapshot {} arrived without all of its data", index),
+            Err(e) => println!("Unable to keep the data of snapshot {}: {}", index, e),
+        }
+
+        let mut failed = proto::PeerMessage::new();
+        failed.mut_snapshot_failed().set_from(self.node.raft.id);
+        failed.mut_snapshot_failed().set_index(index);
+        self.transport.send(msg.get_from(), failed);
+        false
+    }
+
+    /// Tells raft a snapshot didn't reach the follower, so it's sent again.
+    /// Raft ignores this unless it's waiting on a snapshot for the follower.
+    fn snapshot_failed(&mut self, id: u64) {
+        if self.node.raft.state == StateRole::Leader && self.node.raft.prs().get(id).is_some() {
+            self.node.report_snapshot(id, SnapshotStatus::Failure);
+        }
+    }
+
+    /// Once enough has been applied since the last snapshot, or the state
+    /// machine asks for it, the machine is checkpointed and a new snapshot
+    /// is written. The checkpoint always comes first, so the snapshot never
+    /// claims more than the machine has kept.
+    fn maybe_snapshot(&mut self, applied: u64, conf_state: Option<ConfState>) {
+        let mut core = self.node.mut_store().wl();
+        if let Some(cs) = conf_state {
+            core.set_conf_state(cs);
+        }
+
+        let due = core.needs_snapshot(applied).unwrap() || self.machine.needs_checkpoint();
+        if due {
+            self.machine
+                .checkpoint(applied)
+                .expect("Unable to checkpoint the state machine");
+            core.create_snapshot(applied)
+                .expect("Unable to write snapshot");
+        }
+    }
+
+    /// Points the transport at the peers in storage after a snapshot has
+    /// replaced them, where they were `before`.
+    fn route_peers(&mut self, before: &[proto::Peer]) {
+        let id = self.node.raft.id;
+        let after = self.node.get_store().rl().peers().to_vec();
+        for peer in before {
+            if !after.iter().any(|p| p.get_id() == peer.get_id()) {
+                self.transport.remove_peer(peer.get_id());
+            }
+        }
+        for peer in &after {
+            if peer.get_id() != id && !before.contains(peer) {
+                self.transport.add_peer(peer);
+            }
+        }
+    }
+
+    /// Builds the snapshot raft asked for to catch up a follower.
+    fn generate_snapshot(&mut self) {
+        if !self.node.get_store().rl().snapshot_requested() {
+            return;
+        }
+
+        let applied = self.node.raft.raft_log.get_applied();
+        let machine = &self.machine;
+        let result = self.node
+            .mut_store()
+            .wl()
+            .set_generated_snapshot(applied, |data| machine.snapshot(data));
+
+        match result {
+            Ok(()) => println!("Generated a snapshot at {}", applied),
+            Err(e) => println!("Unable to generate a snapshot: {}", e),
+        }
     }
+}
 
-    fn is_leader(&self) -> bool {
-        self.node.raft.leader_id == self.node.raft.id
+/// The peers a bootstrapped node starts with. Raft writes a conf change for
+/// each into the start of the log, the same on every member, and the peer
+/// carried in its context is recorded when it's applied.
+fn initial_members<T: Transport>(
+    id: u64,
+    bootstrap: &Bootstrap,
+    transport: &mut T,
+) -> Result<Vec<RaftPeer>, storage::Error> {
+    use protobuf::Message;
+
+    if !bootstrap.members.iter().any(|&(member, _, _)| member == id) {
+        return Err(storage::Error::Bootstrap(format!(
+            "node {} isn't one of the initial members",
+            id
+        )));
     }
+
+    let mut peers = vec![];
+    for &(member, ref addr, ref public_addr) in &bootstrap.members {
+        let mut peer = proto::Peer::new();
+        peer.set_id(member);
+        peer.set_addr(addr.clone());
+        peer.set_public_addr(public_addr.clone());
+        if member != id {
+            transport.add_peer(&peer);
+        }
+
+        peers.push(RaftPeer {
+            id: member,
+            context: Some(peer.write_to_bytes()?),
+        });
+    }
+    Ok(peers)
+}
+
+/// Restores a snapshot from the leader into the state machine and then
+/// records it in the storage.
+fn restore_snapshot<S: StateMachine>(
+    machine: &mut S,
+    core: &mut KeyValueCore,
+    snapshot: Snapshot,
+) -> Result<(), storage::Error> {
+    let index = snapshot.get_metadata().get_index();
+    let (snap, mut data) = core.read_incoming()?;
+    machine.restore(index, &mut data)?;
+    core.finish_snapshot(snapshot, snap)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use futures::{Future, Sink};
+    use futures::{future, Async, Future, Sink};
+    use server::machine::KeyValueMachine;
     use server::public::Command;
+    use server::transport::{Fault, MemoryNetwork, MemoryTransport};
+    use server::{network, peer};
+    use std::io::{self, Read, Write};
+    use tokio::runtime::current_thread;
 
     #[test]
     fn test_start_and_stop() {
-        let network = network::start();
+        let network = network::start(1, String::from("127.0.0.1:9101"), String::new());
 
-        let db = Db::new(1, "/tmp/data", network);
+        let machine = KeyValueMachine::open("/tmp/data.engine").unwrap();
+        let db = Db::new(1, "/tmp/data", network, machine, None).unwrap();
         let handle = db.start();
         let channel = handle.channel();
 
@@ -470,4 +1697,662 @@ mod tests {
         });
         handle.join();
     }
+
+    fn remove_data(file: &str) {
+        use std::fs;
+        let _ = fs::remove_file(file);
+        let _ = fs::remove_file(format!("{}.hardstate", file));
+        let _ = fs::remove_file(format!("{}.cluster", file));
+        let _ = fs::remove_dir_all(format!("{}.wal", file));
+        let _ = fs::remove_dir_all(format!("{}.engine", file));
+    }
+
+    /// Runs `f` where replies can be spawned, on this thread so a failed
+    /// assertion fails the test rather than being lost in a worker.
+    fn in_runtime<F: FnOnce()>(f: F) {
+        let mut runtime = current_thread::Runtime::new().unwrap();
+        runtime
+            .block_on(future::lazy(|| -> Result<(), ()> {
+                f();
+                Ok(())
+            }))
+            .unwrap();
+        runtime.run().unwrap();
+    }
+
+    fn single_node<S: StateMachine>(file: &str, machine: S) -> Db<S, MemoryTransport> {
+        let (transport, _) = MemoryNetwork::new().join(1);
+        Db::new(1, file, transport, machine, None).unwrap()
+    }
+
+    /// Applies the conf changes a new node starts with, which raft won't
+    /// campaign before, then makes the node its leader.
+    fn elect<S: StateMachine, T: Transport>(db: &mut Db<S, T>) {
+        db.check_ready();
+        db.node.campaign().unwrap();
+        db.check_ready();
+    }
+
+    fn key_value(file: &str) -> KeyValueMachine {
+        KeyValueMachine::open(format!("{}.engine", file)).unwrap()
+    }
+
+    #[test]
+    fn test_term_and_vote_survive_restart() {
+        let file = "/tmp/kv-raft-restart";
+        remove_data(file);
+
+        let mut db = single_node(file, key_value(file));
+        elect(&mut db);
+
+        let term = db.node.raft.term;
+        assert!(term > 0);
+        assert_eq!(db.node.raft.vote, 1);
+        drop(db);
+
+        let db = single_node(file, key_value(file));
+        assert_eq!(db.node.raft.term, term);
+        assert_eq!(db.node.raft.vote, 1);
+
+        let hs = db.node.get_store().initial_state().unwrap().hard_state;
+        assert_eq!(hs.get_term(), term);
+        assert_eq!(hs.get_vote(), 1);
+    }
+
+    /// Counts the writes applied to it.
+    #[derive(Default)]
+    struct Counter {
+        count: u64,
+        checkpointed: u64,
+    }
+
+    impl StateMachine for Counter {
+        fn apply(&mut self, _: &public::Request) -> public::Response {
+            self.count += 1;
+            public::result_response(self.count.to_string().into_bytes())
+        }
+
+        fn query(&self, _: &public::Request) -> public::Response {
+            public::result_response(self.count.to_string().into_bytes())
+        }
+
+        fn checkpointed(&self) -> u64 {
+            self.checkpointed
+        }
+
+        fn checkpoint(&mut self, index: u64) -> io::Result<()> {
+            self.checkpointed = index;
+            Ok(())
+        }
+
+        fn snapshot<W: Write>(&self, out: &mut W) -> io::Result<()> {
+            out.write_all(self.count.to_string().as_bytes())
+        }
+
+        fn restore<R: Read>(&mut self, index: u64, input: &mut R) -> io::Result<()> {
+            let mut count = String::new();
+            input.read_to_string(&mut count)?;
+            self.count = count.parse().unwrap_or(0);
+            self.checkpointed = index;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_custom_state_machine() {
+        let file = "/tmp/kv-raft-counter";
+        remove_data(file);
+
+        let mut db = single_node(file, Counter::default());
+        elect(&mut db);
+
+        let (tx, rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            for _ in 0..2 {
+                db.handle(Command::new(tx.clone(), public::propose_request(vec![])));
+                db.check_ready();
+            }
+
+            // Reads wait for raft to confirm the read index
+            db.handle(Command::new(tx, public::query_request(vec![])));
+            assert_eq!(db.reads.waiting.len(), 1);
+            db.check_ready();
+            assert!(db.reads.waiting.is_empty());
+        });
+
+        // Replies are sent from their own tasks, so they may arrive in any order
+        let mut results: Vec<Vec<u8>> = rx.wait()
+            .map(|response| response.unwrap().get_result().to_vec())
+            .collect();
+        results.sort();
+        assert_eq!(results, vec![b"1".to_vec(), b"2".to_vec(), b"2".to_vec()]);
+    }
+
+    #[test]
+    fn test_lease_and_stale_reads_skip_read_index() {
+        let file = "/tmp/kv-raft-consistency";
+        remove_data(file);
+
+        let mut db = single_node(file, key_value(file));
+        elect(&mut db);
+        assert!(db.has_lease());
+
+        let (tx, rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            db.handle(Command::new(tx.clone(), public::set_request("a", "1")));
+            db.check_ready();
+
+            for consistency in &[Consistency::LEASE, Consistency::STALE] {
+                let request = public::get_request_with("a", *consistency, 1);
+                db.handle(Command::new(tx.clone(), request));
+                assert!(db.reads.waiting.is_empty());
+                assert!(db.reads.confirmed.is_empty());
+            }
+        });
+
+        let found = rx.wait()
+            .filter_map(|response| response.ok())
+            .filter(|response| response.has_get())
+            .map(|response| response.get_get().get_value().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec!["1", "1"]);
+    }
+
+    #[test]
+    fn test_writes_without_a_leader_are_refused() {
+        let file = "/tmp/kv-raft-no-leader";
+        remove_data(file);
+
+        let mut db = single_node(file, key_value(file));
+        let (tx, rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            db.handle(Command::new(tx.clone(), public::set_request("a", "1")));
+            db.handle(Command::new(tx, public::Request::new()));
+        });
+
+        let mut codes: Vec<ErrorCode> = rx.wait()
+            .map(|response| response.unwrap().get_error().get_code())
+            .collect();
+        codes.sort_by_key(|code| *code as i32);
+        assert_eq!(codes, vec![ErrorCode::NOT_LEADER, ErrorCode::INVALID_REQUEST]);
+    }
+
+    #[test]
+    fn test_bootstrap_from_initial_members() {
+        let file = "/tmp/kv-raft-bootstrap";
+        remove_data(file);
+
+        let bootstrap = |token: &str| Bootstrap {
+            token: token.to_string(),
+            members: vec![(
+                1,
+                String::from("127.0.0.1:9101"),
+                String::from("127.0.0.1:9100"),
+            )],
+        };
+        let start = |bootstrap| {
+            let (transport, _) = MemoryNetwork::new().join(1);
+            Db::new(1, file, transport, key_value(file), Some(bootstrap))
+        };
+
+        let mut db = start(bootstrap("alpha")).unwrap();
+        db.check_ready();
+        let peers: Vec<(u64, String, String)> = db.node
+            .get_store()
+            .rl()
+            .peers()
+            .iter()
+            .map(|p| {
+                (
+                    p.get_id(),
+                    p.get_addr().to_string(),
+                    p.get_public_addr().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(peers, bootstrap("alpha").members);
+
+        elect(&mut db);
+        assert_eq!(db.node.raft.state, StateRole::Leader);
+        // Followers point clients at the public address it was started with
+        assert_eq!(
+            db.not_leader().get_error().get_leader_addr(),
+            "127.0.0.1:9100"
+        );
+        drop(db);
+
+        // Restarting carries on, but another cluster can't take the node
+        assert!(start(bootstrap("alpha")).is_ok());
+        match start(bootstrap("beta")) {
+            Err(storage::Error::Bootstrap(_)) => (),
+            _ => panic!("expected the node to refuse to bootstrap"),
+        }
+    }
+
+    #[test]
+    fn test_learners_are_added_and_promoted() {
+        let file = "/tmp/kv-raft-learner";
+        remove_data(file);
+
+        let mut db = single_node(file, key_value(file));
+        elect(&mut db);
+
+        let is_learner = |db: &Db<KeyValueMachine, MemoryTransport>| {
+            db.node
+                .get_store()
+                .rl()
+                .peers()
+                .iter()
+                .find(|p| p.get_id() == 2)
+                .map(|p| p.get_is_learner())
+        };
+
+        let (tx, rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            let add = public::add_node_request(2, "127.0.0.1:1".into(), "".into(), true);
+            db.handle(Command::new(tx.clone(), add));
+            db.check_ready();
+            db.check_ready();
+            assert_eq!(is_learner(&db), Some(true));
+
+            // Only a learner can be promoted
+            db.handle(Command::new(tx.clone(), public::promote_node_request(1)));
+            db.handle(Command::new(tx, public::promote_node_request(2)));
+            db.check_ready();
+            db.check_ready();
+            assert_eq!(is_learner(&db), Some(false));
+        });
+
+        let codes: Vec<ErrorCode> = rx.wait()
+            .map(|response| response.unwrap())
+            .filter(|response| response.has_error())
+            .map(|response| response.get_error().get_code())
+            .collect();
+        assert_eq!(codes, vec![ErrorCode::INVALID_REQUEST]);
+    }
+
+    #[test]
+    fn test_a_second_membership_change_waits_for_the_first() {
+        let file = "/tmp/kv-raft-pending-conf";
+        remove_data(file);
+
+        let mut db = single_node(file, key_value(file));
+        elect(&mut db);
+
+        let (tx, rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            // Raft empties the second change, as the first isn't applied yet
+            let first = public::add_node_request(2, "127.0.0.1:1".into(), "".into(), true);
+            let second = public::add_node_request(3, "127.0.0.1:2".into(), "".into(), true);
+            db.handle(Command::new(tx.clone(), first));
+            db.handle(Command::new(tx, second));
+            db.check_ready();
+            db.check_ready();
+        });
+
+        let errors: Vec<String> = rx.wait()
+            .map(|response| response.unwrap())
+            .filter(|response| response.has_error())
+            .map(|response| response.get_error().get_message().to_string())
+            .collect();
+        assert_eq!(errors, vec!["another membership change is pending"]);
+    }
+
+    #[test]
+    fn test_peers_are_routed_once_applied() {
+        let file = "/tmp/kv-raft-routing";
+        remove_data(file);
+
+        let network = MemoryNetwork::new();
+        let (transport, _) = network.join(1);
+        let (_, mut inbox) = network.join(2);
+        let mut db = Db::new(1, file, transport, key_value(file), None).unwrap();
+        elect(&mut db);
+
+        let addr = |db: &Db<KeyValueMachine, MemoryTransport>| {
+            db.node
+                .get_store()
+                .rl()
+                .peers()
+                .iter()
+                .find(|p| p.get_id() == 2)
+                .map(|p| (p.get_addr().to_string(), p.get_public_addr().to_string()))
+        };
+
+        let (tx, rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            let add = public::add_node_request(2, "old:1".into(), "old:2".into(), true);
+            db.handle(Command::new(tx.clone(), add));
+            db.check_ready();
+            db.check_ready();
+
+            let update = public::update_peer_address_request(2, "new:1".into(), "".into());
+            db.handle(Command::new(tx.clone(), update));
+            db.check_ready();
+            db.check_ready();
+            assert_eq!(addr(&db), Some(("new:1".into(), "old:2".into())));
+
+            // Once it's a member, the leader sends it the log
+            assert!(inbox.poll().unwrap().is_ready());
+
+            // Only a member can be moved
+            let update = public::update_peer_address_request(3, "new:1".into(), "".into());
+            db.handle(Command::new(tx, update));
+        });
+
+        let codes: Vec<ErrorCode> = rx.wait()
+            .map(|response| response.unwrap())
+            .filter(|response| response.has_error())
+            .map(|response| response.get_error().get_code())
+            .collect();
+        assert_eq!(codes, vec![ErrorCode::INVALID_REQUEST]);
+    }
+
+    #[test]
+    fn test_hellos_only_route_members() {
+        let file = "/tmp/kv-raft-hellos";
+        remove_data(file);
+
+        let network = MemoryNetwork::new();
+        let (transport, _) = network.join(1);
+        let (_, mut two) = network.join(2);
+        let (_, mut four) = network.join(4);
+        let mut db = Db::new(1, file, transport, key_value(file), None).unwrap();
+        elect(&mut db);
+
+        let hello = |id: u64| {
+            let mut hello = proto::Hello::new();
+            hello.set_id(id);
+            hello.set_addr(format!("node-{}", id));
+            Message::Hello(hello)
+        };
+
+        let (tx, _rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            for &id in &[2, 3] {
+                let add = public::add_node_request(id, format!("node-{}", id), "".into(), true);
+                db.handle(Command::new(tx.clone(), add));
+                db.check_ready();
+                db.check_ready();
+            }
+            db.handle(Command::new(tx.clone(), public::remove_node_request(2)));
+            db.check_ready();
+            db.check_ready();
+            while let Ok(Async::Ready(Some(_))) = two.poll() {}
+
+            // Neither the removed node nor one never added is routed
+            db.receive(hello(2));
+            db.receive(hello(4));
+            for &id in &[2, 4] {
+                let mut msg = proto::PeerMessage::new();
+                msg.set_raft(vec![]);
+                db.transport.send(id, msg);
+            }
+            assert!(two.poll().unwrap().is_not_ready());
+            assert!(four.poll().unwrap().is_not_ready());
+        });
+    }
+
+    #[test]
+    fn test_transfer_leader_checks_its_target() {
+        let file = "/tmp/kv-raft-transfer";
+        remove_data(file);
+
+        let mut db = single_node(file, key_value(file));
+        elect(&mut db);
+
+        let (tx, rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            // Leading already, and there's nobody else to lead
+            db.handle(Command::new(tx.clone(), public::transfer_leader_request(1)));
+            db.handle(Command::new(tx, public::transfer_leader_request(2)));
+            assert!(db.transfers.pending.is_empty());
+        });
+
+        let mut responses: Vec<public::Response> = rx.wait().map(|r| r.unwrap()).collect();
+        responses.sort_by_key(|response| response.has_error());
+        assert!(responses[0].get_success());
+        assert_eq!(
+            responses[1].get_error().get_code(),
+            ErrorCode::INVALID_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_stopping_refuses_requests_and_flushes() {
+        let file = "/tmp/kv-raft-stop";
+        remove_data(file);
+
+        let mut db = single_node(file, key_value(file));
+        elect(&mut db);
+
+        let (tx, rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            db.handle(Command::new(tx.clone(), public::set_request("a", "1")));
+            db.check_ready();
+
+            // There's nobody to hand leadership to, so it stops straight away
+            db.begin_stop();
+            assert!(db.can_stop());
+            db.handle(Command::new(tx, public::set_request("b", "2")));
+
+            db.flush();
+            assert_eq!(
+                db.machine.checkpointed(),
+                db.node.raft.raft_log.get_applied()
+            );
+        });
+
+        let mut responses: Vec<public::Response> = rx.wait().map(|r| r.unwrap()).collect();
+        responses.sort_by_key(|response| response.has_error());
+        assert!(responses[0].get_success());
+        assert_eq!(
+            responses[1].get_error().get_code(),
+            ErrorCode::SHUTTING_DOWN
+        );
+    }
+
+    type Node = (
+        Db<KeyValueMachine, MemoryTransport>,
+        mpsc::UnboundedReceiver<proto::PeerMessage>,
+    );
+
+    /// Starts a cluster of three nodes linked in memory.
+    fn cluster(name: &str, network: &MemoryNetwork) -> Vec<Node> {
+        let bootstrap = Bootstrap {
+            token: name.to_string(),
+            members: (1..4)
+                .map(|id| (id, format!("node-{}", id), String::new()))
+                .collect(),
+        };
+
+        (1..4)
+            .map(|id| {
+                let file = format!("/tmp/kv-raft-{}-{}", name, id);
+                remove_data(&file);
+                let (transport, inbox) = network.join(id);
+                let db = Db::new(
+                    id,
+                    &file,
+                    transport,
+                    key_value(&file),
+                    Some(bootstrap.clone()),
+                ).unwrap();
+                (db, inbox)
+            })
+            .collect()
+    }
+
+    /// Ticks every node the given number of times, delivering what they
+    /// send each other until the network is quiet again.
+    fn run(nodes: &mut [Node], ticks: usize) {
+        for _ in 0..ticks {
+            for &mut (ref mut db, _) in nodes.iter_mut() {
+                db.receive(Message::Timeout);
+            }
+
+            let mut delivered = true;
+            while delivered {
+                delivered = false;
+                for &mut (ref mut db, ref mut inbox) in nodes.iter_mut() {
+                    while let Ok(Async::Ready(Some(msg))) = inbox.poll() {
+                        if let Some(msg) = peer::into_message(msg) {
+                            db.receive(msg);
+                        }
+                        delivered = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The node every node agrees is leading, if there is one.
+    fn leader(nodes: &[Node]) -> Option<usize> {
+        let id = nodes[0].0.node.raft.leader_id;
+        if id == 0 || nodes.iter().any(|node| node.0.node.raft.leader_id != id) {
+            return None;
+        }
+        Some(id as usize - 1)
+    }
+
+    fn value(node: &Node, key: &str) -> Option<String> {
+        let response = node.0.machine.query(&public::get_request(key));
+        let get = response.get_get();
+        if get.get_is_found() {
+            Some(get.get_value().to_string())
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn test_cluster_replicates_through_faults() {
+        let network = MemoryNetwork::new();
+        let mut nodes = cluster("memory", &network);
+
+        let (tx, rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            run(&mut nodes, 50);
+            let first = leader(&nodes).expect("the cluster should elect a leader");
+
+            nodes[first]
+                .0
+                .handle(Command::new(tx.clone(), public::set_request("a", "1")));
+            run(&mut nodes, 1);
+            for node in &nodes {
+                assert_eq!(value(node, "a"), Some(String::from("1")));
+            }
+
+            // Cut a follower off, the other two still make a quorum. Messages
+            // to the remaining follower arrive twice along the way.
+            let lost = (first + 1) % 3;
+            let other = (first + 2) % 3;
+            for &node in &[first, other] {
+                network.set_fault(lost as u64 + 1, node as u64 + 1, Fault::Drop);
+                network.set_fault(node as u64 + 1, lost as u64 + 1, Fault::Drop);
+            }
+            network.set_fault(first as u64 + 1, other as u64 + 1, Fault::Duplicate);
+
+            nodes[first]
+                .0
+                .handle(Command::new(tx.clone(), public::set_request("b", "2")));
+            run(&mut nodes, 5);
+            assert_eq!(value(&nodes[first], "b"), Some(String::from("2")));
+            assert_eq!(value(&nodes[other], "b"), Some(String::from("2")));
+            assert_eq!(value(&nodes[lost], "b"), None);
+
+            // Healed, the lost follower catches up, whoever leads by then
+            for &node in &[first, other] {
+                network.clear_fault(lost as u64 + 1, node as u64 + 1);
+                network.clear_fault(node as u64 + 1, lost as u64 + 1);
+            }
+            network.clear_fault(first as u64 + 1, other as u64 + 1);
+            run(&mut nodes, 100);
+            assert!(leader(&nodes).is_some());
+            assert_eq!(value(&nodes[lost], "b"), Some(String::from("2")));
+        });
+
+        let responses: Vec<public::Response> = rx.wait().map(|r| r.unwrap()).collect();
+        assert_eq!(responses.len(), 2);
+        assert!(responses.iter().all(|response| response.get_success()));
+    }
+
+    #[test]
+    fn test_a_node_added_to_a_cluster_records_its_token() {
+        let network = MemoryNetwork::new();
+        let mut nodes = cluster("joining", &network);
+        let file = "/tmp/kv-raft-joining-4";
+        remove_data(file);
+        let (transport, inbox) = network.join(4);
+        let mut joining = Db::new(4, file, transport, key_value(file), None).unwrap();
+
+        let (tx, rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            run(&mut nodes, 50);
+            let first = leader(&nodes).expect("the cluster should elect a leader");
+
+            // The node hears from every member as they connect to it
+            for id in 1..4 {
+                let mut hello = proto::Hello::new();
+                hello.set_id(id);
+                hello.set_addr(format!("node-{}", id));
+                hello.set_cluster(String::from("joining"));
+                joining.receive(Message::Hello(hello));
+            }
+            assert_eq!(storage::cluster_token(file).unwrap(), None);
+
+            let add = public::add_node_request(4, "node-4".into(), "".into(), false);
+            nodes[first].0.handle(Command::new(tx, add));
+            nodes.push((joining, inbox));
+            run(&mut nodes, 50);
+            assert_eq!(
+                storage::cluster_token(file).unwrap(),
+                Some(String::from("joining"))
+            );
+        });
+
+        let responses: Vec<public::Response> = rx.wait().map(|r| r.unwrap()).collect();
+        assert_eq!(responses.len(), 1);
+        assert!(responses[0].get_success());
+    }
+
+    #[test]
+    fn test_lagging_follower_catches_up_from_a_snapshot() {
+        let network = MemoryNetwork::new();
+        let mut nodes = cluster("snapshot", &network);
+
+        let (tx, rx) = mpsc::channel(1024);
+        in_runtime(move || {
+            run(&mut nodes, 50);
+            let first = leader(&nodes).expect("the cluster should elect a leader");
+            let lost = (first + 1) % 3;
+            let other = (first + 2) % 3;
+            for &node in &[first, other] {
+                network.set_fault(lost as u64 + 1, node as u64 + 1, Fault::Drop);
+                network.set_fault(node as u64 + 1, lost as u64 + 1, Fault::Drop);
+            }
+
+            nodes[first]
+                .0
+                .handle(Command::new(tx.clone(), public::set_request("a", "1")));
+            run(&mut nodes, 5);
+
+            // The log the lost follower needs is compacted away, so only a
+            // snapshot catches it up. Its chunks arrive twice along the way.
+            for &node in &[first, other] {
+                nodes[node].0.flush();
+            }
+            for &node in &[first, other] {
+                network.clear_fault(lost as u64 + 1, node as u64 + 1);
+                network.clear_fault(node as u64 + 1, lost as u64 + 1);
+                network.set_fault(node as u64 + 1, lost as u64 + 1, Fault::Duplicate);
+            }
+            run(&mut nodes, 50);
+            assert_eq!(value(&nodes[lost], "a"), Some(String::from("1")));
+        });
+
+        let responses: Vec<public::Response> = rx.wait().map(|r| r.unwrap()).collect();
+        assert_eq!(responses.len(), 1);
+        assert!(responses[0].get_success());
+    }
 }
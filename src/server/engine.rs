@@ -0,0 +1,841 @@
+//! An ordered, on-disk key-value engine that backs the state machine, so the
+//! dataset isn't bound by memory.
+//!
+//! Writes land in a memory table sorted by key. When it grows too large, or
+//! when the storage takes a snapshot, it's flushed to an immutable sorted
+//! table on disk. Reads check the memory table first and then the tables from
+//! newest to oldest. Once there are too many tables they are merged into one.
+//!
+//! The engine keeps no log of its own: everything applied since the last
+//! flush is replayed from the raft log on startup. The manifest records which
+//! tables are live and the raft index they hold everything up to.
+
+use super::proto;
+use super::storage::{read_file, write_file, Error};
+use bytes::{Buf, BufMut, IntoBuf};
+use protobuf::{parse_from_bytes, Message};
+use std::collections::btree_map::{self, BTreeMap};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::iter::Rev;
+use std::mem;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The memory table is flushed once its keys and values take this much.
+const MEMTABLE_BYTES: usize = 4 * 1024 * 1024;
+/// Tables are merged into one when there are more than this many.
+const MAX_TABLES: usize = 4;
+/// Every nth key of a table is kept in memory to seek with.
+const INDEX_INTERVAL: usize = 16;
+const TABLE_MAGIC: &[u8] = b"KVST";
+const TABLE_EXTENSION: &str = "sst";
+/// Index offset, index length and the magic number.
+const FOOTER: u64 = 20;
+const MANIFEST: &str = "MANIFEST";
+
+/// A key and its value, or `None` when the key was deleted.
+type Record = (String, Option<String>);
+
+pub struct Engine {
+    dir: PathBuf,
+    memtable: BTreeMap<String, Option<String>>,
+    memtable_bytes: usize,
+    /// Oldest first.
+    tables: Vec<Table>,
+    next_table: u64,
+    applied: u64,
+}
+
+impl Engine {
+    pub fn open<P: Into<PathBuf>>(dir: P) -> Result<Engine, Error> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let manifest_path = dir.join(MANIFEST);
+        let manifest = if manifest_path.is_file() {
+            parse_from_bytes::<proto::Manifest>(&read_file(&manifest_path)?)?
+        } else {
+            proto::Manifest::new()
+        };
+
+        let mut tables = Vec::with_capacity(manifest.get_tables().len());
+        for id in manifest.get_tables() {
+            tables.push(Table::open(*id, table_path(&dir, *id))?);
+        }
+
+        let engine = Engine {
+            dir,
+            memtable: BTreeMap::new(),
+            memtable_bytes: 0,
+            tables,
+            next_table: manifest.get_next_table(),
+            applied: manifest.get_applied(),
+        };
+        engine.remove_orphans()?;
+        Ok(engine)
+    }
+
+    /// The raft index of the last entry applied to the engine.
+    pub fn applied(&self) -> u64 {
+        self.applied
+    }
+
+    pub fn set_applied(&mut self, index: u64) {
+        self.applied = index;
+    }
+
+    pub fn get(&self, key: &str) -> io::Result<Option<String>> {
+        if let Some(value) = self.memtable.get(key) {
+            return Ok(value.clone());
+        }
+
+        for table in self.tables.iter().rev() {
+            if let Some(value) = table.get(key)? {
+                return Ok(value);
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.memtable_bytes += key.len() + value.len();
+        self.memtable
+            .insert(key.to_string(), Some(value.to_string()));
+    }
+
+    /// Deletes the key and returns its value. A key that can't be read is
+    /// still deleted, so the engine ends up the same as on every other node,
+    /// and only the value is lost.
+    pub fn delete(&mut self, key: &str) -> io::Result<Option<String>> {
+        let previous = self.get(key);
+        if previous.as_ref().map_or(true, Option::is_some) {
+            // The tombstone hides the value in any older table
+            self.memtable_bytes += key.len();
+            self.memtable.insert(key.to_string(), None);
+        }
+        previous
+    }
+
+    /// Visits every live key in order, starting at `from`, until `visit`
+    /// returns false.
+    pub fn for_each<F>(&self, from: Option<&str>, visit: F) -> io::Result<()>
+    where
+        F: FnMut(String, String) -> io::Result<bool>,
+    {
+        merge(self.sources(from)?, false, visit)
+    }
+
+    /// Visits every live key in reverse order, starting at the last one
+    /// before `below`, until `visit` returns false.
+    pub fn for_each_rev<F>(&self, below: Option<&str>, visit: F) -> io::Result<()>
+    where
+        F: FnMut(String, String) -> io::Result<bool>,
+    {
+        let range = match below {
+            Some(below) => self
+                .memtable
+                .range::<str, _>((Bound::Unbounded, Bound::Excluded(below))),
+            None => self.memtable.range::<str, _>(..),
+        };
+
+        let mut sources = vec![Source::MemRev(range.rev())];
+        for table in self.tables.iter().rev() {
+            sources.push(Source::TableRev(table.rev_reader(below)?));
+        }
+        merge(sources, true, visit)
+    }
+
+    pub fn needs_flush(&self) -> bool {
+        self.memtable_bytes >= MEMTABLE_BYTES
+    }
+
+    /// Flushes the memory table and records the applied index. Once this
+    /// returns, everything up to `applied` survives a restart.
+    pub fn checkpoint(&mut self) -> Result<(), Error> {
+        if !self.memtable.is_empty() {
+            let id = self.allocate_table();
+            let mut builder = TableBuilder::create(&self.dir, id)?;
+            for (key, value) in &self.memtable {
+                builder.add(key, value.as_ref().map(String::as_str))?;
+            }
+            self.tables.push(builder.finish()?);
+            self.memtable.clear();
+            self.memtable_bytes = 0;
+        }
+
+        let merged = if self.tables.len() > MAX_TABLES {
+            self.merge_tables()?
+        } else {
+            vec![]
+        };
+
+        self.write_manifest()?;
+
+        // Only once the manifest no longer points at them
+        for table in merged {
+            fs::remove_file(&table.path)?;
+        }
+        Ok(())
+    }
+
+    /// Streams every live key and value to `out` as length delimited datums.
+    pub fn write_snapshot<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        self.for_each(None, |key, value| {
+            let mut datum = proto::Datum::new();
+            datum.set_key(key);
+            datum.set_value(value);
+            write_delimited(out, &datum)?;
+            Ok(true)
+        })
+    }
+
+    /// Replaces the contents of the engine with a snapshot written by
+    /// `write_snapshot`. Datums are read one at a time and go straight into a
+    /// new table, since a snapshot arrives in key order.
+    pub fn restore<R: Read>(&mut self, index: u64, input: &mut R) -> Result<(), Error> {
+        let id = self.allocate_table();
+        let mut builder = TableBuilder::create(&self.dir, id)?;
+        while let Some(datum) = read_delimited::<proto::Datum, _>(input)? {
+            builder.add(datum.get_key(), Some(datum.get_value()))?;
+        }
+
+        let replaced = mem::replace(&mut self.tables, vec![builder.finish()?]);
+        self.memtable.clear();
+        self.memtable_bytes = 0;
+        self.applied = index;
+        self.write_manifest()?;
+
+        for table in replaced {
+            fs::remove_file(&table.path)?;
+        }
+        Ok(())
+    }
+
+    /// The memory table followed by each table, newest first.
+    fn sources(&self, from: Option<&str>) -> io::Result<Vec<Source>> {
+        let range = match from {
+            Some(from) => self
+                .memtable
+                .range::<str, _>((Bound::Included(from), Bound::Unbounded)),
+            None => self.memtable.range::<str, _>(..),
+        };
+
+        let mut sources = vec![Source::Mem(range)];
+        for table in self.tables.iter().rev() {
+            sources.push(Source::Table(table.reader(from)?));
+        }
+        Ok(sources)
+    }
+
+    /// Merges every table into a single new one and returns the old tables.
+    /// Tombstones are dropped since there's nothing older left to hide.
+    fn merge_tables(&mut self) -> Result<Vec<Table>, Error> {
+        let id = self.allocate_table();
+        let mut builder = TableBuilder::create(&self.dir, id)?;
+        {
+            let mut sources = Vec::with_capacity(self.tables.len());
+            for table in self.tables.iter().rev() {
+                sources.push(Source::Table(table.reader(None)?));
+            }
+            merge(sources, false, |key, value| {
+                builder.add(&key, Some(&value))?;
+                Ok(true)
+            })?;
+        }
+        let merged = builder.finish()?;
+        println!("Merged {} tables into table {}", self.tables.len(), id);
+        Ok(mem::replace(&mut self.tables, vec![merged]))
+    }
+
+    fn allocate_table(&mut self) -> u64 {
+        let id = self.next_table;
+        self.next_table += 1;
+        id
+    }
+
+    fn write_manifest(&self) -> Result<(), Error> {
+        let mut manifest = proto::Manifest::new();
+        manifest.set_applied(self.applied);
+        manifest.set_tables(self.tables.iter().map(|t| t.id).collect());
+        manifest.set_next_table(self.next_table);
+        write_file(&self.dir.join(MANIFEST), &manifest.write_to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Removes tables a crash left behind before they made the manifest.
+    fn remove_orphans(&self) -> io::Result<()> {
+        for dirent in fs::read_dir(&self.dir)? {
+            let path = dirent?.path();
+            let orphan = match path.extension().and_then(|e| e.to_str()) {
+                Some("tmp") => true,
+                Some(TABLE_EXTENSION) => !self.tables.iter().any(|t| t.path == path),
+                _ => false,
+            };
+            if orphan {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Walks the sources in key order, or in reverse, and visits each live key
+/// once. Sources are ordered newest first, so on a tie the first source wins.
+fn merge<F>(mut sources: Vec<Source>, reverse: bool, mut visit: F) -> io::Result<()>
+where
+    F: FnMut(String, String) -> io::Result<bool>,
+{
+    let mut heads = Vec::with_capacity(sources.len());
+    for source in &mut sources {
+        heads.push(source.next()?);
+    }
+
+    loop {
+        let mut min: Option<usize> = None;
+        for (i, head) in heads.iter().enumerate() {
+            if let Some((ref key, _)) = *head {
+                let first = match min {
+                    Some(m) => heads[m]
+                        .as_ref()
+                        .map_or(true, |h| if reverse { key > &h.0 } else { key < &h.0 }),
+                    None => true,
+                };
+                if first {
+                    min = Some(i);
+                }
+            }
+        }
+
+        let winner = match min {
+            Some(winner) => winner,
+            None => return Ok(()),
+        };
+
+        let (key, value) = heads[winner].take().expect("Winning head");
+        for i in 0..heads.len() {
+            let advance = i == winner || heads[i].as_ref().map_or(false, |h| h.0 == key);
+            if advance {
+                heads[i] = sources[i].next()?;
+            }
+        }
+
+        if let Some(value) = value {
+            if !visit(key, value)? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+enum Source<'a> {
+    Mem(btree_map::Range<'a, String, Option<String>>),
+    MemRev(Rev<btree_map::Range<'a, String, Option<String>>>),
+    Table(TableReader<BufReader<File>>),
+    TableRev(TableRevReader),
+}
+
+impl<'a> Source<'a> {
+    fn next(&mut self) -> io::Result<Option<Record>> {
+        match *self {
+            Source::Mem(ref mut range) => Ok(range.next().map(|(k, v)| (k.clone(), v.clone()))),
+            Source::MemRev(ref mut range) => {
+                Ok(range.next().map(|(k, v)| (k.clone(), v.clone())))
+            }
+            Source::Table(ref mut reader) => reader.next(),
+            Source::TableRev(ref mut reader) => reader.next(),
+        }
+    }
+}
+
+/// An immutable, sorted table on disk. A table is a run of records, then
+/// a sparse index of every `INDEX_INTERVAL`th key and its offset, then the
+/// footer.
+struct Table {
+    id: u64,
+    path: PathBuf,
+    index: Vec<(String, u64)>,
+    /// Where the records end and the index begins.
+    data_len: u64,
+    /// Kept open for lookups, which seek it to the key they're after. Scans
+    /// open their own, since they hold their place between records.
+    file: Mutex<File>,
+}
+
+impl Table {
+    fn open(id: u64, path: PathBuf) -> Result<Table, Error> {
+        let mut file = File::open(&path)?;
+        let len = file.metadata()?.len();
+        if len < FOOTER {
+            return Err(Error::Truncated(path));
+        }
+
+        let mut footer = [0u8; FOOTER as usize];
+        file.seek(SeekFrom::Start(len - FOOTER))?;
+        file.read_exact(&mut footer)?;
+        if &footer[16..] != TABLE_MAGIC {
+            return Err(Error::BadMagic(path));
+        }
+
+        let mut buf = (&footer[..16]).into_buf();
+        let data_len = buf.get_u64_be();
+        let count = buf.get_u64_be() as usize;
+        if data_len > len - FOOTER {
+            return Err(Error::Truncated(path));
+        }
+
+        let mut raw = vec![0; (len - FOOTER - data_len) as usize];
+        file.seek(SeekFrom::Start(data_len))?;
+        file.read_exact(&mut raw)?;
+
+        let mut input = &raw[..];
+        let mut index = Vec::with_capacity(count);
+        for _ in 0..count {
+            let len = read_u32(&mut input)? as usize;
+            let key = read_string(&mut input, len)?;
+            let mut offset = [0u8; 8];
+            input.read_exact(&mut offset)?;
+            index.push((key, (&offset[..]).into_buf().get_u64_be()));
+        }
+
+        Ok(Table {
+            id,
+            path,
+            index,
+            data_len,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// `Some(None)` means the table holds a tombstone for the key.
+    fn get(&self, key: &str) -> io::Result<Option<Option<String>>> {
+        let start = self.seek_offset(key);
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start(start))?;
+
+        let mut reader = TableReader {
+            input: BufReader::new(&mut *file),
+            pos: start,
+            end: self.data_len,
+            from: Some(key.to_string()),
+        };
+        match reader.next()? {
+            Some((found, value)) => Ok(if found == key { Some(value) } else { None }),
+            None => Ok(None),
+        }
+    }
+
+    fn reader(&self, from: Option<&str>) -> io::Result<TableReader<BufReader<File>>> {
+        let start = from.map_or(0, |key| self.seek_offset(key));
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(start))?;
+
+        Ok(TableReader {
+            input: BufReader::new(file),
+            pos: start,
+            end: self.data_len,
+            from: from.map(str::to_string),
+        })
+    }
+
+    /// Reads the records before `below` from the last backwards, a run of
+    /// `INDEX_INTERVAL` records at a time.
+    fn rev_reader(&self, below: Option<&str>) -> io::Result<TableRevReader> {
+        // Runs that start at or past `below` hold nothing before it
+        let runs = match below {
+            Some(below) => match self
+                .index
+                .binary_search_by(|entry| entry.0.as_str().cmp(below))
+            {
+                Ok(i) | Err(i) => i,
+            },
+            None => self.index.len(),
+        };
+        let end = self.index.get(runs).map_or(self.data_len, |entry| entry.1);
+
+        Ok(TableRevReader {
+            input: File::open(&self.path)?,
+            starts: self.index[..runs].iter().map(|entry| entry.1).collect(),
+            end,
+            run: vec![],
+            below: below.map(str::to_string),
+        })
+    }
+
+    /// The offset of the last indexed key at or before `key`.
+    fn seek_offset(&self, key: &str) -> u64 {
+        match self
+            .index
+            .binary_search_by(|entry| entry.0.as_str().cmp(key))
+        {
+            Ok(i) => self.index[i].1,
+            Err(0) => 0,
+            Err(i) => self.index[i - 1].1,
+        }
+    }
+}
+
+struct TableReader<R> {
+    input: R,
+    pos: u64,
+    end: u64,
+    /// Records before this key are skipped.
+    from: Option<String>,
+}
+
+impl<R: Read> TableReader<R> {
+    fn next(&mut self) -> io::Result<Option<Record>> {
+        while self.pos < self.end {
+            let (record, len) = decode_record(&mut self.input)?;
+            self.pos += len;
+
+            let skip = match self.from {
+                Some(ref from) => record.0 < *from,
+                None => false,
+            };
+            if !skip {
+                self.from = None;
+                return Ok(Some(record));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Walks a table backwards. The records of a run can only be read forwards,
+/// so each run is read whole and then handed out from its last record.
+struct TableRevReader {
+    input: File,
+    /// Where each run left to read starts, the next one last.
+    starts: Vec<u64>,
+    /// Where the next run to read ends.
+    end: u64,
+    /// What's left of the current run, its last record at the end.
+    run: Vec<Record>,
+    /// Records from this key on are skipped.
+    below: Option<String>,
+}
+
+impl TableRevReader {
+    fn next(&mut self) -> io::Result<Option<Record>> {
+        loop {
+            if let Some(record) = self.run.pop() {
+                return Ok(Some(record));
+            }
+            let start = match self.starts.pop() {
+                Some(start) => start,
+                None => return Ok(None),
+            };
+
+            let mut raw = vec![0; (self.end - start) as usize];
+            self.input.seek(SeekFrom::Start(start))?;
+            self.input.read_exact(&mut raw)?;
+            self.end = start;
+
+            let mut input = &raw[..];
+            while !input.is_empty() {
+                let (record, _) = decode_record(&mut input)?;
+                let skip = match self.below {
+                    Some(ref below) => record.0 >= *below,
+                    None => false,
+                };
+                if !skip {
+                    self.run.push(record);
+                }
+            }
+        }
+    }
+}
+
+/// Tables are written to a temporary file and renamed into place once
+/// they're complete and synced.
+struct TableBuilder {
+    id: u64,
+    path: PathBuf,
+    tmp: PathBuf,
+    out: BufWriter<File>,
+    index: Vec<(String, u64)>,
+    offset: u64,
+    count: usize,
+    buf: Vec<u8>,
+}
+
+impl TableBuilder {
+    fn create(dir: &Path, id: u64) -> io::Result<TableBuilder> {
+        let path = table_path(dir, id);
+        let tmp = path.with_extension("tmp");
+        let out = BufWriter::new(File::create(&tmp)?);
+
+        Ok(TableBuilder {
+            id,
+            path,
+            tmp,
+            out,
+            index: vec![],
+            offset: 0,
+            count: 0,
+            buf: vec![],
+        })
+    }
+
+    /// Keys must be added in order.
+    fn add(&mut self, key: &str, value: Option<&str>) -> io::Result<()> {
+        if self.count % INDEX_INTERVAL == 0 {
+            self.index.push((key.to_string(), self.offset));
+        }
+
+        self.buf.clear();
+        encode_record(&mut self.buf, key, value);
+        self.out.write_all(&self.buf)?;
+        self.offset += self.buf.len() as u64;
+        self.count += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<Table> {
+        let mut buf = vec![];
+        for &(ref key, offset) in &self.index {
+            buf.put_u32_be(key.len() as u32);
+            buf.extend_from_slice(key.as_bytes());
+            buf.put_u64_be(offset);
+        }
+        buf.put_u64_be(self.offset);
+        buf.put_u64_be(self.index.len() as u64);
+        buf.extend_from_slice(TABLE_MAGIC);
+        self.out.write_all(&buf)?;
+        self.out.flush()?;
+        self.out.get_ref().sync_all()?;
+
+        fs::rename(&self.tmp, &self.path)?;
+        if let Some(dir) = self.path.parent() {
+            File::open(dir)?.sync_all()?;
+        }
+
+        let file = File::open(&self.path)?;
+        Ok(Table {
+            id: self.id,
+            path: self.path,
+            index: self.index,
+            data_len: self.offset,
+            file: Mutex::new(file),
+        })
+    }
+}
+
+/// A record is the key length and key, a tag that marks a value or a
+/// tombstone, and then the value length and value if there is one.
+fn encode_record(buf: &mut Vec<u8>, key: &str, value: Option<&str>) {
+    buf.put_u32_be(key.len() as u32);
+    buf.extend_from_slice(key.as_bytes());
+    match value {
+        Some(value) => {
+            buf.put_u8(1);
+            buf.put_u32_be(value.len() as u32);
+            buf.extend_from_slice(value.as_bytes());
+        }
+        None => buf.put_u8(0),
+    }
+}
+
+fn decode_record<R: Read>(input: &mut R) -> io::Result<(Record, u64)> {
+    let key_len = read_u32(input)? as usize;
+    let key = read_string(input, key_len)?;
+
+    let mut tag = [0u8; 1];
+    input.read_exact(&mut tag)?;
+
+    let (value, value_len) = if tag[0] == 1 {
+        let len = read_u32(input)? as usize;
+        (Some(read_string(input, len)?), 4 + len)
+    } else {
+        (None, 0)
+    };
+
+    let len = 4 + key_len + 1 + value_len;
+    Ok(((key, value), len as u64))
+}
+
+fn read_u32<R: Read>(input: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    input.read_exact(&mut buf)?;
+    Ok((&buf[..]).into_buf().get_u32_be())
+}
+
+fn read_string<R: Read>(input: &mut R, len: usize) -> io::Result<String> {
+    let mut buf = vec![0; len];
+    input.read_exact(&mut buf)?;
+    String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Writes a protobuf prefixed by its length.
+pub fn write_delimited<W: Write, M: Message>(out: &mut W, msg: &M) -> io::Result<()> {
+    let bytes = msg
+        .write_to_bytes()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))?;
+    let mut header = vec![];
+    header.put_u32_be(bytes.len() as u32);
+    out.write_all(&header)?;
+    out.write_all(&bytes)
+}
+
+/// Reads a protobuf written by `write_delimited`, or `None` at the end of
+/// the input.
+pub fn read_delimited<M: Message, R: Read>(input: &mut R) -> io::Result<Option<M>> {
+    let mut header = [0u8; 4];
+    let mut read = 0;
+    while read < header.len() {
+        match input.read(&mut header[read..])? {
+            0 if read == 0 => return Ok(None),
+            0 => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Partial header",
+                ))
+            }
+            n => read += n,
+        }
+    }
+
+    let len = (&header[..]).into_buf().get_u32_be() as usize;
+    let mut buf = vec![0; len];
+    input.read_exact(&mut buf)?;
+    parse_from_bytes::<M>(&buf)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))
+}
+
+fn table_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{:010}.{}", id, TABLE_EXTENSION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("kv-raft-engine-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn keys(engine: &Engine) -> Vec<String> {
+        let mut keys = vec![];
+        engine
+            .for_each(None, |key, _| {
+                keys.push(key);
+                Ok(true)
+            })
+            .unwrap();
+        keys
+    }
+
+    #[test]
+    fn test_reads_across_flushes_and_restarts() {
+        let dir = temp_dir("restart");
+
+        let mut engine = Engine::open(&dir).unwrap();
+        engine.set("a", "1");
+        engine.set("b", "2");
+        engine.set_applied(2);
+        engine.checkpoint().unwrap();
+
+        engine.set("b", "3");
+        assert_eq!(engine.delete("a").unwrap(), Some(String::from("1")));
+        assert_eq!(engine.delete("missing").unwrap(), None);
+        engine.set_applied(4);
+        engine.checkpoint().unwrap();
+
+        // Not checkpointed, so it is gone after a restart
+        engine.set("c", "4");
+        drop(engine);
+
+        let engine = Engine::open(&dir).unwrap();
+        assert_eq!(engine.applied(), 4);
+        assert_eq!(engine.get("a").unwrap(), None);
+        assert_eq!(engine.get("b").unwrap(), Some(String::from("3")));
+        assert_eq!(engine.get("c").unwrap(), None);
+    }
+
+    #[test]
+    fn test_keys_are_ordered_and_merged() {
+        let dir = temp_dir("ordered");
+
+        let mut engine = Engine::open(&dir).unwrap();
+        for round in 0..(MAX_TABLES + 2) {
+            for i in 0..100 {
+                engine.set(&format!("key-{:03}", (i * 7 + round) % 100), "value");
+            }
+            engine.checkpoint().unwrap();
+        }
+        assert!(engine.tables.len() <= MAX_TABLES);
+
+        engine.delete("key-050").unwrap();
+        let keys = keys(&engine);
+        assert_eq!(keys.len(), 99);
+
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+        assert!(!keys.contains(&String::from("key-050")));
+    }
+
+    #[test]
+    fn test_reverse_walks_match_forward_ones() {
+        let mut engine = Engine::open(temp_dir("reverse")).unwrap();
+        for round in 0..3 {
+            for i in 0..100 {
+                engine.set(&format!("key-{:03}", (i * 7 + round) % 100), "value");
+            }
+            engine.checkpoint().unwrap();
+        }
+        engine.delete("key-050").unwrap();
+        engine.set("key-100", "value");
+
+        let mut forward = keys(&engine);
+        forward.reverse();
+        let reverse = |below: Option<&str>| {
+            let mut keys = vec![];
+            engine
+                .for_each_rev(below, |key, _| {
+                    keys.push(key);
+                    Ok(true)
+                })
+                .unwrap();
+            keys
+        };
+        assert_eq!(reverse(None), forward);
+
+        let below: Vec<String> = forward
+            .iter()
+            .filter(|key| key.as_str() < "key-033")
+            .cloned()
+            .collect();
+        assert_eq!(reverse(Some("key-033")), below);
+        assert!(reverse(Some("key-000")).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut source = Engine::open(temp_dir("snap-source")).unwrap();
+        for i in 0..50 {
+            source.set(&format!("key-{:02}", i), &format!("value-{}", i));
+        }
+        source.checkpoint().unwrap();
+        source.set("key-00", "updated");
+
+        let mut data = vec![];
+        source.write_snapshot(&mut data).unwrap();
+
+        let mut dest = Engine::open(temp_dir("snap-dest")).unwrap();
+        dest.set("stale", "value");
+        dest.restore(10, &mut &data[..]).unwrap();
+
+        assert_eq!(dest.applied(), 10);
+        assert_eq!(dest.get("stale").unwrap(), None);
+        assert_eq!(dest.get("key-00").unwrap(), Some(String::from("updated")));
+        assert_eq!(keys(&dest), keys(&source));
+    }
+}
@@ -0,0 +1,531 @@
+//! Serves the public API as JSON over HTTP, for clients that can't speak the
+//! framed protobuf protocol. Each HTTP request is turned into the same
+//! command a client connection sends to the database.
+
+use super::public::{ask, Handle};
+use super::Message;
+use codec::DEFAULT_MAX_FRAME;
+use futures::future;
+use futures::prelude::*;
+use futures::sync::{mpsc, oneshot};
+use hyper::header::{HeaderMap, CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
+use hyper::{self, Body, Method, StatusCode};
+use public::{self, request, response::ErrorCode, Request, Response};
+use serde_json::{self, Value};
+use std::collections::HashMap;
+use std::io;
+use std::net::{self, SocketAddr};
+use std::str;
+use std::thread;
+use tokio;
+use tokio::net::TcpListener;
+use tokio::reactor::Handle as Reactor;
+use tokio::runtime::Runtime;
+
+type HttpResponse = hyper::Response<Body>;
+
+/// Why a request was refused before it reached the database.
+type Rejection = (StatusCode, String);
+
+/// Listens for HTTP requests on `addr` and sends them to the database. The
+/// address is bound before this returns, so a port that's taken is an error
+/// for the caller.
+pub fn listen(db_channel: mpsc::Sender<Message>, addr: &SocketAddr) -> io::Result<Handle> {
+    let listener = net::TcpListener::bind(addr)?;
+    let (stop, stopped) = oneshot::channel::<()>();
+    let (close, closed) = oneshot::channel::<()>();
+    let handle = thread::spawn(move || {
+        // Registered with the runtime's reactor once it's first polled
+        let listener = TcpListener::from_std(listener, &Reactor::default())
+            .expect("Unable to start the HTTP listener");
+        let closed = closed.shared();
+        let http = Http::new();
+
+        let server = listener
+            .incoming()
+            .map_err(handle_err)
+            .for_each(move |sock| {
+                let db_channel = db_channel.clone();
+                let service = service_fn(move |req| serve(db_channel.clone(), req));
+                tokio::spawn(
+                    http.serve_connection(sock, service)
+                        .map_err(handle_err)
+                        .select2(closed.clone())
+                        .then(|_| Ok(())),
+                );
+                Ok(())
+            });
+
+        let mut runtime = Runtime::new().expect("Unable to start the HTTP listener");
+        let _ = runtime.block_on(server.select2(stopped));
+        runtime
+            .shutdown_on_idle()
+            .wait()
+            .expect("HTTP listener failed to shut down");
+    });
+
+    Ok(Handle::new(handle, stop, close))
+}
+
+fn serve(
+    db_channel: mpsc::Sender<Message>,
+    req: hyper::Request<Body>,
+) -> impl Future<Item = HttpResponse, Error = hyper::Error> {
+    let (parts, body) = req.into_parts();
+    read_body(&parts.headers, body).and_then(move |body| {
+        let query = parts.uri.query().unwrap_or("");
+        let routed = body
+            .ok_or_else(too_large)
+            .and_then(|body| route(&parts.method, parts.uri.path(), query, &body));
+        let reply: Box<Future<Item = HttpResponse, Error = hyper::Error> + Send> = match routed {
+            Ok(request) => Box::new(ask(db_channel, request).then(|response| {
+                Ok(match response {
+                    Ok(response) => render(response),
+                    Err(()) => error(StatusCode::SERVICE_UNAVAILABLE, "the node is stopping"),
+                })
+            })),
+            Err((status, message)) => Box::new(future::ok(error(status, &message))),
+        };
+        reply
+    })
+}
+
+/// Reads the body, unless it's larger than a client's frame may be. That's
+/// known from the `Content-Length` when there is one, and otherwise reading
+/// stops once the limit has been passed.
+fn read_body(
+    headers: &HeaderMap,
+    body: Body,
+) -> impl Future<Item = Option<Vec<u8>>, Error = hyper::Error> {
+    let declared = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok())
+        .and_then(|len| len.parse::<u64>().ok());
+    if declared.map_or(false, |len| len > DEFAULT_MAX_FRAME as u64) {
+        return future::Either::A(future::ok(None));
+    }
+
+    let read = body.map_err(Some).fold(Vec::new(), |mut buf, chunk| {
+        if buf.len() + chunk.len() > DEFAULT_MAX_FRAME {
+            return Err(None);
+        }
+        buf.extend_from_slice(&chunk);
+        Ok(buf)
+    });
+    future::Either::B(read.then(|read| match read {
+        Ok(buf) => Ok(Some(buf)),
+        Err(None) => Ok(None),
+        Err(Some(e)) => Err(e),
+    }))
+}
+
+fn too_large() -> Rejection {
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!("the body is larger than {} bytes", DEFAULT_MAX_FRAME),
+    )
+}
+
+/// Maps a method and path onto the request it stands for.
+fn route(method: &Method, path: &str, query: &str, body: &[u8]) -> Result<Request, Rejection> {
+    let params = params(query)?;
+
+    if path.starts_with("/v1/kv/") {
+        let key = decode(&path["/v1/kv/".len()..])?;
+        if key.is_empty() {
+            return Err(bad_request("the key is empty"));
+        }
+        return match *method {
+            Method::GET => {
+                let (consistency, max_lag) = consistency(&params)?;
+                Ok(public::get_request_with(&key, consistency, max_lag))
+            }
+            Method::PUT => {
+                let value = str::from_utf8(body)
+                    .map_err(|_| bad_request("the value isn't valid UTF-8"))?;
+                Ok(public::set_request(&key, value))
+            }
+            Method::DELETE => Ok(public::delete_request(&key)),
+            _ => Err(not_allowed()),
+        };
+    }
+
+    let segments: Vec<&str> = path.trim_right_matches('/').split('/').collect();
+    match (method, &segments[..]) {
+        (&Method::GET, ["", "v1", "kv"]) => Ok(public::range_scan_request(scan(&params)?)),
+        (&Method::GET, ["", "v1", "cluster", "info"]) => Ok(public::info_request()),
+        (&Method::POST, ["", "v1", "cluster", "nodes"]) => {
+            let body = json_body(body)?;
+            Ok(public::add_node_request(
+                required_id(&body, "id")?,
+                required_str(&body, "addr")?,
+                optional_str(&body, "public_addr"),
+                body.get("learner").and_then(Value::as_bool).unwrap_or(false),
+            ))
+        }
+        (&Method::PUT, ["", "v1", "cluster", "nodes", id]) => {
+            let body = json_body(body)?;
+            Ok(public::update_peer_address_request(
+                parse_id(id)?,
+                required_str(&body, "addr")?,
+                optional_str(&body, "public_addr"),
+            ))
+        }
+        (&Method::DELETE, ["", "v1", "cluster", "nodes", id]) => {
+            Ok(public::remove_node_request(parse_id(id)?))
+        }
+        (&Method::POST, ["", "v1", "cluster", "nodes", id, "promote"]) => {
+            Ok(public::promote_node_request(parse_id(id)?))
+        }
+        (&Method::POST, ["", "v1", "cluster", "leader"]) => {
+            let body = json_body(body)?;
+            Ok(public::transfer_leader_request(required_id(&body, "id")?))
+        }
+        (_, ["", "v1", "kv"])
+        | (_, ["", "v1", "cluster", "info"])
+        | (_, ["", "v1", "cluster", "nodes"])
+        | (_, ["", "v1", "cluster", "nodes", _])
+        | (_, ["", "v1", "cluster", "nodes", _, "promote"])
+        | (_, ["", "v1", "cluster", "leader"]) => Err(not_allowed()),
+        _ => Err((StatusCode::NOT_FOUND, format!("nothing is served at {}", path))),
+    }
+}
+
+/// Builds a scan from the query string. Every bound is optional.
+fn scan(params: &HashMap<String, String>) -> Result<request::Scan, Rejection> {
+    let mut scan = request::Scan::new();
+    let text = |name: &str| params.get(name).cloned().unwrap_or_default();
+    scan.set_start(text("start"));
+    scan.set_end(text("end"));
+    scan.set_prefix(text("prefix"));
+    scan.set_token(text("token"));
+    if let Some(limit) = params.get("limit") {
+        scan.set_limit(
+            limit
+                .parse()
+                .map_err(|_| bad_request("the limit isn't a number"))?,
+        );
+    }
+    scan.set_reverse(flag(params, "reverse")?);
+    scan.set_include_values(flag(params, "values")?);
+
+    let (consistency, max_lag) = consistency(params)?;
+    scan.set_consistency(consistency);
+    scan.set_max_lag(max_lag);
+    Ok(scan)
+}
+
+fn consistency(
+    params: &HashMap<String, String>,
+) -> Result<(request::Consistency, u64), Rejection> {
+    let consistency = match params.get("consistency").map(String::as_str) {
+        None | Some("linearizable") => request::Consistency::LINEARIZABLE,
+        Some("lease") => request::Consistency::LEASE,
+        Some("stale") => request::Consistency::STALE,
+        Some(other) => return Err(bad_request(&format!("unknown consistency {:?}", other))),
+    };
+    let max_lag = match params.get("max_lag") {
+        Some(max_lag) => max_lag
+            .parse()
+            .map_err(|_| bad_request("max_lag isn't a number"))?,
+        None => 0,
+    };
+    Ok((consistency, max_lag))
+}
+
+fn flag(params: &HashMap<String, String>, name: &str) -> Result<bool, Rejection> {
+    match params.get(name).map(String::as_str) {
+        None | Some("false") => Ok(false),
+        Some("") | Some("true") => Ok(true),
+        Some(_) => Err(bad_request(&format!("{} must be true or false", name))),
+    }
+}
+
+/// Splits a query string into its decoded names and values.
+fn params(query: &str) -> Result<HashMap<String, String>, Rejection> {
+    let mut params = HashMap::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let mut parts = pair.splitn(2, '=');
+        let name = decode(parts.next().unwrap_or(""))?;
+        let value = decode(parts.next().unwrap_or(""))?;
+        params.insert(name, value);
+    }
+    Ok(params)
+}
+
+/// Decodes the `%XX` escapes in part of a URI.
+fn decode(encoded: &str) -> Result<String, Rejection> {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let byte = encoded
+                .get(i + 1..i + 3)
+                .and_then(|hex| {
+                    if hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                        u8::from_str_radix(hex, 16).ok()
+                    } else {
+                        None
+                    }
+                })
+                .ok_or_else(|| bad_request("the URI holds an invalid escape"))?;
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| bad_request("the URI isn't valid UTF-8"))
+}
+
+fn json_body(body: &[u8]) -> Result<Value, Rejection> {
+    serde_json::from_slice(body).map_err(|e| bad_request(&format!("invalid JSON body: {}", e)))
+}
+
+fn required_id(body: &Value, name: &str) -> Result<u64, Rejection> {
+    body.get(name)
+        .and_then(Value::as_u64)
+        .and_then(|id| if id > 0 { Some(id) } else { None })
+        .ok_or_else(|| bad_request(&format!("{} must be a node id", name)))
+}
+
+fn required_str(body: &Value, name: &str) -> Result<String, Rejection> {
+    body.get(name)
+        .and_then(Value::as_str)
+        .and_then(|value| if value.is_empty() { None } else { Some(value) })
+        .map(String::from)
+        .ok_or_else(|| bad_request(&format!("{} is required", name)))
+}
+
+fn optional_str(body: &Value, name: &str) -> String {
+    body.get(name)
+        .and_then(Value::as_str)
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+fn parse_id(id: &str) -> Result<u64, Rejection> {
+    id.parse()
+        .ok()
+        .and_then(|id| if id > 0 { Some(id) } else { None })
+        .ok_or_else(|| bad_request(&format!("{:?} isn't a node id", id)))
+}
+
+/// Turns the database's reply into JSON.
+fn render(response: Response) -> HttpResponse {
+    if response.has_error() {
+        let e = response.get_error();
+        let mut body = json!({
+            "code": format!("{:?}", e.get_code()),
+            "message": e.get_message(),
+        });
+        if e.get_leader_id() > 0 {
+            body["leader_id"] = json!(e.get_leader_id());
+            body["leader_addr"] = json!(e.get_leader_addr());
+        }
+        return json(status(e.get_code()), json!({ "error": body }));
+    }
+
+    if response.has_get() || response.has_delete() {
+        let (found, value) = if response.has_get() {
+            let get = response.get_get();
+            (get.get_is_found(), get.get_value())
+        } else {
+            let delete = response.get_delete();
+            (delete.get_is_found(), delete.get_value())
+        };
+        return if found {
+            json(StatusCode::OK, json!({ "value": value }))
+        } else {
+            error(StatusCode::NOT_FOUND, "the key isn't set")
+        };
+    }
+
+    if response.has_scan() {
+        let scan = response.get_scan();
+        let mut body = json!({ "keys": scan.get_keys() });
+        if !scan.get_values().is_empty() {
+            body["values"] = json!(scan.get_values());
+        }
+        if !scan.get_token().is_empty() {
+            body["token"] = json!(scan.get_token());
+        }
+        return json(StatusCode::OK, body);
+    }
+
+    if response.has_info() {
+        let info = response.get_info();
+        return json(
+            StatusCode::OK,
+            json!({
+                "id": info.get_id(),
+                "leader_id": info.get_leader_id(),
+                "term": info.get_term(),
+                "applied": info.get_applied(),
+                "peers": info.get_peers(),
+                "learners": info.get_learners(),
+            }),
+        );
+    }
+
+    if response.has_success() && response.get_success() {
+        return json(StatusCode::OK, json!({ "success": true }));
+    }
+
+    error(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "the request failed without saying why",
+    )
+}
+
+/// The status an error from the database is served with.
+fn status(code: ErrorCode) -> StatusCode {
+    match code {
+        ErrorCode::INVALID_REQUEST | ErrorCode::INCOMPATIBLE => StatusCode::BAD_REQUEST,
+        ErrorCode::TIMEOUT => StatusCode::GATEWAY_TIMEOUT,
+        // Worth trying again, here or at the leader
+        ErrorCode::NOT_LEADER
+        | ErrorCode::PROPOSAL_DROPPED
+        | ErrorCode::SHUTTING_DOWN
+        | ErrorCode::LAGGING
+        | ErrorCode::LEADER_CHANGED => StatusCode::SERVICE_UNAVAILABLE,
+        ErrorCode::UNKNOWN => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn json(status: StatusCode, body: Value) -> HttpResponse {
+    hyper::Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("A JSON response should build")
+}
+
+fn error(status: StatusCode, message: &str) -> HttpResponse {
+    json(status, json!({ "error": { "message": message } }))
+}
+
+fn bad_request(message: &str) -> Rejection {
+    (StatusCode::BAD_REQUEST, message.to_string())
+}
+
+fn not_allowed() -> Rejection {
+    (
+        StatusCode::METHOD_NOT_ALLOWED,
+        String::from("the method isn't allowed here"),
+    )
+}
+
+fn handle_err(e: impl ::std::fmt::Debug) {
+    println!("error occurred: {:?}", e);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routed(method: Method, uri: &str, body: &str) -> Result<Request, StatusCode> {
+        let mut parts = uri.splitn(2, '?');
+        let path = parts.next().unwrap();
+        let query = parts.next().unwrap_or("");
+        route(&method, path, query, body.as_bytes()).map_err(|(status, _)| status)
+    }
+
+    #[test]
+    fn test_routes_map_onto_requests() {
+        let get = routed(Method::GET, "/v1/kv/tenant%2F1/a?consistency=stale", "").unwrap();
+        assert_eq!(get.get_get().get_key(), "tenant/1/a");
+        assert_eq!(get.get_get().get_consistency(), request::Consistency::STALE);
+
+        let set = routed(Method::PUT, "/v1/kv/a", "one").unwrap();
+        assert_eq!(set.get_set().get_value(), "one");
+
+        let scan = routed(Method::GET, "/v1/kv?prefix=a&limit=5&values", "").unwrap();
+        assert_eq!(scan.get_scan().get_prefix(), "a");
+        assert_eq!(scan.get_scan().get_limit(), 5);
+        assert!(scan.get_scan().get_include_values());
+
+        let add = routed(
+            Method::POST,
+            "/v1/cluster/nodes",
+            r#"{"id": 4, "addr": "db4:9004", "learner": true}"#,
+        ).unwrap();
+        assert_eq!(add.get_add_node().get_id(), 4);
+        assert!(add.get_add_node().get_is_learner());
+
+        let promote = routed(Method::POST, "/v1/cluster/nodes/4/promote", "").unwrap();
+        assert_eq!(promote.get_promote_node().get_id(), 4);
+
+        assert_eq!(
+            routed(Method::POST, "/v1/kv/a", "").unwrap_err(),
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+        assert_eq!(
+            routed(Method::GET, "/v1/nothing", "").unwrap_err(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            routed(Method::POST, "/v1/cluster/nodes", "{}").unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            routed(Method::GET, "/v1/kv/a%zz", "").unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_oversized_bodies_are_refused() {
+        let read = |headers: &HeaderMap, body: Vec<u8>| read_body(headers, Body::from(body)).wait();
+
+        let mut declared = HeaderMap::new();
+        declared.insert(CONTENT_LENGTH, (DEFAULT_MAX_FRAME + 1).into());
+        assert_eq!(read(&declared, vec![]).unwrap(), None);
+
+        let undeclared = HeaderMap::new();
+        assert_eq!(
+            read(&undeclared, vec![0; DEFAULT_MAX_FRAME + 1]).unwrap(),
+            None
+        );
+        assert_eq!(
+            read(&undeclared, b"one".to_vec()).unwrap(),
+            Some(b"one".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_errors_map_onto_statuses() {
+        assert_eq!(render(public::get_response(None)).status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            render(public::get_response(Some("one".to_string()))).status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            render(public::not_leader_response(2, "db2:9000")).status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            render(public::error_response(ErrorCode::TIMEOUT, "")).status(),
+            StatusCode::GATEWAY_TIMEOUT
+        );
+        assert_eq!(
+            render(public::error_response(ErrorCode::INVALID_REQUEST, "")).status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            render(public::failure_response()).status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_a_taken_address_is_an_error() {
+        let taken = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let (db_channel, _) = mpsc::channel(1);
+        assert!(listen(db_channel, &taken.local_addr().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,387 @@
+//! The state machine is what the raft log is replicated into. Raft decides
+//! the order of the writes, the state machine decides what they mean.
+//!
+//! `KeyValueMachine` is the key-value store the server runs by default, but
+//! anything implementing `StateMachine` can be handed to `Server::start`.
+
+use super::engine::Engine;
+use super::storage::Error;
+use public::{self, request, response, Request, Response};
+use std::cmp;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+/// The most keys a single scan response holds.
+const SCAN_PAGE: usize = 1000;
+
+/// Application state that's kept in sync by the raft log.
+///
+/// Writes are applied in log order on every node, so `apply` must be
+/// deterministic: the same requests applied to the same state must always
+/// give the same state. Reads never go through the log and are answered by
+/// `query` on the node that received them.
+///
+/// A machine doesn't need to persist each write. Instead, everything applied
+/// up to an index is made durable by `checkpoint`, and on startup every entry
+/// after `checkpointed` is applied again from the log.
+pub trait StateMachine: Send + 'static {
+    /// Applies a committed write and returns the reply for the client that
+    /// sent it. A write that fails part way must still leave the state as it
+    /// would be on every other node, and report the failure in the reply.
+    fn apply(&mut self, request: &Request) -> Response;
+
+    /// Answers a read from the current state.
+    fn query(&self, request: &Request) -> Response;
+
+    /// The index of the last checkpoint, or zero if there hasn't been one.
+    fn checkpointed(&self) -> u64;
+
+    /// Whether the machine wants to checkpoint before the next snapshot is
+    /// due, for example to bound how much it holds in memory.
+    fn needs_checkpoint(&self) -> bool {
+        false
+    }
+
+    /// Makes everything applied so far durable as of `index`. Once this
+    /// returns the log up to `index` may be discarded.
+    fn checkpoint(&mut self, index: u64) -> io::Result<()>;
+
+    /// Writes the current state to `out`, to be sent to a follower that's
+    /// too far behind to catch up from the log.
+    fn snapshot<W: Write>(&self, out: &mut W) -> io::Result<()>;
+
+    /// Replaces the current state with one written by `snapshot` and makes
+    /// it durable as of `index`.
+    fn restore<R: Read>(&mut self, index: u64, input: &mut R) -> io::Result<()>;
+}
+
+/// The key-value store, kept in an on-disk engine.
+pub struct KeyValueMachine {
+    engine: Engine,
+}
+
+impl KeyValueMachine {
+    pub fn open<P: Into<PathBuf>>(dir: P) -> Result<KeyValueMachine, Error> {
+        Ok(KeyValueMachine {
+            engine: Engine::open(dir)?,
+        })
+    }
+}
+
+impl StateMachine for KeyValueMachine {
+    fn apply(&mut self, request: &Request) -> Response {
+        if request.has_set() {
+            let set = request.get_set();
+            self.engine.set(set.get_key(), set.get_value());
+            public::success_response()
+        } else if request.has_multi_set() {
+            for set in request.get_multi_set().get_sets() {
+                self.engine.set(set.get_key(), set.get_value());
+            }
+            public::success_response()
+        } else if request.has_delete() {
+            match self.engine.delete(request.get_delete().get_key()) {
+                Ok(value) => public::delete_response(value),
+                Err(e) => {
+                    println!("Unable to read the value being deleted: {:?}", e);
+                    public::error_response(
+                        response::ErrorCode::UNKNOWN,
+                        "the key was deleted but its value couldn't be read",
+                    )
+                }
+            }
+        } else {
+            invalid_request()
+        }
+    }
+
+    fn query(&self, request: &Request) -> Response {
+        let response = if request.has_get() {
+            self.engine
+                .get(request.get_get().get_key())
+                .map(public::get_response)
+        } else if request.has_scan() {
+            let scan = request.get_scan();
+            scan_page(&self.engine, scan).map(|(entries, token)| {
+                public::scan_entries_response(entries, scan.get_include_values(), token)
+            })
+        } else {
+            Ok(invalid_request())
+        };
+
+        response.unwrap_or_else(|e| {
+            println!("Unable to read from the engine: {:?}", e);
+            public::failure_response()
+        })
+    }
+
+    fn checkpointed(&self) -> u64 {
+        self.engine.applied()
+    }
+
+    fn needs_checkpoint(&self) -> bool {
+        self.engine.needs_flush()
+    }
+
+    fn checkpoint(&mut self, index: u64) -> io::Result<()> {
+        self.engine.set_applied(index);
+        self.engine.checkpoint().map_err(into_io)
+    }
+
+    fn snapshot<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        self.engine.write_snapshot(out)
+    }
+
+    fn restore<R: Read>(&mut self, index: u64, input: &mut R) -> io::Result<()> {
+        self.engine.restore(index, input).map_err(into_io)
+    }
+}
+
+/// Collects a page of the keys and values within the scan's bounds, in the
+/// order the scan asks for, along with the token for the next page if there
+/// is one. The token is the last key of the page.
+fn scan_page(
+    engine: &Engine,
+    scan: &request::Scan,
+) -> io::Result<(Vec<(String, String)>, Option<String>)> {
+    let prefix = scan.get_prefix();
+    let token = scan.get_token();
+    let start = cmp::max(scan.get_start(), prefix);
+    let end = scan.get_end();
+    let page = match scan.get_limit() as usize {
+        0 => SCAN_PAGE,
+        limit => cmp::min(limit, SCAN_PAGE),
+    };
+
+    let mut entries = Vec::new();
+    let mut more = false;
+    {
+        let mut collect = |key: String, value: String| {
+            let outside = key.as_str() < start
+                || (!end.is_empty() && key.as_str() >= end)
+                || !key.starts_with(prefix);
+            if outside {
+                // Keys are in order, so nothing after this is in range either
+                return Ok(false);
+            }
+            if entries.len() == page {
+                more = true;
+                return Ok(false);
+            }
+            entries.push((key, value));
+            Ok(true)
+        };
+
+        if scan.get_reverse() {
+            // A reverse scan continues before the token, and starts before
+            // the end of the range or the keys with the prefix
+            let prefix_end = prefix_end(prefix);
+            let below = [end, token, prefix_end.as_ref().map_or("", String::as_str)]
+                .iter()
+                .cloned()
+                .filter(|bound| !bound.is_empty())
+                .min();
+            engine.for_each_rev(below, &mut collect)?;
+        } else {
+            // A forward scan continues after the token
+            engine.for_each(Some(cmp::max(start, token)), |key, value| {
+                if !token.is_empty() && key == token {
+                    return Ok(true);
+                }
+                collect(key, value)
+            })?;
+        }
+    }
+
+    let token = if more {
+        entries.last().map(|&(ref key, _)| key.clone())
+    } else {
+        None
+    };
+    Ok((entries, token))
+}
+
+/// The first key past every key with the prefix, or `None` if every key
+/// past the prefix has it.
+fn prefix_end(prefix: &str) -> Option<String> {
+    let mut chars: Vec<char> = prefix.chars().collect();
+    while let Some(last) = chars.pop() {
+        // Skips the surrogates, which aren't chars
+        let next = match last as u32 + 1 {
+            0xD800 => Some('\u{E000}'),
+            next => ::std::char::from_u32(next),
+        };
+        if let Some(next) = next {
+            chars.push(next);
+            return Some(chars.into_iter().collect());
+        }
+    }
+    None
+}
+
+fn invalid_request() -> Response {
+    public::error_response(
+        response::ErrorCode::INVALID_REQUEST,
+        "not a key-value request",
+    )
+}
+
+fn into_io(e: Error) -> io::Error {
+    match e {
+        Error::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_key_value_machine() {
+        let dir = env::temp_dir().join("kv-raft-machine");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut machine = KeyValueMachine::open(&dir).unwrap();
+        assert_eq!(
+            machine.apply(&public::set_request("hello", "world")),
+            public::success_response()
+        );
+        assert_eq!(
+            machine.query(&public::get_request("hello")),
+            public::get_response(Some(String::from("world")))
+        );
+        assert_eq!(
+            machine.apply(&public::delete_request("hello")),
+            public::delete_response(Some(String::from("world")))
+        );
+        assert_eq!(
+            machine.apply(&public::multi_set_request(&[("a", "1"), ("b", "2")])),
+            public::success_response()
+        );
+        assert_eq!(
+            machine.query(&public::get_request("b")),
+            public::get_response(Some(String::from("2")))
+        );
+
+        // Reads aren't writes and writes aren't reads
+        assert_eq!(
+            machine.apply(&public::get_request("hello")),
+            invalid_request()
+        );
+        assert_eq!(
+            machine.query(&public::set_request("hello", "world")),
+            invalid_request()
+        );
+    }
+
+    fn scan(machine: &KeyValueMachine, scan: request::Scan) -> Vec<String> {
+        let response = machine.query(&public::range_scan_request(scan));
+        let scan = response.get_scan();
+        if scan.get_values().is_empty() {
+            return scan.get_keys().to_vec();
+        }
+        scan.get_keys()
+            .iter()
+            .zip(scan.get_values())
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect()
+    }
+
+    #[test]
+    fn test_range_scans() {
+        let dir = env::temp_dir().join("kv-raft-machine-scan");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut machine = KeyValueMachine::open(&dir).unwrap();
+        for key in &[
+            "a",
+            "tenant/1/x",
+            "tenant/1/y",
+            "tenant/2/x",
+            "tenant/3/x",
+            "z",
+        ] {
+            machine.apply(&public::set_request(key, &key.to_uppercase()));
+        }
+
+        let mut range = request::Scan::new();
+        range.set_start(String::from("tenant/1/y"));
+        range.set_end(String::from("tenant/3"));
+        assert_eq!(scan(&machine, range), vec!["tenant/1/y", "tenant/2/x"]);
+
+        let mut prefix = request::Scan::new();
+        prefix.set_prefix(String::from("tenant/"));
+        prefix.set_limit(3);
+        assert_eq!(
+            scan(&machine, prefix.clone()),
+            vec!["tenant/1/x", "tenant/1/y", "tenant/2/x"]
+        );
+
+        prefix.set_reverse(true);
+        prefix.set_include_values(true);
+        assert_eq!(
+            scan(&machine, prefix),
+            vec![
+                "tenant/3/x=TENANT/3/X",
+                "tenant/2/x=TENANT/2/X",
+                "tenant/1/y=TENANT/1/Y"
+            ]
+        );
+
+        assert_eq!(
+            scan(&machine, request::Scan::new()),
+            vec![
+                "a",
+                "tenant/1/x",
+                "tenant/1/y",
+                "tenant/2/x",
+                "tenant/3/x",
+                "z"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prefix_end() {
+        assert_eq!(prefix_end(""), None);
+        assert_eq!(prefix_end("tenant/"), Some(String::from("tenant0")));
+        assert_eq!(prefix_end("a\u{D7FF}"), Some(String::from("a\u{E000}")));
+        assert_eq!(prefix_end("a\u{10FFFF}"), Some(String::from("b")));
+        assert_eq!(prefix_end("\u{10FFFF}"), None);
+    }
+
+    #[test]
+    fn test_scans_are_paged() {
+        let dir = env::temp_dir().join("kv-raft-machine-pages");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut machine = KeyValueMachine::open(&dir).unwrap();
+        for key in &["a", "b", "c", "d", "e"] {
+            machine.apply(&public::set_request(key, "value"));
+        }
+
+        for &(reverse, ref expected) in &[
+            (false, vec![vec!["a", "b"], vec!["c", "d"], vec!["e"]]),
+            (true, vec![vec!["e", "d"], vec!["c", "b"], vec!["a"]]),
+        ] {
+            let mut scan = request::Scan::new();
+            scan.set_limit(2);
+            scan.set_reverse(reverse);
+
+            let mut pages = vec![];
+            loop {
+                let mut response = machine.query(&public::range_scan_request(scan.clone()));
+                let mut page = response.take_scan();
+                pages.push(page.take_keys().into_vec());
+                if page.get_token().is_empty() {
+                    break;
+                }
+                scan.set_token(page.take_token());
+            }
+            assert_eq!(&pages, expected);
+        }
+    }
+}
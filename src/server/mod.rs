@@ -1,53 +1,157 @@
 use raft;
+use std::io;
 use std::net::SocketAddr;
 
+mod crc;
 mod db;
+mod engine;
+mod http;
+mod machine;
 mod network;
 mod peer;
 mod proto;
 mod public;
+mod redis;
+mod resp;
 mod storage;
+mod transport;
+mod wal;
+
+pub use self::machine::{KeyValueMachine, StateMachine};
+pub use self::storage::Error as StorageError;
 
 #[derive(Debug)]
 pub enum Message {
     Timeout,
     Cmd(public::Command),
     Raft(raft::eraftpb::Message),
+    /// Part of the data of a snapshot the leader is sending.
+    Chunk(proto::SnapshotChunk),
+    /// A follower failed to receive the snapshot this node sent it.
+    SnapshotFailed(proto::SnapshotFailed),
+    /// A write a follower forwarded to this node as the leader.
+    Forward(proto::Forward),
+    /// The leader's reply to a write this node forwarded.
+    ForwardReply(proto::ForwardReply),
+    /// The network failed to reach the peer with this id.
+    Unreachable(u64),
+    /// A peer connected and said who it is.
+    Hello(proto::Hello),
     Ping,
     Stop,
 }
 
+/// The members a new cluster starts out with. Every node of the cluster is
+/// started with the same members and token, so they all begin from the same
+/// configuration and elect a leader among themselves.
+#[derive(Debug, Clone)]
+pub struct Bootstrap {
+    /// Names the cluster. A node that has bootstrapped one cluster refuses
+    /// to bootstrap another.
+    pub token: String,
+    /// The id, peer address and public address of each member. Clients
+    /// are pointed at the public address of the leader, so it may only be
+    /// left empty if they're never to be told where it is.
+    pub members: Vec<(u64, String, String)>,
+}
+
 pub struct Server {
     db: db::Handle,
     public: public::Handle,
     peer: peer::Handle,
+    /// Listeners serving the public API over other protocols.
+    gateways: Vec<public::Handle>,
 }
 
 impl Server {
     /// Starts a server and returns a handle to it.
     ///
-    /// A server must know it's peers before it can start. Once started, this
-    /// data is ignored and managed via the network. An improvement would be
-    /// to all peers to be added and pass their context down with the raft
-    /// message.
-    pub fn start(id: u64, file: &str, peer_addr: String) -> Server {
+    /// The server listens for peers on `peer_addr` and tells every peer it
+    /// connects to that it can be reached on `advertise_addr`, so a peer
+    /// can answer it even before it's been told about the server.
+    ///
+    /// Writes are replicated into `machine`, which `KeyValueMachine` does
+    /// for the key-value store.
+    ///
+    /// With `bootstrap`, a node that has never run starts out as a member of
+    /// the given cluster. Without it, a new node is a cluster of its own
+    /// until it's added to another.
+    ///
+    /// Clients and peers must open their connections with a handshake of a
+    /// compatible protocol version, naming the same cluster if they name
+    /// one. A peer that's refused is told why and stops connecting.
+    pub fn start<S: StateMachine>(
+        id: u64,
+        file: &str,
+        peer_addr: String,
+        advertise_addr: String,
+        machine: S,
+        bootstrap: Option<Bootstrap>,
+    ) -> Result<Server, StorageError> {
         let pub_addr = SocketAddr::new("0.0.0.0".parse().unwrap(), 9000);
 
-        let mut network = network::start();
+        // A node knows its cluster once it has bootstrapped or joined one
+        let cluster = match bootstrap {
+            Some(ref bootstrap) => bootstrap.token.clone(),
+            None => storage::cluster_token(file)?.unwrap_or_default(),
+        };
+
+        let network = network::start(id, advertise_addr, cluster.clone());
+        let db = db::Db::new(id, &file, network, machine, bootstrap)?.start();
+        let public = public::listen(db.channel(), &pub_addr, id, cluster.clone());
+        let peer = peer::listen(db.channel(), &peer_addr.parse().unwrap(), cluster);
 
-        // Always add self to the network
-        ::tokio::run(network.add(id, peer_addr.clone()));
+        Ok(Server {
+            db,
+            public,
+            peer,
+            gateways: Vec::new(),
+        })
+    }
 
-        let db = db::Db::new(id, &file, network).start();
-        let public = public::listen(db.channel(), &pub_addr);
-        let peer = peer::listen(db.channel(), &peer_addr.parse().unwrap());
+    /// Also serves the public API as JSON over HTTP on `addr`. Requests go
+    /// to the database the same way a client's do, without a handshake.
+    /// Fails if `addr` can't be bound.
+    pub fn serve_http(&mut self, addr: &SocketAddr) -> io::Result<()> {
+        let gateway = http::listen(self.db.channel(), addr)?;
+        self.gateways.push(gateway);
+        Ok(())
+    }
 
-        Server { db, public, peer }
+    /// Also speaks the Redis protocol on `addr`, so Redis tools can read and
+    /// write the store. Writes are still replicated through raft. Fails if
+    /// `addr` can't be bound.
+    pub fn serve_redis(&mut self, addr: &SocketAddr) -> io::Result<()> {
+        let gateway = redis::listen(self.db.channel(), addr)?;
+        self.gateways.push(gateway);
+        Ok(())
     }
 
     pub fn join(self) {
         self.db.join();
         self.peer.join();
         self.public.join();
+        for gateway in self.gateways {
+            gateway.join();
+        }
+    }
+
+    /// Stops the server gracefully. It stops accepting clients, hands off
+    /// leadership if it leads, fails whatever is still pending and
+    /// checkpoints what it has applied before every thread is joined.
+    /// Clients already connected are sent their replies before they're
+    /// disconnected.
+    pub fn shutdown(mut self) {
+        self.public.stop();
+        for gateway in &mut self.gateways {
+            gateway.stop();
+        }
+        self.db.stop();
+        self.db.join();
+        self.public.join();
+        for gateway in self.gateways {
+            gateway.join();
+        }
+        self.peer.join();
     }
 }
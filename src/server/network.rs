@@ -1,67 +1,130 @@
+use super::peer::MAX_PEER_FRAME;
 use super::proto;
-use codec::Proto;
+use super::transport::{self, SnapshotChunks, Transport};
+use codec::{self, Proto};
 use futures::prelude::*;
+use futures::stream;
 use futures::sync::mpsc;
+use public;
 use raft;
-use raft::raw_node::Peer as RaftPeer;
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io;
 use std::net::ToSocketAddrs;
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio;
-use tokio::net::TcpStream;
+use tokio::io::{ReadHalf, WriteHalf};
+use tokio::net::{ConnectFuture, TcpStream};
 use tokio::prelude::*;
-use tokio_codec::FramedWrite;
+use tokio::timer::Delay;
+use tokio_codec::{FramedRead, FramedWrite};
 
-pub fn start() -> Handle {
-    let (tx, rx) = mpsc::channel(1024);
+/// How many messages may wait to be sent to a peer. Past that the oldest are
+/// dropped, raft sends again whatever a peer doesn't acknowledge. Snapshots
+/// are only dropped when nothing else is left to.
+const PEER_QUEUE: usize = 256;
+/// The first and the longest waits before connecting to a peer again.
+const MIN_BACKOFF: Duration = Duration::from_millis(50);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
 
-    let handle = thread::spawn(move || Network::new().listen(rx));
+/// Starts the network for the node `id` of `cluster`, which tells every peer
+/// it connects to that it listens on `addr`.
+pub fn start(id: u64, addr: String, cluster: String) -> Handle {
+    let (tx, rx) = mpsc::unbounded();
+    let (unreachable_tx, unreachable_rx) = mpsc::unbounded();
+
+    let mut hello = proto::PeerMessage::new();
+    {
+        let hello = hello.mut_hello();
+        hello.set_id(id);
+        hello.set_addr(addr);
+        hello.set_magic(public::MAGIC.to_vec());
+        hello.set_version(public::PROTOCOL_VERSION);
+        hello.set_min_version(public::MIN_PROTOCOL_VERSION);
+        hello.set_cluster(cluster);
+    }
+
+    let handle = thread::spawn(move || Network::new(hello, unreachable_tx).listen(rx));
 
     Handle {
         tx,
-        handle,
-        ids: vec![],
+        handle: Some(handle),
+        unreachable: Some(unreachable_rx),
     }
 }
 
+/// The TCP transport. Messages are handed to the network's thread, which
+/// keeps a connection open to each peer.
 #[derive(Debug)]
 pub struct Handle {
-    tx: mpsc::Sender<Cmd>,
-    handle: JoinHandle<()>,
-    ids: Vec<u64>,
+    tx: mpsc::UnboundedSender<Cmd>,
+    handle: Option<JoinHandle<()>>,
+    unreachable: Option<mpsc::UnboundedReceiver<u64>>,
 }
 
 impl Handle {
-    pub fn send(&self, id: u64, msg: raft::eraftpb::Message) -> impl Future<Item = (), Error = ()> {
-        self.tx
-            .clone()
-            .send(Cmd::raft(id, msg))
-            .map(|_| ())
-            .map_err(|e| println!("Error when queuing message to network: {:?}", e))
+    /// Adds a peer, or moves it to a new address.
+    pub fn add(&mut self, id: u64, addr: String) {
+        assert!(id > 0);
+        self.queue(Cmd::add(id, addr));
     }
 
-    pub fn peers(&self) -> Vec<RaftPeer> {
-        self.ids
-            .iter()
-            .map(|id| RaftPeer {
-                id: *id,
-                context: None,
-            })
-            .collect()
+    /// Stops sending to a peer. What's already queued for it still goes out.
+    pub fn remove(&mut self, id: u64) {
+        self.queue(Cmd::remove(id));
     }
 
-    pub fn add_peer(&mut self, peer: &proto::Peer) -> impl Future<Item = (), Error = ()> {
-        self.add(peer.get_id(), peer.get_addr().to_string())
+    fn queue(&self, cmd: Cmd) {
+        if let Err(e) = self.tx.unbounded_send(cmd) {
+            println!("Error when queuing to network: {:?}", e);
+        }
     }
+}
 
-    pub fn add(&mut self, id: u64, addr: String) -> impl Future<Item = (), Error = ()> {
-        assert!(id > 0);
-        self.ids.push(id);
-        self.tx
-            .clone()
-            .send(Cmd::add(id, addr))
-            .map(|_| ())
-            .map_err(|e| println!("Error when queuing node add: {:?}", e))
+impl Transport for Handle {
+    fn send(&mut self, to: u64, msg: proto::PeerMessage) {
+        self.queue(Cmd::peer(to, msg));
+    }
+
+    fn sink(&self, to: u64) -> Box<Sink<SinkItem = proto::PeerMessage, SinkError = ()> + Send> {
+        Box::new(
+            self.tx
+                .clone()
+                .sink_map_err(|e| println!("Error when queuing message to network: {:?}", e))
+                .with(move |msg| Ok::<_, ()>(Cmd::peer(to, msg))),
+        )
+    }
+
+    fn add_peer(&mut self, peer: &proto::Peer) {
+        self.add(peer.get_id(), peer.get_addr().to_string());
+    }
+
+    fn remove_peer(&mut self, id: u64) {
+        self.remove(id);
+    }
+
+    /// The chunks are read on the network's thread as the connection takes
+    /// them, so the data is never held in memory whole.
+    fn send_snapshot(&mut self, to: u64, msg: &raft::eraftpb::Message, data: File) {
+        let chunks = SnapshotChunks::new(msg, data);
+        self.queue(Cmd::snapshot(to, chunks, transport::raft_message(msg)));
+    }
+
+    fn discovered(&mut self, id: u64, addr: &str) {
+        self.queue(Cmd::discover(id, addr.to_string()));
+    }
+
+    fn unreachable(&mut self) -> Box<Stream<Item = u64, Error = ()> + Send> {
+        match self.unreachable.take() {
+            Some(rx) => Box::new(rx),
+            None => Box::new(stream::empty()),
+        }
+    }
+
+    fn take_thread(&mut self) -> Option<JoinHandle<()>> {
+        self.handle.take()
     }
 }
 
@@ -71,21 +134,35 @@ impl Handle {
 #[derive(Debug)]
 struct Network {
     peers: HashMap<u64, Peer>,
+    /// Sent first on every connection.
+    hello: proto::PeerMessage,
+    unreachable: mpsc::UnboundedSender<u64>,
 }
 
 #[derive(Debug)]
 struct Peer {
-    tx: mpsc::Sender<raft::eraftpb::Message>,
+    tx: mpsc::Sender<Outgoing>,
     id: u64,
+    addr: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 enum Kind {
     Add(String),
-    Raft(raft::eraftpb::Message),
+    Discover(String),
+    Remove,
+    Peer(Outgoing),
+}
+
+/// What's queued for a peer. A snapshot is its chunks followed by the raft
+/// message, which are only read once the connection is ready for them.
+#[derive(Debug)]
+enum Outgoing {
+    Message(proto::PeerMessage),
+    Snapshot(SnapshotChunks, proto::PeerMessage),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 struct Cmd {
     id: u64,
     kind: Kind,
@@ -99,67 +176,661 @@ impl Cmd {
         }
     }
 
-    fn raft(id: u64, msg: raft::eraftpb::Message) -> Self {
+    fn discover(id: u64, addr: String) -> Self {
+        Cmd {
+            id,
+            kind: Kind::Discover(addr),
+        }
+    }
+
+    fn remove(id: u64) -> Self {
+        Cmd {
+            id,
+            kind: Kind::Remove,
+        }
+    }
+
+    fn peer(id: u64, msg: proto::PeerMessage) -> Self {
+        Cmd {
+            id,
+            kind: Kind::Peer(Outgoing::Message(msg)),
+        }
+    }
+
+    fn snapshot(id: u64, chunks: SnapshotChunks, msg: proto::PeerMessage) -> Self {
         Cmd {
             id,
-            kind: Kind::Raft(msg),
+            kind: Kind::Peer(Outgoing::Snapshot(chunks, msg)),
         }
     }
 }
 
 impl Network {
-    fn new() -> Network {
+    fn new(hello: proto::PeerMessage, unreachable: mpsc::UnboundedSender<u64>) -> Network {
         Network {
             peers: HashMap::new(),
+            hello,
+            unreachable,
         }
     }
 
-    fn listen(mut self, rx: mpsc::Receiver<Cmd>) {
+    fn listen(mut self, rx: mpsc::UnboundedReceiver<Cmd>) {
         let network = rx.for_each(move |cmd| {
             match cmd.kind {
                 Kind::Add(addr) => self.add(cmd.id, addr),
-                Kind::Raft(msg) => self.send(cmd.id, msg),
+                Kind::Discover(addr) => self.discover(cmd.id, addr),
+                Kind::Remove => self.remove(cmd.id),
+                Kind::Peer(msg) => self.send(cmd.id, msg),
             }
             Ok(())
         });
         tokio::run(network);
     }
 
+    /// Opens a connection to the peer that lasts until it's replaced or
+    /// removed. Adding a peer again with a new address closes the old
+    /// connection, while the same address keeps it.
     fn add(&mut self, id: u64, addr: String) {
-        let (tx, rx) = mpsc::channel(1024);
-        self.peers.insert(id, Peer { tx, id });
-
-        tokio::spawn({
-            rx.for_each(move |msg| {
-                if let Ok(Some(addr)) = addr.to_socket_addrs().map(|mut i| i.next()) {
-                    tokio::spawn(
-                        TcpStream::connect(&addr)
-                            .map_err(|e| println!("Error connecting to peer: {:?}", e))
-                            .and_then(move |sock| {
-                                let (_, sink) = sock.split();
-
-                                let sink =
-                                    FramedWrite::new(sink, Proto::<raft::eraftpb::Message>::new());
-                                sink.send(msg)
-                                    .map(|_| ())
-                                    .map_err(|e| println!("Error sending message to peer: {:?}", e))
-                            }),
+        if self.peers.get(&id).map_or(false, |peer| peer.addr == addr) {
+            return;
+        }
+
+        println!("Routing peer {} to {}", id, addr);
+        let (tx, rx) = mpsc::channel(PEER_QUEUE);
+        let connection = Connection::new(
+            id,
+            addr.clone(),
+            rx,
+            self.hello.clone(),
+            self.unreachable.clone(),
+        );
+        self.peers.insert(id, Peer { tx, id, addr });
+        tokio::spawn(connection);
+    }
+
+    /// Routes to a peer that connected to this node, unless it's already
+    /// known. The address it gave only fills in for one nobody told us.
+    fn discover(&mut self, id: u64, addr: String) {
+        if id > 0 && !self.peers.contains_key(&id) {
+            self.add(id, addr);
+        }
+    }
+
+    /// Drops the peer, whose connection closes once what's queued for it
+    /// has been sent.
+    fn remove(&mut self, id: u64) {
+        if self.peers.remove(&id).is_some() {
+            println!("Stopped routing peer {}", id);
+        }
+    }
+
+    fn send(&mut self, id: u64, msg: Outgoing) {
+        if let Some(peer) = self.peers.get_mut(&id) {
+            // The connection drains its channel into its own queue, so this
+            // is only full for a moment and the message can be dropped.
+            if let Err(e) = peer.tx.try_send(msg) {
+                if !e.is_full() {
+                    println!("Connection to peer {} has closed", peer.id);
+                    return;
+                }
+                println!("Queue to peer {} is full, dropping a message", peer.id);
+                // Raft waits on a snapshot until it hears it failed
+                if let Outgoing::Snapshot(..) = e.into_inner() {
+                    let _ = self.unreachable.unbounded_send(peer.id);
+                }
+            }
+        }
+    }
+}
+
+type PeerSink = FramedWrite<WriteHalf<TcpStream>, Proto<proto::PeerMessage>>;
+type PeerStream = FramedRead<ReadHalf<TcpStream>, Proto<proto::PeerMessage>>;
+
+enum State {
+    Connecting(ConnectFuture),
+    /// Messages go out without waiting for the answer to the hello, which
+    /// is read until it arrives.
+    Connected(PeerSink, Option<PeerStream>),
+    Waiting(Delay),
+    /// The peer refused the hello, so what's sent to it is dropped.
+    Refused,
+}
+
+/// What to do once the state has been polled.
+enum Step {
+    Connect,
+    Connected(TcpStream),
+    Failed,
+    Refused(String),
+}
+
+/// A long-lived connection to a single peer. Messages are written in the
+/// order they're sent. When the connection fails, the peer is reported as
+/// unreachable and connected to again after a backoff, while messages keep
+/// queueing up to `PEER_QUEUE`. Every connection opens with a hello, and
+/// one the peer refuses isn't tried again.
+struct Connection {
+    id: u64,
+    addr: String,
+    rx: mpsc::Receiver<Outgoing>,
+    hello: proto::PeerMessage,
+    queue: VecDeque<Outgoing>,
+    unreachable: mpsc::UnboundedSender<u64>,
+    state: State,
+    failures: u32,
+}
+
+impl Connection {
+    fn new(
+        id: u64,
+        addr: String,
+        rx: mpsc::Receiver<Outgoing>,
+        hello: proto::PeerMessage,
+        unreachable: mpsc::UnboundedSender<u64>,
+    ) -> Connection {
+        Connection {
+            id,
+            addr,
+            rx,
+            hello,
+            queue: VecDeque::new(),
+            unreachable,
+            // Waiting for no time at all connects on the first poll
+            state: State::Waiting(Delay::new(Instant::now())),
+            failures: 0,
+        }
+    }
+
+    /// Moves everything sent so far into the queue, returning whether the
+    /// peer has been dropped from the network.
+    fn receive(&mut self) -> bool {
+        loop {
+            match self.rx.poll() {
+                Ok(Async::Ready(Some(msg))) => {
+                    if self.queue.len() == PEER_QUEUE {
+                        self.make_room();
+                    }
+                    self.queue.push_back(msg);
+                }
+                Ok(Async::NotReady) => return false,
+                Ok(Async::Ready(None)) | Err(_) => return true,
+            }
+        }
+    }
+
+    /// Drops the oldest message to make room for another. Snapshots and
+    /// their chunks are kept, since raft only sends a snapshot again once
+    /// it's told the last one failed. When nothing else is queued, the
+    /// oldest goes anyway and the peer is reported unreachable to say so.
+    fn make_room(&mut self) {
+        let oldest = self.queue.iter().position(|next| match *next {
+            Outgoing::Message(ref msg) => !msg.has_chunk(),
+            Outgoing::Snapshot(..) => false,
+        });
+        match oldest {
+            Some(i) => {
+                self.queue.remove(i);
+            }
+            None => {
+                self.queue.pop_front();
+                let _ = self.unreachable.unbounded_send(self.id);
+            }
+        }
+    }
+
+    fn connect(&mut self) {
+        match self.addr.to_socket_addrs().map(|mut addrs| addrs.next()) {
+            Ok(Some(addr)) => self.state = State::Connecting(TcpStream::connect(&addr)),
+            _ => {
+                println!("Unable to resolve peer {} at {}", self.id, self.addr);
+                self.fail();
+            }
+        }
+    }
+
+    fn fail(&mut self) {
+        self.failures += 1;
+        let _ = self.unreachable.unbounded_send(self.id);
+        self.state = State::Waiting(Delay::new(Instant::now() + backoff(self.failures)));
+    }
+}
+
+impl Future for Connection {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        let closed = self.receive();
+        loop {
+            // Once dropped, what's queued gets one more try to go out
+            let give_up = closed && (self.queue.is_empty() || self.failures > 0);
+            let step = match self.state {
+                State::Waiting(ref mut delay) => match delay.poll() {
+                    Ok(Async::NotReady) if give_up => return Ok(Async::Ready(())),
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    _ => Step::Connect,
+                },
+                State::Connecting(ref mut connect) => match connect.poll() {
+                    Ok(Async::Ready(sock)) => Step::Connected(sock),
+                    Ok(Async::NotReady) if give_up => return Ok(Async::Ready(())),
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Err(e) => {
+                        println!("Error connecting to peer {}: {:?}", self.id, e);
+                        Step::Failed
+                    }
+                },
+                State::Connected(ref mut sink, ref mut reply) => match read_reply(reply) {
+                    Err(e) => {
+                        println!("Error reading from peer {}: {:?}", self.id, e);
+                        Step::Failed
+                    }
+                    Ok(Some(reason)) => Step::Refused(reason),
+                    Ok(None) => match send_queued(sink, &mut self.queue) {
+                        // Once dropped, the peer is closed after what's queued
+                        Ok(Async::Ready(())) if closed => return Ok(Async::Ready(())),
+                        Ok(_) => return Ok(Async::NotReady),
+                        Err(e) => {
+                            println!("Error sending message to peer {}: {:?}", self.id, e);
+                            Step::Failed
+                        }
+                    },
+                },
+                State::Refused => {
+                    self.queue.clear();
+                    if closed {
+                        return Ok(Async::Ready(()));
+                    }
+                    return Ok(Async::NotReady);
+                }
+            };
+
+            match step {
+                Step::Connect => self.connect(),
+                Step::Connected(sock) => {
+                    self.failures = 0;
+                    self.queue.push_front(Outgoing::Message(self.hello.clone()));
+                    let (stream, sink) = sock.split();
+                    let sink = FramedWrite::new(sink, Proto::with_max_frame(MAX_PEER_FRAME));
+                    let stream = FramedRead::new(stream, Proto::with_max_frame(MAX_PEER_FRAME));
+                    self.state = State::Connected(sink, Some(stream));
+                }
+                Step::Failed => self.fail(),
+                Step::Refused(reason) => {
+                    println!(
+                        "Peer {} at {} refused this node, so it won't be connected to again \
+                         until it's moved or this node restarts: {}",
+                        self.id, self.addr, reason
                     );
+                    let _ = self.unreachable.unbounded_send(self.id);
+                    self.state = State::Refused;
                 }
-                Ok(())
-            })
+            }
+        }
+    }
+}
+
+/// Reads the peer's answer to the hello until it arrives, returning why the
+/// peer refused it if it did. A peer closing the connection before it
+/// answers is an error like any other.
+fn read_reply(reply: &mut Option<PeerStream>) -> Result<Option<String>, codec::Error> {
+    let answer = match *reply {
+        Some(ref mut stream) => match stream.poll()? {
+            Async::Ready(answer) => answer,
+            Async::NotReady => return Ok(None),
+        },
+        None => return Ok(None),
+    };
+    *reply = None;
+    match answer {
+        Some(mut msg) => {
+            let reply = msg.take_hello_reply();
+            if reply.get_accepted() {
+                Ok(None)
+            } else {
+                Ok(Some(reply.get_reason().to_string()))
+            }
+        }
+        None => Err(codec::Error::Io(io::ErrorKind::UnexpectedEof.into())),
+    }
+}
+
+/// Writes out as much of the queue as the connection takes, and is ready
+/// once all of it has been flushed.
+fn send_queued(sink: &mut PeerSink, queue: &mut VecDeque<Outgoing>) -> Poll<(), codec::Error> {
+    loop {
+        while let Some(next) = queue.pop_front() {
+            let msg = match next {
+                Outgoing::Message(msg) => msg,
+                Outgoing::Snapshot(mut chunks, msg) => match chunks.next() {
+                    Some(Ok(chunk)) => {
+                        queue.push_front(Outgoing::Snapshot(chunks, msg));
+                        chunk
+                    }
+                    // The follower refuses a snapshot without all its data,
+                    // which has the leader send it again
+                    Some(Err(e)) => {
+                        println!("Unable to read a snapshot: {}", e);
+                        msg
+                    }
+                    None => msg,
+                },
+            };
+            match sink.start_send(msg) {
+                Ok(AsyncSink::Ready) => (),
+                Ok(AsyncSink::NotReady(msg)) => {
+                    queue.push_front(Outgoing::Message(msg));
+                    break;
+                }
+                // Nothing of it was written, so the connection is still good
+                // and the message is dropped like any other that's lost
+                Err(codec::Error::FrameTooLarge(size, max)) => {
+                    println!(
+                        "Dropping a message of {} bytes, over the {} a peer takes",
+                        size, max
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        if sink.poll_complete()?.is_not_ready() {
+            return Ok(Async::NotReady);
+        }
+        if queue.is_empty() {
+            return Ok(Async::Ready(()));
+        }
+    }
+}
+
+/// How long to wait before connecting again after `failures` attempts in a
+/// row have failed. The wait doubles each time up to `MAX_BACKOFF`, and half
+/// of it is random so that peers which lost a node at the same moment don't
+/// all come back to it together.
+fn backoff(failures: u32) -> Duration {
+    let doublings = cmp::min(failures.saturating_sub(1), 16);
+    let wait = cmp::min(MIN_BACKOFF * 2u32.pow(doublings), MAX_BACKOFF);
+    wait / 2 + jitter(wait / 2)
+}
+
+/// A duration somewhere below `max`. The clock's nanoseconds are random
+/// enough to spread out reconnects.
+fn jitter(max: Duration) -> Duration {
+    let max_nanos = max.as_secs() * 1_000_000_000 + u64::from(max.subsec_nanos());
+    if max_nanos == 0 {
+        return Duration::from_secs(0);
+    }
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| u64::from(now.subsec_nanos()))
+        .unwrap_or(0);
+    // Below a second, as the clock's nanoseconds are
+    Duration::new(0, (nanos % max_nanos) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+    use futures::future;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc as sync_mpsc;
+    use tokio_codec::{Decoder, Encoder};
+
+    #[test]
+    fn test_backoff_grows_to_a_limit() {
+        for failures in 1..40 {
+            let wait = backoff(failures);
+            assert!(wait >= MIN_BACKOFF / 2);
+            assert!(wait <= MAX_BACKOFF);
+        }
+        assert!(backoff(30) >= MAX_BACKOFF / 2);
+    }
+
+    #[test]
+    fn test_messages_arrive_in_order_over_one_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let reader = thread::spawn(move || {
+            let (mut sock, _) = listener.accept().unwrap();
+            let mut buf = vec![];
+            sock.read_to_end(&mut buf).unwrap();
+            buf
         });
+
+        let mut network = start(1, String::from("127.0.0.1:9101"), String::new());
+        let network_thread = network.take_thread().unwrap();
+        network.add(2, addr);
+        for i in 0..100u8 {
+            let mut msg = proto::PeerMessage::new();
+            msg.set_raft(vec![i]);
+            network.send(2, msg);
+        }
+
+        // Dropping the network closes the connection once it's flushed
+        drop(network);
+        network_thread.join().unwrap();
+
+        assert_eq!(
+            decode(reader.join().unwrap()),
+            (0..100).collect::<Vec<u8>>()
+        );
     }
 
-    fn send(&self, id: u64, msg: raft::eraftpb::Message) {
-        if let Some(peer) = self.peers.get(&id) {
-            tokio::spawn({
-                peer.tx
-                    .clone()
-                    .send(msg)
-                    .map(|_| ())
-                    .map_err(|e| println!("Error when sending message over peer channel: {:?}", e))
-            });
+    #[test]
+    fn test_oversized_messages_are_dropped() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let (first, _) = sync_mpsc::channel();
+        let reader = read_one(listener, first);
+
+        let mut network = start(1, String::from("127.0.0.1:9101"), String::new());
+        let network_thread = network.take_thread().unwrap();
+        network.add(2, addr);
+        for data in vec![vec![1], vec![0; MAX_PEER_FRAME + 1], vec![2]] {
+            let mut msg = proto::PeerMessage::new();
+            msg.set_raft(data);
+            network.send(2, msg);
         }
+
+        // The connection outlives the message it couldn't send
+        drop(network);
+        network_thread.join().unwrap();
+        assert_eq!(decode(reader.join().unwrap()), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_a_refused_node_stops_connecting() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let mut network = start(1, String::from("127.0.0.1:9101"), String::new());
+        let network_thread = network.take_thread().unwrap();
+        network.add(2, addr);
+        network.send(2, proto::PeerMessage::new());
+
+        let (mut sock, _) = listener.accept().unwrap();
+        let mut refusal = proto::PeerMessage::new();
+        refusal
+            .mut_hello_reply()
+            .set_reason(String::from("it belongs to another cluster"));
+        let mut buf = BytesMut::new();
+        Proto::new().encode(refusal, &mut buf).unwrap();
+        sock.write_all(&buf).unwrap();
+
+        // The node hangs up, and what's sent later doesn't reconnect
+        sock.read_to_end(&mut vec![]).unwrap();
+        network.send(2, proto::PeerMessage::new());
+        thread::sleep(MIN_BACKOFF * 4);
+        listener.set_nonblocking(true).unwrap();
+        assert!(listener.accept().is_err());
+
+        drop(network);
+        network_thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_a_full_queue_drops_messages_before_snapshots() {
+        let path = ::std::env::temp_dir().join("kv-raft-network-queue");
+        ::std::fs::write(&path, vec![1; 10]).unwrap();
+        let snapshot = || {
+            let msg = raft::eraftpb::Message::new();
+            let chunks = SnapshotChunks::new(&msg, File::open(&path).unwrap());
+            Outgoing::Snapshot(chunks, transport::raft_message(&msg))
+        };
+        let message = |n: u8| {
+            let mut msg = proto::PeerMessage::new();
+            msg.set_raft(vec![n]);
+            Outgoing::Message(msg)
+        };
+
+        let (mut tx, rx) = mpsc::channel(PEER_QUEUE * 2);
+        let (unreachable_tx, mut unreachable) = mpsc::unbounded();
+        let mut connection = Connection::new(
+            2,
+            String::from("127.0.0.1:9102"),
+            rx,
+            proto::PeerMessage::new(),
+            unreachable_tx,
+        );
+        let mut receive = |connection: &mut Connection| {
+            future::lazy(|| {
+                connection.receive();
+                let unreachable = match unreachable.poll() {
+                    Ok(Async::Ready(Some(id))) => Some(id),
+                    _ => None,
+                };
+                Ok::<_, ()>(unreachable)
+            }).wait()
+                .unwrap()
+        };
+
+        // Overflowing the queue during a snapshot drops messages behind it
+        tx.try_send(snapshot()).unwrap();
+        for n in 0..PEER_QUEUE {
+            tx.try_send(message(n as u8)).unwrap();
+        }
+        assert_eq!(receive(&mut connection), None);
+        assert_eq!(connection.queue.len(), PEER_QUEUE);
+        match connection.queue[0] {
+            Outgoing::Snapshot(..) => (),
+            _ => panic!("the snapshot should still be queued"),
+        }
+        match connection.queue[1] {
+            Outgoing::Message(ref msg) => assert_eq!(msg.get_raft(), &[1]),
+            _ => panic!("the oldest message should have been dropped"),
+        }
+
+        // With only snapshots left to drop, raft is told one failed
+        connection.queue.clear();
+        for _ in 0..PEER_QUEUE + 1 {
+            tx.try_send(snapshot()).unwrap();
+        }
+        assert_eq!(receive(&mut connection), Some(2));
+        assert_eq!(connection.queue.len(), PEER_QUEUE);
+    }
+
+    /// Accepts a single connection and reads it until it's closed, telling
+    /// `first` once something has arrived.
+    fn read_one(listener: TcpListener, first: sync_mpsc::Sender<()>) -> JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let (mut sock, _) = listener.accept().unwrap();
+            let mut buf = vec![];
+            let mut chunk = [0; 1024];
+            loop {
+                match sock.read(&mut chunk).unwrap() {
+                    0 => return buf,
+                    n => buf.extend_from_slice(&chunk[..n]),
+                }
+                let _ = first.send(());
+            }
+        })
+    }
+
+    /// Decodes what a connection received, checking it opened with a hello
+    /// from node 1.
+    fn decode(buf: Vec<u8>) -> Vec<u8> {
+        let mut buf = BytesMut::from(buf);
+        let mut decoder = Proto::<proto::PeerMessage>::new();
+        let hello = decoder.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(hello.get_hello().get_id(), 1);
+        assert_eq!(hello.get_hello().get_addr(), "127.0.0.1:9101");
+
+        let mut received = vec![];
+        while let Some(msg) = decoder.decode(&mut buf).unwrap() {
+            received.push(msg.get_raft()[0]);
+        }
+        received
+    }
+
+    #[test]
+    fn test_peers_are_moved_and_removed() {
+        let old = TcpListener::bind("127.0.0.1:0").unwrap();
+        let new = TcpListener::bind("127.0.0.1:0").unwrap();
+        let old_addr = old.local_addr().unwrap().to_string();
+        let new_addr = new.local_addr().unwrap().to_string();
+        let (first, old_arrived) = sync_mpsc::channel();
+        let old = read_one(old, first);
+        let (first, new_arrived) = sync_mpsc::channel();
+        let new = read_one(new, first);
+
+        let message = |n: u8| {
+            let mut msg = proto::PeerMessage::new();
+            msg.set_raft(vec![n]);
+            msg
+        };
+
+        let mut network = start(1, String::from("127.0.0.1:9101"), String::new());
+        let network_thread = network.take_thread().unwrap();
+        network.add(2, old_addr.clone());
+        network.send(2, message(1));
+        old_arrived.recv().unwrap();
+
+        // Adding the same address again keeps the connection
+        network.add(2, old_addr);
+        network.send(2, message(2));
+
+        // Moving the peer closes the old connection
+        network.add(2, new_addr);
+        assert_eq!(decode(old.join().unwrap()), vec![1, 2]);
+        network.send(2, message(3));
+        new_arrived.recv().unwrap();
+
+        // Removing it closes the new one, and nothing more is sent
+        network.remove(2);
+        assert_eq!(decode(new.join().unwrap()), vec![3]);
+        network.send(2, message(4));
+
+        drop(network);
+        network_thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_discovered_peers_are_routed_unless_known() {
+        let known = TcpListener::bind("127.0.0.1:0").unwrap();
+        let found = TcpListener::bind("127.0.0.1:0").unwrap();
+        let known_addr = known.local_addr().unwrap().to_string();
+        let found_addr = found.local_addr().unwrap().to_string();
+        let (first, _) = sync_mpsc::channel();
+        let known = read_one(known, first.clone());
+        let found = read_one(found, first);
+
+        let message = |n: u8| {
+            let mut msg = proto::PeerMessage::new();
+            msg.set_raft(vec![n]);
+            msg
+        };
+
+        let mut network = start(1, String::from("127.0.0.1:9101"), String::new());
+        let network_thread = network.take_thread().unwrap();
+        network.add(2, known_addr);
+        network.discovered(2, &found_addr);
+        network.discovered(3, &found_addr);
+        network.send(2, message(2));
+        network.send(3, message(3));
+
+        drop(network);
+        network_thread.join().unwrap();
+        assert_eq!(decode(known.join().unwrap()), vec![2]);
+        assert_eq!(decode(found.join().unwrap()), vec![3]);
     }
 }
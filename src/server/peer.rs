@@ -1,49 +1,177 @@
-//! The peer module starts a listener for raft messages only.
-//! These message are wrapped into a server message and forwarded
-//! to the db channel.
+//! The peer module starts a listener for messages from other peers. These
+//! are mostly raft messages, along with the data of snapshots, writes
+//! forwarded to the leader and their replies. They are wrapped into a server
+//! message and forwarded to the db channel.
 
-use super::Message;
-use codec::Proto;
+use super::{proto, Message};
+use codec::{Proto, DEFAULT_MAX_FRAME};
+use futures::future::{self, Either};
 use futures::prelude::*;
-use futures::sync::mpsc;
+use futures::sync::{mpsc, oneshot};
+use protobuf::parse_from_bytes;
+use public;
 use raft;
 use std::net::SocketAddr;
 use std::thread::{self, JoinHandle};
 use tokio;
 use tokio::net::TcpListener;
 use tokio::prelude::*;
-use tokio_codec::FramedRead;
+use tokio::runtime::Runtime;
+use tokio_codec::{FramedRead, FramedWrite};
 
-pub fn listen(db_channel: mpsc::Sender<Message>, addr: &SocketAddr) -> Handle {
+/// The largest frame peers send each other. It's twice a client's, which
+/// leaves room for the largest request a client can send once it's wrapped
+/// into a raft entry or forwarded to the leader. Snapshots go in chunks far
+/// smaller than this.
+pub const MAX_PEER_FRAME: usize = 2 * DEFAULT_MAX_FRAME;
+
+/// Listens for peers of the cluster named by `cluster`, empty if it isn't
+/// known. A peer must open with a hello this node is compatible with.
+pub fn listen(db_channel: mpsc::Sender<Message>, addr: &SocketAddr, cluster: String) -> Handle {
     let addr = (*addr).clone();
+    let (close, closed) = oneshot::channel::<()>();
     let handle = thread::spawn(move || {
         let listener = TcpListener::bind(&addr).unwrap();
         let db_channel = db_channel.clone();
+        let closed = closed.shared();
+        let connections = closed.clone();
 
         let server = listener
             .incoming()
             .map_err(handle_err)
             .for_each(move |sock| {
-                println!("New peer connected!");
-
-                let (stream, _) = sock.split();
+                let (stream, sink) = sock.split();
 
-                let stream = FramedRead::new(stream, Proto::<raft::eraftpb::Message>::new());
+                let stream = FramedRead::new(
+                    stream,
+                    Proto::<proto::PeerMessage>::with_max_frame(MAX_PEER_FRAME),
+                );
+                let sink = FramedWrite::new(
+                    sink,
+                    Proto::<proto::PeerMessage>::with_max_frame(MAX_PEER_FRAME),
+                );
+                let db_channel = db_channel.clone();
+                let cluster = cluster.clone();
                 tokio::spawn(
                     stream
-                        .map_err(handle_err)
-                        .map(move |msg| Message::Raft(msg))
-                        .forward(db_channel.clone().sink_map_err(handle_err))
+                        .into_future()
+                        .map_err(|(e, _)| handle_err(e))
+                        .and_then(move |(first, stream)| match first {
+                            Some(first) => Ok((greet(first, &cluster), stream)),
+                            None => Err(()),
+                        })
+                        .and_then(move |(greeted, stream)| {
+                            sink.send(hello_reply(&greeted)).then(move |sent| {
+                                match (greeted, sent) {
+                                    (Ok(hello), Ok(_)) => Either::A(future::ok((hello, stream))),
+                                    (Ok(_), Err(e)) => {
+                                        handle_err(e);
+                                        Either::A(future::err(()))
+                                    }
+                                    // Closing now could lose the reply when
+                                    // the peer has sent more after its hello,
+                                    // so the peer is left to hang up.
+                                    (Err(e), _) => {
+                                        println!("Refusing a peer connection: {}", e);
+                                        Either::B(stream.for_each(|_| Ok(())).then(|_| Err(())))
+                                    }
+                                }
+                            })
+                        })
+                        .and_then(|(hello, stream)| {
+                            db_channel
+                                .send(hello)
+                                .map_err(handle_err)
+                                .and_then(|db_channel| {
+                                    stream
+                                        .map_err(handle_err)
+                                        .filter_map(into_message)
+                                        .forward(db_channel.sink_map_err(handle_err))
+                                })
+                        })
+                        .select2(connections.clone())
                         .then(|_| Ok(())),
                 );
 
                 Ok(())
             });
 
-        tokio::run(server);
+        // Peers are listened to until the very end, as handing off
+        // leadership needs to hear from them.
+        let mut runtime = Runtime::new().expect("Unable to start the peer listener");
+        let _ = runtime.block_on(server.select2(closed));
+        runtime
+            .shutdown_now()
+            .wait()
+            .expect("Peer listener failed to shut down");
     });
 
-    Handle { handle }
+    Handle { handle, close }
+}
+
+/// Checks the hello a peer opens with, which becomes a message for the db
+/// so it can reach the peer in turn.
+fn greet(mut msg: proto::PeerMessage, cluster: &str) -> Result<Message, String> {
+    if !msg.has_hello() {
+        return Err(String::from(
+            "it didn't open with a hello, it may be an older build",
+        ));
+    }
+
+    let hello = msg.take_hello();
+    public::check_handshake(
+        hello.get_magic(),
+        hello.get_version(),
+        hello.get_min_version(),
+        hello.get_cluster(),
+        cluster,
+    ).map_err(|e| format!("node {} at {}: {}", hello.get_id(), hello.get_addr(), e))?;
+    if hello.get_id() == 0 {
+        return Err(String::from("it didn't give a node id"));
+    }
+
+    println!(
+        "Peer {} connected, it listens on {}",
+        hello.get_id(),
+        hello.get_addr()
+    );
+    Ok(Message::Hello(hello))
+}
+
+/// The answer to a hello, which tells a refused peer why.
+fn hello_reply(greeted: &Result<Message, String>) -> proto::PeerMessage {
+    let mut msg = proto::PeerMessage::new();
+    {
+        let reply = msg.mut_hello_reply();
+        reply.set_accepted(greeted.is_ok());
+        if let Err(ref reason) = *greeted {
+            reply.set_reason(reason.clone());
+        }
+    }
+    msg
+}
+
+/// Unwraps what a peer sent after its hello.
+pub fn into_message(mut msg: proto::PeerMessage) -> Option<Message> {
+    if msg.has_raft() {
+        match parse_from_bytes::<raft::eraftpb::Message>(msg.get_raft()) {
+            Ok(raft) => Some(Message::Raft(raft)),
+            Err(e) => {
+                handle_err(e);
+                None
+            }
+        }
+    } else if msg.has_chunk() {
+        Some(Message::Chunk(msg.take_chunk()))
+    } else if msg.has_snapshot_failed() {
+        Some(Message::SnapshotFailed(msg.take_snapshot_failed()))
+    } else if msg.has_forward() {
+        Some(Message::Forward(msg.take_forward()))
+    } else if msg.has_reply() {
+        Some(Message::ForwardReply(msg.take_reply()))
+    } else {
+        None
+    }
 }
 
 fn handle_err(e: impl ::std::fmt::Debug) {
@@ -52,10 +180,14 @@ fn handle_err(e: impl ::std::fmt::Debug) {
 
 pub struct Handle {
     handle: JoinHandle<()>,
+    close: oneshot::Sender<()>,
 }
 
 impl Handle {
+    /// Closes the listener along with every peer connection and waits for
+    /// it to finish.
     pub fn join(self) {
-        self.handle.join().expect("Client listener panicked");
+        let _ = self.close.send(());
+        self.handle.join().expect("Peer listener panicked");
     }
 }
@@ -28,6 +28,8 @@ pub struct Entry {
     pub key: ::std::string::String,
     pub value: ::std::string::String,
     pub kind: EntryKind,
+    pub request: ::std::vec::Vec<u8>,
+    pub node: u64,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::protobuf::CachedSize,
@@ -119,6 +121,47 @@ impl Entry {
     pub fn get_kind(&self) -> EntryKind {
         self.kind
     }
+
+    // bytes request = 5;
+
+    pub fn clear_request(&mut self) {
+        self.request.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_request(&mut self, v: ::std::vec::Vec<u8>) {
+        self.request = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_request(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.request
+    }
+
+    // Take field
+    pub fn take_request(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.request, ::std::vec::Vec::new())
+    }
+
+    pub fn get_request(&self) -> &[u8] {
+        &self.request
+    }
+
+    // uint64 node = 6;
+
+    pub fn clear_node(&mut self) {
+        self.node = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_node(&mut self, v: u64) {
+        self.node = v;
+    }
+
+    pub fn get_node(&self) -> u64 {
+        self.node
+    }
 }
 
 impl ::protobuf::Message for Entry {
@@ -146,6 +189,16 @@ impl ::protobuf::Message for Entry {
                 4 => {
                     ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.kind, 4, &mut self.unknown_fields)?
                 },
+                5 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.request)?;
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.node = tmp;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -170,6 +223,12 @@ impl ::protobuf::Message for Entry {
         if self.kind != EntryKind::SET {
             my_size += ::protobuf::rt::enum_size(4, self.kind);
         }
+        if !self.request.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(5, &self.request);
+        }
+        if self.node != 0 {
+            my_size += ::protobuf::rt::value_size(6, self.node, ::protobuf::wire_format::WireTypeVarint);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -188,6 +247,12 @@ impl ::protobuf::Message for Entry {
         if self.kind != EntryKind::SET {
             os.write_enum(4, self.kind.value())?;
         }
+        if !self.request.is_empty() {
+            os.write_bytes(5, &self.request)?;
+        }
+        if self.node != 0 {
+            os.write_uint64(6, self.node)?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -250,6 +315,16 @@ impl ::protobuf::Message for Entry {
                     |m: &Entry| { &m.kind },
                     |m: &mut Entry| { &mut m.kind },
                 ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                    "request",
+                    |m: &Entry| { &m.request },
+                    |m: &mut Entry| { &mut m.request },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "node",
+                    |m: &Entry| { &m.node },
+                    |m: &mut Entry| { &mut m.node },
+                ));
                 ::protobuf::reflect::MessageDescriptor::new::<Entry>(
                     "Entry",
                     fields,
@@ -276,6 +351,8 @@ impl ::protobuf::Clear for Entry {
         self.clear_key();
         self.clear_value();
         self.clear_kind();
+        self.clear_request();
+        self.clear_node();
         self.unknown_fields.clear();
     }
 }
@@ -516,6 +593,8 @@ pub struct Peer {
     // message fields
     pub id: u64,
     pub addr: ::std::string::String,
+    pub public_addr: ::std::string::String,
+    pub is_learner: bool,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::protobuf::CachedSize,
@@ -566,6 +645,47 @@ impl Peer {
     pub fn get_addr(&self) -> &str {
         &self.addr
     }
+
+    // string public_addr = 3;
+
+    pub fn clear_public_addr(&mut self) {
+        self.public_addr.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_public_addr(&mut self, v: ::std::string::String) {
+        self.public_addr = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_public_addr(&mut self) -> &mut ::std::string::String {
+        &mut self.public_addr
+    }
+
+    // Take field
+    pub fn take_public_addr(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.public_addr, ::std::string::String::new())
+    }
+
+    pub fn get_public_addr(&self) -> &str {
+        &self.public_addr
+    }
+
+    // bool is_learner = 4;
+
+    pub fn clear_is_learner(&mut self) {
+        self.is_learner = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_is_learner(&mut self, v: bool) {
+        self.is_learner = v;
+    }
+
+    pub fn get_is_learner(&self) -> bool {
+        self.is_learner
+    }
 }
 
 impl ::protobuf::Message for Peer {
@@ -587,6 +707,16 @@ impl ::protobuf::Message for Peer {
                 2 => {
                     ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.addr)?;
                 },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.public_addr)?;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.is_learner = tmp;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -605,6 +735,12 @@ impl ::protobuf::Message for Peer {
         if !self.addr.is_empty() {
             my_size += ::protobuf::rt::string_size(2, &self.addr);
         }
+        if !self.public_addr.is_empty() {
+            my_size += ::protobuf::rt::string_size(3, &self.public_addr);
+        }
+        if self.is_learner != false {
+            my_size += 2;
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -617,6 +753,12 @@ impl ::protobuf::Message for Peer {
         if !self.addr.is_empty() {
             os.write_string(2, &self.addr)?;
         }
+        if !self.public_addr.is_empty() {
+            os.write_string(3, &self.public_addr)?;
+        }
+        if self.is_learner != false {
+            os.write_bool(4, self.is_learner)?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -669,6 +811,16 @@ impl ::protobuf::Message for Peer {
                     |m: &Peer| { &m.addr },
                     |m: &mut Peer| { &mut m.addr },
                 ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "public_addr",
+                    |m: &Peer| { &m.public_addr },
+                    |m: &mut Peer| { &mut m.public_addr },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                    "is_learner",
+                    |m: &Peer| { &m.is_learner },
+                    |m: &mut Peer| { &mut m.is_learner },
+                ));
                 ::protobuf::reflect::MessageDescriptor::new::<Peer>(
                     "Peer",
                     fields,
@@ -693,6 +845,8 @@ impl ::protobuf::Clear for Peer {
     fn clear(&mut self) {
         self.clear_id();
         self.clear_addr();
+        self.clear_public_addr();
+        self.clear_is_learner();
         self.unknown_fields.clear();
     }
 }
@@ -914,11 +1068,2377 @@ impl ::protobuf::reflect::ProtobufValue for Datum {
     }
 }
 
+#[derive(PartialEq,Clone,Default)]
+pub struct Manifest {
+    // message fields
+    pub applied: u64,
+    pub tables: ::std::vec::Vec<u64>,
+    pub next_table: u64,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl Manifest {
+    pub fn new() -> Manifest {
+        ::std::default::Default::default()
+    }
+
+    // uint64 applied = 1;
+
+    pub fn clear_applied(&mut self) {
+        self.applied = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_applied(&mut self, v: u64) {
+        self.applied = v;
+    }
+
+    pub fn get_applied(&self) -> u64 {
+        self.applied
+    }
+
+    // repeated uint64 tables = 2;
+
+    pub fn clear_tables(&mut self) {
+        self.tables.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tables(&mut self, v: ::std::vec::Vec<u64>) {
+        self.tables = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_tables(&mut self) -> &mut ::std::vec::Vec<u64> {
+        &mut self.tables
+    }
+
+    // Take field
+    pub fn take_tables(&mut self) -> ::std::vec::Vec<u64> {
+        ::std::mem::replace(&mut self.tables, ::std::vec::Vec::new())
+    }
+
+    pub fn get_tables(&self) -> &[u64] {
+        &self.tables
+    }
+
+    // uint64 next_table = 3;
+
+    pub fn clear_next_table(&mut self) {
+        self.next_table = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_next_table(&mut self, v: u64) {
+        self.next_table = v;
+    }
+
+    pub fn get_next_table(&self) -> u64 {
+        self.next_table
+    }
+}
+
+impl ::protobuf::Message for Manifest {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.applied = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_repeated_uint64_into(wire_type, is, &mut self.tables)?;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.next_table = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.applied != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.applied, ::protobuf::wire_format::WireTypeVarint);
+        }
+        for value in &self.tables {
+            my_size += ::protobuf::rt::value_size(2, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        if self.next_table != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.next_table, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.applied != 0 {
+            os.write_uint64(1, self.applied)?;
+        }
+        for v in &self.tables {
+            os.write_uint64(2, *v)?;
+        };
+        if self.next_table != 0 {
+            os.write_uint64(3, self.next_table)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Manifest {
+        Manifest::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "applied",
+                    |m: &Manifest| { &m.applied },
+                    |m: &mut Manifest| { &mut m.applied },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "tables",
+                    |m: &Manifest| { &m.tables },
+                    |m: &mut Manifest| { &mut m.tables },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "next_table",
+                    |m: &Manifest| { &m.next_table },
+                    |m: &mut Manifest| { &mut m.next_table },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<Manifest>(
+                    "Manifest",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static Manifest {
+        static mut instance: ::protobuf::lazy::Lazy<Manifest> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const Manifest,
+        };
+        unsafe {
+            instance.get(Manifest::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for Manifest {
+    fn clear(&mut self) {
+        self.clear_applied();
+        self.clear_tables();
+        self.clear_next_table();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Manifest {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Manifest {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct PeerMessage {
+    // message oneof groups
+    pub kind: ::std::option::Option<PeerMessage_oneof_kind>,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+#[derive(Clone,PartialEq)]
+pub enum PeerMessage_oneof_kind {
+    raft(::std::vec::Vec<u8>),
+    chunk(SnapshotChunk),
+    snapshot_failed(SnapshotFailed),
+    forward(Forward),
+    reply(ForwardReply),
+    hello(Hello),
+    hello_reply(HelloReply),
+}
+
+impl PeerMessage {
+    pub fn new() -> PeerMessage {
+        ::std::default::Default::default()
+    }
+
+    // bytes raft = 1;
+
+    pub fn clear_raft(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_raft(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::raft(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_raft(&mut self, v: ::std::vec::Vec<u8>) {
+        self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::raft(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_raft(&mut self) -> &mut ::std::vec::Vec<u8> {
+        if let ::std::option::Option::Some(PeerMessage_oneof_kind::raft(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::raft(::std::vec::Vec::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::raft(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_raft(&mut self) -> ::std::vec::Vec<u8> {
+        if self.has_raft() {
+            match self.kind.take() {
+                ::std::option::Option::Some(PeerMessage_oneof_kind::raft(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            ::std::vec::Vec::new()
+        }
+    }
+
+    pub fn get_raft(&self) -> &[u8] {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::raft(ref v)) => v,
+            _ => &[],
+        }
+    }
+
+    // .db.SnapshotChunk chunk = 2;
+
+    pub fn clear_chunk(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_chunk(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::chunk(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_chunk(&mut self, v: SnapshotChunk) {
+        self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::chunk(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_chunk(&mut self) -> &mut SnapshotChunk {
+        if let ::std::option::Option::Some(PeerMessage_oneof_kind::chunk(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::chunk(SnapshotChunk::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::chunk(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_chunk(&mut self) -> SnapshotChunk {
+        if self.has_chunk() {
+            match self.kind.take() {
+                ::std::option::Option::Some(PeerMessage_oneof_kind::chunk(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            SnapshotChunk::new()
+        }
+    }
+
+    pub fn get_chunk(&self) -> &SnapshotChunk {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::chunk(ref v)) => v,
+            _ => SnapshotChunk::default_instance(),
+        }
+    }
+
+    // .db.SnapshotFailed snapshot_failed = 3;
+
+    pub fn clear_snapshot_failed(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_snapshot_failed(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::snapshot_failed(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_snapshot_failed(&mut self, v: SnapshotFailed) {
+        self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::snapshot_failed(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_snapshot_failed(&mut self) -> &mut SnapshotFailed {
+        if let ::std::option::Option::Some(PeerMessage_oneof_kind::snapshot_failed(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::snapshot_failed(SnapshotFailed::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::snapshot_failed(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_snapshot_failed(&mut self) -> SnapshotFailed {
+        if self.has_snapshot_failed() {
+            match self.kind.take() {
+                ::std::option::Option::Some(PeerMessage_oneof_kind::snapshot_failed(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            SnapshotFailed::new()
+        }
+    }
+
+    pub fn get_snapshot_failed(&self) -> &SnapshotFailed {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::snapshot_failed(ref v)) => v,
+            _ => SnapshotFailed::default_instance(),
+        }
+    }
+
+    // .db.Forward forward = 4;
+
+    pub fn clear_forward(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_forward(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::forward(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_forward(&mut self, v: Forward) {
+        self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::forward(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_forward(&mut self) -> &mut Forward {
+        if let ::std::option::Option::Some(PeerMessage_oneof_kind::forward(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::forward(Forward::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::forward(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_forward(&mut self) -> Forward {
+        if self.has_forward() {
+            match self.kind.take() {
+                ::std::option::Option::Some(PeerMessage_oneof_kind::forward(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Forward::new()
+        }
+    }
+
+    pub fn get_forward(&self) -> &Forward {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::forward(ref v)) => v,
+            _ => Forward::default_instance(),
+        }
+    }
+
+    // .db.ForwardReply reply = 5;
+
+    pub fn clear_reply(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_reply(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::reply(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_reply(&mut self, v: ForwardReply) {
+        self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::reply(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_reply(&mut self) -> &mut ForwardReply {
+        if let ::std::option::Option::Some(PeerMessage_oneof_kind::reply(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::reply(ForwardReply::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::reply(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_reply(&mut self) -> ForwardReply {
+        if self.has_reply() {
+            match self.kind.take() {
+                ::std::option::Option::Some(PeerMessage_oneof_kind::reply(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            ForwardReply::new()
+        }
+    }
+
+    pub fn get_reply(&self) -> &ForwardReply {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::reply(ref v)) => v,
+            _ => ForwardReply::default_instance(),
+        }
+    }
+
+    // .db.Hello hello = 6;
+
+    pub fn clear_hello(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_hello(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::hello(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_hello(&mut self, v: Hello) {
+        self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::hello(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_hello(&mut self) -> &mut Hello {
+        if let ::std::option::Option::Some(PeerMessage_oneof_kind::hello(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::hello(Hello::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::hello(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_hello(&mut self) -> Hello {
+        if self.has_hello() {
+            match self.kind.take() {
+                ::std::option::Option::Some(PeerMessage_oneof_kind::hello(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Hello::new()
+        }
+    }
+
+    pub fn get_hello(&self) -> &Hello {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::hello(ref v)) => v,
+            _ => Hello::default_instance(),
+        }
+    }
+
+    // .db.HelloReply hello_reply = 7;
+
+    pub fn clear_hello_reply(&mut self) {
+        self.kind = ::std::option::Option::None;
+    }
+
+    pub fn has_hello_reply(&self) -> bool {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::hello_reply(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_hello_reply(&mut self, v: HelloReply) {
+        self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::hello_reply(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_hello_reply(&mut self) -> &mut HelloReply {
+        if let ::std::option::Option::Some(PeerMessage_oneof_kind::hello_reply(_)) = self.kind {
+        } else {
+            self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::hello_reply(HelloReply::new()));
+        }
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::hello_reply(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_hello_reply(&mut self) -> HelloReply {
+        if self.has_hello_reply() {
+            match self.kind.take() {
+                ::std::option::Option::Some(PeerMessage_oneof_kind::hello_reply(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            HelloReply::new()
+        }
+    }
+
+    pub fn get_hello_reply(&self) -> &HelloReply {
+        match self.kind {
+            ::std::option::Option::Some(PeerMessage_oneof_kind::hello_reply(ref v)) => v,
+            _ => HelloReply::default_instance(),
+        }
+    }
+}
+
+impl ::protobuf::Message for PeerMessage {
+    fn is_initialized(&self) -> bool {
+        if let Some(PeerMessage_oneof_kind::chunk(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(PeerMessage_oneof_kind::snapshot_failed(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(PeerMessage_oneof_kind::forward(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(PeerMessage_oneof_kind::reply(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(PeerMessage_oneof_kind::hello(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(PeerMessage_oneof_kind::hello_reply(ref v)) = self.kind {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::raft(is.read_bytes()?));
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::chunk(is.read_message()?));
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::snapshot_failed(is.read_message()?));
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::forward(is.read_message()?));
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::reply(is.read_message()?));
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::hello(is.read_message()?));
+                },
+                7 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.kind = ::std::option::Option::Some(PeerMessage_oneof_kind::hello_reply(is.read_message()?));
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let ::std::option::Option::Some(ref v) = self.kind {
+            match v {
+                &PeerMessage_oneof_kind::raft(ref v) => {
+                    my_size += ::protobuf::rt::bytes_size(1, &v);
+                },
+                &PeerMessage_oneof_kind::chunk(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &PeerMessage_oneof_kind::snapshot_failed(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &PeerMessage_oneof_kind::forward(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &PeerMessage_oneof_kind::reply(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &PeerMessage_oneof_kind::hello(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &PeerMessage_oneof_kind::hello_reply(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+            };
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if let ::std::option::Option::Some(ref v) = self.kind {
+            match v {
+                &PeerMessage_oneof_kind::raft(ref v) => {
+                    os.write_bytes(1, v)?;
+                },
+                &PeerMessage_oneof_kind::chunk(ref v) => {
+                    os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &PeerMessage_oneof_kind::snapshot_failed(ref v) => {
+                    os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &PeerMessage_oneof_kind::forward(ref v) => {
+                    os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &PeerMessage_oneof_kind::reply(ref v) => {
+                    os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &PeerMessage_oneof_kind::hello(ref v) => {
+                    os.write_tag(6, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &PeerMessage_oneof_kind::hello_reply(ref v) => {
+                    os.write_tag(7, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+            };
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> PeerMessage {
+        PeerMessage::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_singular_bytes_accessor::<_>(
+                    "raft",
+                    PeerMessage::has_raft,
+                    PeerMessage::get_raft,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, SnapshotChunk>(
+                    "chunk",
+                    PeerMessage::has_chunk,
+                    PeerMessage::get_chunk,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, SnapshotFailed>(
+                    "snapshot_failed",
+                    PeerMessage::has_snapshot_failed,
+                    PeerMessage::get_snapshot_failed,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Forward>(
+                    "forward",
+                    PeerMessage::has_forward,
+                    PeerMessage::get_forward,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, ForwardReply>(
+                    "reply",
+                    PeerMessage::has_reply,
+                    PeerMessage::get_reply,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Hello>(
+                    "hello",
+                    PeerMessage::has_hello,
+                    PeerMessage::get_hello,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, HelloReply>(
+                    "hello_reply",
+                    PeerMessage::has_hello_reply,
+                    PeerMessage::get_hello_reply,
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<PeerMessage>(
+                    "PeerMessage",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static PeerMessage {
+        static mut instance: ::protobuf::lazy::Lazy<PeerMessage> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const PeerMessage,
+        };
+        unsafe {
+            instance.get(PeerMessage::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for PeerMessage {
+    fn clear(&mut self) {
+        self.clear_raft();
+        self.clear_chunk();
+        self.clear_snapshot_failed();
+        self.clear_forward();
+        self.clear_reply();
+        self.clear_hello();
+        self.clear_hello_reply();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for PeerMessage {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for PeerMessage {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Hello {
+    // message fields
+    pub id: u64,
+    pub addr: ::std::string::String,
+    pub magic: ::std::vec::Vec<u8>,
+    pub version: u32,
+    pub min_version: u32,
+    pub cluster: ::std::string::String,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl Hello {
+    pub fn new() -> Hello {
+        ::std::default::Default::default()
+    }
+
+    // uint64 id = 1;
+
+    pub fn clear_id(&mut self) {
+        self.id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_id(&mut self, v: u64) {
+        self.id = v;
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    // string addr = 2;
+
+    pub fn clear_addr(&mut self) {
+        self.addr.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_addr(&mut self, v: ::std::string::String) {
+        self.addr = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_addr(&mut self) -> &mut ::std::string::String {
+        &mut self.addr
+    }
+
+    // Take field
+    pub fn take_addr(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.addr, ::std::string::String::new())
+    }
+
+    pub fn get_addr(&self) -> &str {
+        &self.addr
+    }
+
+    // bytes magic = 3;
+
+    pub fn clear_magic(&mut self) {
+        self.magic.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_magic(&mut self, v: ::std::vec::Vec<u8>) {
+        self.magic = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_magic(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.magic
+    }
+
+    // Take field
+    pub fn take_magic(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.magic, ::std::vec::Vec::new())
+    }
+
+    pub fn get_magic(&self) -> &[u8] {
+        &self.magic
+    }
+
+    // uint32 version = 4;
+
+    pub fn clear_version(&mut self) {
+        self.version = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_version(&mut self, v: u32) {
+        self.version = v;
+    }
+
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+
+    // uint32 min_version = 5;
+
+    pub fn clear_min_version(&mut self) {
+        self.min_version = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_min_version(&mut self, v: u32) {
+        self.min_version = v;
+    }
+
+    pub fn get_min_version(&self) -> u32 {
+        self.min_version
+    }
+
+    // string cluster = 6;
+
+    pub fn clear_cluster(&mut self) {
+        self.cluster.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_cluster(&mut self, v: ::std::string::String) {
+        self.cluster = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_cluster(&mut self) -> &mut ::std::string::String {
+        &mut self.cluster
+    }
+
+    // Take field
+    pub fn take_cluster(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.cluster, ::std::string::String::new())
+    }
+
+    pub fn get_cluster(&self) -> &str {
+        &self.cluster
+    }
+}
+
+impl ::protobuf::Message for Hello {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.id = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.addr)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.magic)?;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.version = tmp;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.min_version = tmp;
+                },
+                6 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.cluster)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.addr.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.addr);
+        }
+        if !self.magic.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(3, &self.magic);
+        }
+        if self.version != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.version, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.min_version != 0 {
+            my_size += ::protobuf::rt::value_size(5, self.min_version, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.cluster.is_empty() {
+            my_size += ::protobuf::rt::string_size(6, &self.cluster);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.id != 0 {
+            os.write_uint64(1, self.id)?;
+        }
+        if !self.addr.is_empty() {
+            os.write_string(2, &self.addr)?;
+        }
+        if !self.magic.is_empty() {
+            os.write_bytes(3, &self.magic)?;
+        }
+        if self.version != 0 {
+            os.write_uint32(4, self.version)?;
+        }
+        if self.min_version != 0 {
+            os.write_uint32(5, self.min_version)?;
+        }
+        if !self.cluster.is_empty() {
+            os.write_string(6, &self.cluster)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Hello {
+        Hello::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "id",
+                    |m: &Hello| { &m.id },
+                    |m: &mut Hello| { &mut m.id },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "addr",
+                    |m: &Hello| { &m.addr },
+                    |m: &mut Hello| { &mut m.addr },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                    "magic",
+                    |m: &Hello| { &m.magic },
+                    |m: &mut Hello| { &mut m.magic },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                    "version",
+                    |m: &Hello| { &m.version },
+                    |m: &mut Hello| { &mut m.version },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                    "min_version",
+                    |m: &Hello| { &m.min_version },
+                    |m: &mut Hello| { &mut m.min_version },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "cluster",
+                    |m: &Hello| { &m.cluster },
+                    |m: &mut Hello| { &mut m.cluster },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<Hello>(
+                    "Hello",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static Hello {
+        static mut instance: ::protobuf::lazy::Lazy<Hello> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const Hello,
+        };
+        unsafe {
+            instance.get(Hello::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for Hello {
+    fn clear(&mut self) {
+        self.clear_id();
+        self.clear_addr();
+        self.clear_magic();
+        self.clear_version();
+        self.clear_min_version();
+        self.clear_cluster();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Hello {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Hello {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct HelloReply {
+    // message fields
+    pub accepted: bool,
+    pub reason: ::std::string::String,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl HelloReply {
+    pub fn new() -> HelloReply {
+        ::std::default::Default::default()
+    }
+
+    // bool accepted = 1;
+
+    pub fn clear_accepted(&mut self) {
+        self.accepted = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_accepted(&mut self, v: bool) {
+        self.accepted = v;
+    }
+
+    pub fn get_accepted(&self) -> bool {
+        self.accepted
+    }
+
+    // string reason = 2;
+
+    pub fn clear_reason(&mut self) {
+        self.reason.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_reason(&mut self, v: ::std::string::String) {
+        self.reason = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_reason(&mut self) -> &mut ::std::string::String {
+        &mut self.reason
+    }
+
+    // Take field
+    pub fn take_reason(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.reason, ::std::string::String::new())
+    }
+
+    pub fn get_reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl ::protobuf::Message for HelloReply {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.accepted = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.reason)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.accepted != false {
+            my_size += 2;
+        }
+        if !self.reason.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.reason);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.accepted != false {
+            os.write_bool(1, self.accepted)?;
+        }
+        if !self.reason.is_empty() {
+            os.write_string(2, &self.reason)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> HelloReply {
+        HelloReply::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                    "accepted",
+                    |m: &HelloReply| { &m.accepted },
+                    |m: &mut HelloReply| { &mut m.accepted },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "reason",
+                    |m: &HelloReply| { &m.reason },
+                    |m: &mut HelloReply| { &mut m.reason },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<HelloReply>(
+                    "HelloReply",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static HelloReply {
+        static mut instance: ::protobuf::lazy::Lazy<HelloReply> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const HelloReply,
+        };
+        unsafe {
+            instance.get(HelloReply::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for HelloReply {
+    fn clear(&mut self) {
+        self.clear_accepted();
+        self.clear_reason();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for HelloReply {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for HelloReply {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SnapshotChunk {
+    // message fields
+    pub index: u64,
+    pub term: u64,
+    pub offset: u64,
+    pub data: ::std::vec::Vec<u8>,
+    pub last: bool,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl SnapshotChunk {
+    pub fn new() -> SnapshotChunk {
+        ::std::default::Default::default()
+    }
+
+    // uint64 index = 1;
+
+    pub fn clear_index(&mut self) {
+        self.index = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_index(&mut self, v: u64) {
+        self.index = v;
+    }
+
+    pub fn get_index(&self) -> u64 {
+        self.index
+    }
+
+    // uint64 term = 2;
+
+    pub fn clear_term(&mut self) {
+        self.term = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_term(&mut self, v: u64) {
+        self.term = v;
+    }
+
+    pub fn get_term(&self) -> u64 {
+        self.term
+    }
+
+    // uint64 offset = 3;
+
+    pub fn clear_offset(&mut self) {
+        self.offset = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_offset(&mut self, v: u64) {
+        self.offset = v;
+    }
+
+    pub fn get_offset(&self) -> u64 {
+        self.offset
+    }
+
+    // bytes data = 4;
+
+    pub fn clear_data(&mut self) {
+        self.data.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_data(&mut self, v: ::std::vec::Vec<u8>) {
+        self.data = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_data(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.data
+    }
+
+    // Take field
+    pub fn take_data(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.data, ::std::vec::Vec::new())
+    }
+
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+
+    // bool last = 5;
+
+    pub fn clear_last(&mut self) {
+        self.last = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last(&mut self, v: bool) {
+        self.last = v;
+    }
+
+    pub fn get_last(&self) -> bool {
+        self.last
+    }
+}
+
+impl ::protobuf::Message for SnapshotChunk {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.index = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.term = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.offset = tmp;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.data)?;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.last = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.index != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.index, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.term != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.term, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.offset != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.offset, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.data.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(4, &self.data);
+        }
+        if self.last != false {
+            my_size += 2;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.index != 0 {
+            os.write_uint64(1, self.index)?;
+        }
+        if self.term != 0 {
+            os.write_uint64(2, self.term)?;
+        }
+        if self.offset != 0 {
+            os.write_uint64(3, self.offset)?;
+        }
+        if !self.data.is_empty() {
+            os.write_bytes(4, &self.data)?;
+        }
+        if self.last != false {
+            os.write_bool(5, self.last)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SnapshotChunk {
+        SnapshotChunk::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "index",
+                    |m: &SnapshotChunk| { &m.index },
+                    |m: &mut SnapshotChunk| { &mut m.index },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "term",
+                    |m: &SnapshotChunk| { &m.term },
+                    |m: &mut SnapshotChunk| { &mut m.term },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "offset",
+                    |m: &SnapshotChunk| { &m.offset },
+                    |m: &mut SnapshotChunk| { &mut m.offset },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                    "data",
+                    |m: &SnapshotChunk| { &m.data },
+                    |m: &mut SnapshotChunk| { &mut m.data },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                    "last",
+                    |m: &SnapshotChunk| { &m.last },
+                    |m: &mut SnapshotChunk| { &mut m.last },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<SnapshotChunk>(
+                    "SnapshotChunk",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static SnapshotChunk {
+        static mut instance: ::protobuf::lazy::Lazy<SnapshotChunk> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const SnapshotChunk,
+        };
+        unsafe {
+            instance.get(SnapshotChunk::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for SnapshotChunk {
+    fn clear(&mut self) {
+        self.clear_index();
+        self.clear_term();
+        self.clear_offset();
+        self.clear_data();
+        self.clear_last();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SnapshotChunk {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SnapshotChunk {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SnapshotFailed {
+    // message fields
+    pub from: u64,
+    pub index: u64,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl SnapshotFailed {
+    pub fn new() -> SnapshotFailed {
+        ::std::default::Default::default()
+    }
+
+    // uint64 from = 1;
+
+    pub fn clear_from(&mut self) {
+        self.from = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_from(&mut self, v: u64) {
+        self.from = v;
+    }
+
+    pub fn get_from(&self) -> u64 {
+        self.from
+    }
+
+    // uint64 index = 2;
+
+    pub fn clear_index(&mut self) {
+        self.index = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_index(&mut self, v: u64) {
+        self.index = v;
+    }
+
+    pub fn get_index(&self) -> u64 {
+        self.index
+    }
+}
+
+impl ::protobuf::Message for SnapshotFailed {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.from = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.index = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.from != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.from, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.index != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.index, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.from != 0 {
+            os.write_uint64(1, self.from)?;
+        }
+        if self.index != 0 {
+            os.write_uint64(2, self.index)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SnapshotFailed {
+        SnapshotFailed::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "from",
+                    |m: &SnapshotFailed| { &m.from },
+                    |m: &mut SnapshotFailed| { &mut m.from },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "index",
+                    |m: &SnapshotFailed| { &m.index },
+                    |m: &mut SnapshotFailed| { &mut m.index },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<SnapshotFailed>(
+                    "SnapshotFailed",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static SnapshotFailed {
+        static mut instance: ::protobuf::lazy::Lazy<SnapshotFailed> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const SnapshotFailed,
+        };
+        unsafe {
+            instance.get(SnapshotFailed::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for SnapshotFailed {
+    fn clear(&mut self) {
+        self.clear_from();
+        self.clear_index();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SnapshotFailed {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SnapshotFailed {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Forward {
+    // message fields
+    pub from: u64,
+    pub id: u64,
+    pub request: ::std::vec::Vec<u8>,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl Forward {
+    pub fn new() -> Forward {
+        ::std::default::Default::default()
+    }
+
+    // uint64 from = 1;
+
+    pub fn clear_from(&mut self) {
+        self.from = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_from(&mut self, v: u64) {
+        self.from = v;
+    }
+
+    pub fn get_from(&self) -> u64 {
+        self.from
+    }
+
+    // uint64 id = 2;
+
+    pub fn clear_id(&mut self) {
+        self.id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_id(&mut self, v: u64) {
+        self.id = v;
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    // bytes request = 3;
+
+    pub fn clear_request(&mut self) {
+        self.request.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_request(&mut self, v: ::std::vec::Vec<u8>) {
+        self.request = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_request(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.request
+    }
+
+    // Take field
+    pub fn take_request(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.request, ::std::vec::Vec::new())
+    }
+
+    pub fn get_request(&self) -> &[u8] {
+        &self.request
+    }
+}
+
+impl ::protobuf::Message for Forward {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.from = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.id = tmp;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.request)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.from != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.from, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.id != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.request.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(3, &self.request);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.from != 0 {
+            os.write_uint64(1, self.from)?;
+        }
+        if self.id != 0 {
+            os.write_uint64(2, self.id)?;
+        }
+        if !self.request.is_empty() {
+            os.write_bytes(3, &self.request)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Forward {
+        Forward::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "from",
+                    |m: &Forward| { &m.from },
+                    |m: &mut Forward| { &mut m.from },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "id",
+                    |m: &Forward| { &m.id },
+                    |m: &mut Forward| { &mut m.id },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                    "request",
+                    |m: &Forward| { &m.request },
+                    |m: &mut Forward| { &mut m.request },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<Forward>(
+                    "Forward",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static Forward {
+        static mut instance: ::protobuf::lazy::Lazy<Forward> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const Forward,
+        };
+        unsafe {
+            instance.get(Forward::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for Forward {
+    fn clear(&mut self) {
+        self.clear_from();
+        self.clear_id();
+        self.clear_request();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Forward {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Forward {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ForwardReply {
+    // message fields
+    pub id: u64,
+    pub response: ::std::vec::Vec<u8>,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl ForwardReply {
+    pub fn new() -> ForwardReply {
+        ::std::default::Default::default()
+    }
+
+    // uint64 id = 1;
+
+    pub fn clear_id(&mut self) {
+        self.id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_id(&mut self, v: u64) {
+        self.id = v;
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    // bytes response = 2;
+
+    pub fn clear_response(&mut self) {
+        self.response.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_response(&mut self, v: ::std::vec::Vec<u8>) {
+        self.response = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_response(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.response
+    }
+
+    // Take field
+    pub fn take_response(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.response, ::std::vec::Vec::new())
+    }
+
+    pub fn get_response(&self) -> &[u8] {
+        &self.response
+    }
+}
+
+impl ::protobuf::Message for ForwardReply {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.id = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.response)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.response.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(2, &self.response);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.id != 0 {
+            os.write_uint64(1, self.id)?;
+        }
+        if !self.response.is_empty() {
+            os.write_bytes(2, &self.response)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ForwardReply {
+        ForwardReply::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "id",
+                    |m: &ForwardReply| { &m.id },
+                    |m: &mut ForwardReply| { &mut m.id },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                    "response",
+                    |m: &ForwardReply| { &m.response },
+                    |m: &mut ForwardReply| { &mut m.response },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<ForwardReply>(
+                    "ForwardReply",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static ForwardReply {
+        static mut instance: ::protobuf::lazy::Lazy<ForwardReply> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ForwardReply,
+        };
+        unsafe {
+            instance.get(ForwardReply::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for ForwardReply {
+    fn clear(&mut self) {
+        self.clear_id();
+        self.clear_response();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ForwardReply {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ForwardReply {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
 #[derive(Clone,PartialEq,Eq,Debug,Hash)]
 pub enum EntryKind {
     SET = 0,
     DELETE = 1,
     CALLBACK = 2,
+    REQUEST = 3,
 }
 
 impl ::protobuf::ProtobufEnum for EntryKind {
@@ -931,6 +3451,7 @@ impl ::protobuf::ProtobufEnum for EntryKind {
             0 => ::std::option::Option::Some(EntryKind::SET),
             1 => ::std::option::Option::Some(EntryKind::DELETE),
             2 => ::std::option::Option::Some(EntryKind::CALLBACK),
+            3 => ::std::option::Option::Some(EntryKind::REQUEST),
             _ => ::std::option::Option::None
         }
     }
@@ -940,6 +3461,7 @@ impl ::protobuf::ProtobufEnum for EntryKind {
             EntryKind::SET,
             EntryKind::DELETE,
             EntryKind::CALLBACK,
+            EntryKind::REQUEST,
         ];
         values
     }
@@ -973,16 +3495,45 @@ impl ::protobuf::reflect::ProtobufValue for EntryKind {
 }
 
 static file_descriptor_proto_data: &'static [u8] = b"\
-    \n\x08db.proto\x12\x02db\"b\n\x05Entry\x12\x0e\n\x02id\x18\x01\x20\x01(\
-    \x04R\x02id\x12\x10\n\x03key\x18\x02\x20\x01(\tR\x03key\x12\x14\n\x05val\
-    ue\x18\x03\x20\x01(\tR\x05value\x12!\n\x04kind\x18\x04\x20\x01(\x0e2\r.d\
-    b.EntryKindR\x04kind\"E\n\x04Snap\x12\x1d\n\x04data\x18\x01\x20\x03(\x0b\
-    2\t.db.DatumR\x04data\x12\x1e\n\x05peers\x18\x02\x20\x03(\x0b2\x08.db.Pe\
-    erR\x05peers\"*\n\x04Peer\x12\x0e\n\x02id\x18\x01\x20\x01(\x04R\x02id\
-    \x12\x12\n\x04addr\x18\x02\x20\x01(\tR\x04addr\"/\n\x05Datum\x12\x10\n\
-    \x03key\x18\x01\x20\x01(\tR\x03key\x12\x14\n\x05value\x18\x02\x20\x01(\t\
-    R\x05value*.\n\tEntryKind\x12\x07\n\x03SET\x10\0\x12\n\n\x06DELETE\x10\
-    \x01\x12\x0c\n\x08CALLBACK\x10\x02b\x06proto3\
+    \n\x08db.proto\x12\x02db\"\x90\x01\n\x05Entry\x12\x0e\n\x02id\x18\x01\
+    \x20\x01(\x04R\x02id\x12\x10\n\x03key\x18\x02\x20\x01(\tR\x03key\x12\x14\
+    \n\x05value\x18\x03\x20\x01(\tR\x05value\x12!\n\x04kind\x18\x04\x20\x01(\
+    \x0e2\r.db.EntryKindR\x04kind\x12\x18\n\x07request\x18\x05\x20\x01(\x0cR\
+    \x07request\x12\x12\n\x04node\x18\x06\x20\x01(\x04R\x04node\"E\n\x04Snap\
+    \x12\x1d\n\x04data\x18\x01\x20\x03(\x0b2\t.db.DatumR\x04data\x12\x1e\n\
+    \x05peers\x18\x02\x20\x03(\x0b2\x08.db.PeerR\x05peers\"j\n\x04Peer\x12\
+    \x0e\n\x02id\x18\x01\x20\x01(\x04R\x02id\x12\x12\n\x04addr\x18\x02\x20\
+    \x01(\tR\x04addr\x12\x1f\n\x0bpublic_addr\x18\x03\x20\x01(\tR\npublicAdd\
+    r\x12\x1d\n\nis_learner\x18\x04\x20\x01(\x08R\tisLearner\"/\n\x05Datum\
+    \x12\x10\n\x03key\x18\x01\x20\x01(\tR\x03key\x12\x14\n\x05value\x18\x02\
+    \x20\x01(\tR\x05value\"[\n\x08Manifest\x12\x18\n\x07applied\x18\x01\x20\
+    \x01(\x04R\x07applied\x12\x16\n\x06tables\x18\x02\x20\x03(\x04R\x06table\
+    s\x12\x1d\n\nnext_table\x18\x03\x20\x01(\x04R\tnextTable\"\xbe\x02\n\x0b\
+    PeerMessage\x12\x14\n\x04raft\x18\x01\x20\x01(\x0cH\0R\x04raft\x12)\n\
+    \x05chunk\x18\x02\x20\x01(\x0b2\x11.db.SnapshotChunkH\0R\x05chunk\x12=\n\
+    \x0fsnapshot_failed\x18\x03\x20\x01(\x0b2\x12.db.SnapshotFailedH\0R\x0es\
+    napshotFailed\x12'\n\x07forward\x18\x04\x20\x01(\x0b2\x0b.db.ForwardH\0R\
+    \x07forward\x12(\n\x05reply\x18\x05\x20\x01(\x0b2\x10.db.ForwardReplyH\0\
+    R\x05reply\x12!\n\x05hello\x18\x06\x20\x01(\x0b2\t.db.HelloH\0R\x05hello\
+    \x121\n\x0bhello_reply\x18\x07\x20\x01(\x0b2\x0e.db.HelloReplyH\0R\nhell\
+    oReplyB\x06\n\x04kind\"\x96\x01\n\x05Hello\x12\x0e\n\x02id\x18\x01\x20\
+    \x01(\x04R\x02id\x12\x12\n\x04addr\x18\x02\x20\x01(\tR\x04addr\x12\x14\n\
+    \x05magic\x18\x03\x20\x01(\x0cR\x05magic\x12\x18\n\x07version\x18\x04\
+    \x20\x01(\rR\x07version\x12\x1f\n\x0bmin_version\x18\x05\x20\x01(\rR\nmi\
+    nVersion\x12\x18\n\x07cluster\x18\x06\x20\x01(\tR\x07cluster\"@\n\nHello\
+    Reply\x12\x1a\n\x08accepted\x18\x01\x20\x01(\x08R\x08accepted\x12\x16\n\
+    \x06reason\x18\x02\x20\x01(\tR\x06reason\"y\n\rSnapshotChunk\x12\x14\n\
+    \x05index\x18\x01\x20\x01(\x04R\x05index\x12\x12\n\x04term\x18\x02\x20\
+    \x01(\x04R\x04term\x12\x16\n\x06offset\x18\x03\x20\x01(\x04R\x06offset\
+    \x12\x12\n\x04data\x18\x04\x20\x01(\x0cR\x04data\x12\x12\n\x04last\x18\
+    \x05\x20\x01(\x08R\x04last\":\n\x0eSnapshotFailed\x12\x12\n\x04from\x18\
+    \x01\x20\x01(\x04R\x04from\x12\x14\n\x05index\x18\x02\x20\x01(\x04R\x05i\
+    ndex\"G\n\x07Forward\x12\x12\n\x04from\x18\x01\x20\x01(\x04R\x04from\x12\
+    \x0e\n\x02id\x18\x02\x20\x01(\x04R\x02id\x12\x18\n\x07request\x18\x03\
+    \x20\x01(\x0cR\x07request\":\n\x0cForwardReply\x12\x0e\n\x02id\x18\x01\
+    \x20\x01(\x04R\x02id\x12\x1a\n\x08response\x18\x02\x20\x01(\x0cR\x08resp\
+    onse*;\n\tEntryKind\x12\x07\n\x03SET\x10\0\x12\n\n\x06DELETE\x10\x01\x12\
+    \x0c\n\x08CALLBACK\x10\x02\x12\x0b\n\x07REQUEST\x10\x03b\x06proto3\
 ";
 
 static mut file_descriptor_proto_lazy: ::protobuf::lazy::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::lazy::Lazy {
@@ -4,13 +4,14 @@
 use super::Message;
 use codec::Proto;
 use futures::prelude::*;
-use futures::sync::mpsc;
-use public::{Request, Response};
+use futures::sync::{mpsc, oneshot};
+use public::{self, response::ErrorCode, Request, Response};
 use std::net::SocketAddr;
 use std::thread::{self, JoinHandle};
 use tokio;
 use tokio::net::TcpListener;
 use tokio::prelude::*;
+use tokio::runtime::Runtime;
 use tokio_codec::{FramedRead, FramedWrite};
 
 #[derive(Debug)]
@@ -28,16 +29,44 @@ impl Command {
         &self.request
     }
 
-    pub fn reply(self, resp: Response) {
+    /// Sends the response back to the client, tagged with the id of the
+    /// request it answers.
+    pub fn reply(self, mut resp: Response) {
+        resp.set_request_id(self.request.get_request_id());
         tokio::spawn(self.tx.send(resp).then(|_| Ok(())));
     }
 }
 
-pub fn listen(db_channel: mpsc::Sender<Message>, addr: &SocketAddr) -> Handle {
+/// Sends a request to the database on behalf of a gateway and waits for its
+/// reply, which never comes if the database stops first.
+pub fn ask(
+    db_channel: mpsc::Sender<Message>,
+    request: Request,
+) -> impl Future<Item = Response, Error = ()> {
+    let (tx, rx) = mpsc::channel(1);
+    db_channel
+        .send(Message::Cmd(Command::new(tx, request)))
+        .map_err(|_| ())
+        .and_then(|_| rx.into_future().map_err(|_| ()))
+        .and_then(|(response, _)| response.ok_or(()))
+}
+
+/// Listens for clients of the node `id` in the cluster named by `cluster`,
+/// empty if it isn't known. A client must open with a handshake this node
+/// is compatible with.
+pub fn listen(
+    db_channel: mpsc::Sender<Message>,
+    addr: &SocketAddr,
+    id: u64,
+    cluster: String,
+) -> Handle {
     let addr = (*addr).clone();
+    let (stop, stopped) = oneshot::channel::<()>();
+    let (close, closed) = oneshot::channel::<()>();
     let handle = thread::spawn(move || {
         let listener = TcpListener::bind(&addr).unwrap();
         let db_channel = db_channel.clone();
+        let closed = closed.shared();
 
         let server = listener
             .incoming()
@@ -55,22 +84,81 @@ pub fn listen(db_channel: mpsc::Sender<Message>, addr: &SocketAddr) -> Handle {
                         .map_err(handle_err)
                 });
 
+                // Reading stops when the listener is closed, after which the
+                // writer finishes once the replies it's owed are written. A
+                // refused client is only sent the error.
                 let stream = FramedRead::new(stream, Proto::<Request>::new());
+                let db_channel = db_channel.clone();
+                let cluster = cluster.clone();
                 tokio::spawn(
                     stream
-                        .map_err(handle_err)
-                        .map(move |request| Message::Cmd(Command::new(tx.clone(), request)))
-                        .forward(db_channel.clone().sink_map_err(handle_err))
+                        .into_future()
+                        .map_err(|(e, _)| handle_err(e))
+                        .and_then(move |(first, stream)| {
+                            let (accepted, response) = match greet(first, id, &cluster) {
+                                Ok(response) => (true, response),
+                                Err(response) => (false, response),
+                            };
+                            tx.send(response).map_err(handle_err).and_then(move |tx| {
+                                if accepted {
+                                    Ok((tx, stream))
+                                } else {
+                                    Err(())
+                                }
+                            })
+                        })
+                        .and_then(|(tx, stream)| {
+                            stream
+                                .map_err(handle_err)
+                                .map(move |request| Message::Cmd(Command::new(tx.clone(), request)))
+                                .forward(db_channel.sink_map_err(handle_err))
+                        })
+                        .select2(closed.clone())
                         .then(|_| Ok(())),
                 );
 
                 Ok(())
             });
 
-        tokio::run(server);
+        let mut runtime = Runtime::new().expect("Unable to start the client listener");
+        let _ = runtime.block_on(server.select2(stopped));
+        runtime
+            .shutdown_on_idle()
+            .wait()
+            .expect("Client listener failed to shut down");
     });
 
-    Handle { handle }
+    Handle::new(handle, stop, close)
+}
+
+/// Answers the handshake a client opens with, or refuses the client with an
+/// error saying why.
+fn greet(first: Option<Request>, id: u64, cluster: &str) -> Result<Response, Response> {
+    let refuse = |reason: String| {
+        println!("Refusing a client: {}", reason);
+        Err(public::error_response(ErrorCode::INCOMPATIBLE, &reason))
+    };
+
+    let handshake = match first {
+        Some(ref request) if request.has_handshake() => request.get_handshake(),
+        Some(_) => {
+            return refuse(String::from(
+                "the client didn't open with a handshake, it may be an older build",
+            ))
+        }
+        None => return refuse(String::from("the client left before its handshake")),
+    };
+
+    match public::check_handshake(
+        handshake.get_magic(),
+        handshake.get_version(),
+        handshake.get_min_version(),
+        handshake.get_cluster(),
+        cluster,
+    ) {
+        Ok(()) => Ok(public::handshake_response(cluster, id)),
+        Err(reason) => refuse(reason),
+    }
 }
 
 fn handle_err(e: impl ::std::fmt::Debug) {
@@ -79,10 +167,37 @@ fn handle_err(e: impl ::std::fmt::Debug) {
 
 pub struct Handle {
     handle: JoinHandle<()>,
+    stop: Option<oneshot::Sender<()>>,
+    close: oneshot::Sender<()>,
 }
 
 impl Handle {
-    pub fn join(self) {
+    /// A handle to a listener running on `handle`, which stops accepting
+    /// connections once `stop` fires and drops them once `close` does.
+    pub(super) fn new(
+        handle: JoinHandle<()>,
+        stop: oneshot::Sender<()>,
+        close: oneshot::Sender<()>,
+    ) -> Handle {
+        Handle {
+            handle,
+            stop: Some(stop),
+            close,
+        }
+    }
+
+    /// Stops accepting connections. Those already open stay open, so they
+    /// can still be answered.
+    pub fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+    }
+
+    /// Closes every connection and waits for the listener to finish.
+    pub fn join(mut self) {
+        self.stop();
+        let _ = self.close.send(());
         self.handle.join().expect("Client listener panicked");
     }
 }
@@ -0,0 +1,539 @@
+//! Speaks enough of the Redis protocol for Redis tools to read and write
+//! the store. Each command is turned into requests to the database, so
+//! writes are still replicated through raft, and replies are sent in the
+//! order the commands arrived.
+
+use super::public::{ask, Handle};
+use super::resp::{Resp, Value};
+use super::Message;
+use codec;
+use futures::future::{self, Loop};
+use futures::prelude::*;
+use futures::stream;
+use futures::sync::{mpsc, oneshot};
+use public::{self, request, response, Request, Response};
+use std::io;
+use std::net::{self, SocketAddr};
+use std::str;
+use std::thread;
+use tokio;
+use tokio::net::TcpListener;
+use tokio::prelude::*;
+use tokio::reactor::Handle as Reactor;
+use tokio::runtime::Runtime;
+use tokio_codec::{FramedRead, FramedWrite};
+
+/// The reply to a command. Failing with a value means replying with it,
+/// which is always an error.
+type Reply = Box<Future<Item = Value, Error = Value> + Send>;
+
+/// How many keys `SCAN` returns when it isn't given a `COUNT`.
+const SCAN_COUNT: u64 = 10;
+
+/// Listens for Redis clients on `addr` and sends their commands to the
+/// database. The address is bound before this returns.
+pub fn listen(db_channel: mpsc::Sender<Message>, addr: &SocketAddr) -> io::Result<Handle> {
+    let listener = net::TcpListener::bind(addr)?;
+    let (stop, stopped) = oneshot::channel::<()>();
+    let (close, closed) = oneshot::channel::<()>();
+    let handle = thread::spawn(move || {
+        let listener = TcpListener::from_std(listener, &Reactor::default())
+            .expect("Unable to start the Redis listener");
+        let closed = closed.shared();
+
+        let server = listener
+            .incoming()
+            .map_err(handle_err)
+            .for_each(move |sock| {
+                let (stream, sink) = sock.split();
+                let db_channel = db_channel.clone();
+
+                // The connection is closed after the error for a command that
+                // doesn't parse is sent
+                let mut failed = false;
+                let replies = FramedRead::new(stream, Resp::new())
+                    .then(Ok::<_, ()>)
+                    .take_while(move |command| {
+                        let more = !failed;
+                        failed = command.is_err();
+                        Ok(more)
+                    })
+                    .and_then(move |command| {
+                        let reply: Reply = match command {
+                            Ok(args) => execute(db_channel.clone(), args),
+                            Err(e) => Box::new(future::err(protocol_error(e))),
+                        };
+                        reply.then(|reply| Ok::<_, ()>(reply.unwrap_or_else(|e| e)))
+                    });
+
+                let sink = FramedWrite::new(sink, Resp::new());
+                tokio::spawn(
+                    replies
+                        .forward(sink.sink_map_err(handle_err))
+                        .map(|_| ())
+                        .select2(closed.clone())
+                        .then(|_| Ok(())),
+                );
+                Ok(())
+            });
+
+        let mut runtime = Runtime::new().expect("Unable to start the Redis listener");
+        let _ = runtime.block_on(server.select2(stopped));
+        runtime
+            .shutdown_on_idle()
+            .wait()
+            .expect("Redis listener failed to shut down");
+    });
+
+    Ok(Handle::new(handle, stop, close))
+}
+
+/// Runs a command, given as its name followed by its arguments.
+fn execute(db: mpsc::Sender<Message>, args: Vec<Vec<u8>>) -> Reply {
+    let mut args = match args
+        .into_iter()
+        .map(String::from_utf8)
+        .collect::<Result<Vec<String>, _>>()
+    {
+        Ok(args) => args,
+        Err(_) => return fail("ERR commands must be valid UTF-8"),
+    };
+    let name = args.remove(0).to_uppercase();
+
+    match (name.as_str(), args.len()) {
+        ("PING", 0) => Box::new(ask_db(db, public::ping_request()).map(|_| ok("PONG"))),
+        ("PING", 1) => {
+            let message = args.remove(0).into_bytes();
+            Box::new(ask_db(db, public::ping_request()).map(|_| Value::Bulk(message)))
+        }
+        ("GET", 1) => Box::new(
+            ask_db(db, public::get_request(&args[0])).map(|response| value(response.get_get())),
+        ),
+        ("SET", 2) => Box::new(ask_write(db, public::set_request(&args[0], &args[1]))),
+        ("MSET", n) if n > 0 && n % 2 == 0 => {
+            let pairs = args
+                .chunks(2)
+                .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+                .collect::<Vec<_>>();
+            Box::new(ask_write(db, public::multi_set_request(&pairs)))
+        }
+        ("MGET", n) if n > 0 => {
+            let gets = args.iter().map(|key| public::get_request(key)).collect();
+            Box::new(ask_all(db, gets).map(|responses| {
+                Value::Array(
+                    responses
+                        .iter()
+                        .map(|response| value(response.get_get()))
+                        .collect(),
+                )
+            }))
+        }
+        ("EXISTS", n) if n > 0 => {
+            let gets = args.iter().map(|key| public::get_request(key)).collect();
+            Box::new(ask_all(db, gets).map(|responses| {
+                count(responses.iter(), |response| response.get_get().get_is_found())
+            }))
+        }
+        ("DEL", n) if n > 0 => {
+            let deletes = args.iter().map(|key| public::delete_request(key)).collect();
+            Box::new(ask_all(db, deletes).map(|responses| {
+                count(responses.iter(), |response| {
+                    response.get_delete().get_is_found()
+                })
+            }))
+        }
+        ("KEYS", 1) => keys(db, args.remove(0)),
+        ("SCAN", n) if n > 0 => scan(db, args),
+        ("INFO", 0) | ("INFO", 1) => {
+            Box::new(ask_db(db, public::info_request()).map(|response| info(response.get_info())))
+        }
+        ("PING", _) | ("GET", _) | ("SET", _) | ("MSET", _) | ("MGET", _) | ("EXISTS", _)
+        | ("DEL", _) | ("KEYS", _) | ("SCAN", _) | ("INFO", _) => fail(&format!(
+            "ERR wrong number of arguments for '{}' command",
+            name.to_lowercase()
+        )),
+        _ => fail(&format!("ERR unknown command '{}'", name)),
+    }
+}
+
+/// Lists every key matching the pattern, a page at a time.
+fn keys(db: mpsc::Sender<Message>, pattern: String) -> Reply {
+    let mut scan = request::Scan::new();
+    scan.set_prefix(literal_prefix(&pattern));
+
+    Box::new(
+        future::loop_fn((scan, Vec::new()), move |(mut scan, mut keys)| {
+            let pattern = pattern.clone();
+            ask_db(db.clone(), public::range_scan_request(scan.clone())).map(move |mut response| {
+                let mut page = response.take_scan();
+                keys.extend(
+                    page.take_keys()
+                        .into_vec()
+                        .into_iter()
+                        .filter(|key| glob(&pattern, key))
+                        .map(|key| Value::Bulk(key.into_bytes())),
+                );
+                if page.get_token().is_empty() {
+                    Loop::Break(Value::Array(keys))
+                } else {
+                    scan.set_token(page.take_token());
+                    Loop::Continue((scan, keys))
+                }
+            })
+        }),
+    )
+}
+
+/// Scans a page of keys. The cursor is the hex of the database's token for
+/// the next page, so it's never `0` until the scan is done.
+fn scan(db: mpsc::Sender<Message>, args: Vec<String>) -> Reply {
+    let mut scan = request::Scan::new();
+    let token = match from_hex(&args[0]) {
+        Some(token) => token,
+        None => return fail("ERR invalid cursor"),
+    };
+    scan.set_token(token);
+    scan.set_limit(SCAN_COUNT);
+
+    let mut pattern = String::from("*");
+    for option in args[1..].chunks(2) {
+        match (option[0].to_uppercase().as_str(), option.get(1)) {
+            ("MATCH", Some(value)) => pattern = value.clone(),
+            ("COUNT", Some(value)) => match value.parse() {
+                Ok(count) if count > 0 => scan.set_limit(count),
+                _ => return fail("ERR value is not an integer or out of range"),
+            },
+            _ => return fail("ERR syntax error"),
+        }
+    }
+    scan.set_prefix(literal_prefix(&pattern));
+
+    Box::new(
+        ask_db(db, public::range_scan_request(scan)).map(move |mut response| {
+            let mut page = response.take_scan();
+            let keys = page
+                .take_keys()
+                .into_vec()
+                .into_iter()
+                .filter(|key| glob(&pattern, key))
+                .map(|key| Value::Bulk(key.into_bytes()))
+                .collect();
+            let cursor = to_hex(page.get_token());
+            Value::Array(vec![Value::Bulk(cursor.into_bytes()), Value::Array(keys)])
+        }),
+    )
+}
+
+/// Sends a request to the database, failing with the error it replies with.
+fn ask_db(
+    db: mpsc::Sender<Message>,
+    request: Request,
+) -> impl Future<Item = Response, Error = Value> {
+    ask(db, request)
+        .map_err(|()| error_value("ERR the node is stopping"))
+        .and_then(|response| {
+            if response.has_error() {
+                Err(db_error(response.get_error()))
+            } else {
+                Ok(response)
+            }
+        })
+}
+
+/// Sends the requests one after another, so writes to a key are applied in
+/// the order they were given.
+fn ask_all(
+    db: mpsc::Sender<Message>,
+    requests: Vec<Request>,
+) -> impl Future<Item = Vec<Response>, Error = Value> {
+    stream::iter_ok(requests)
+        .and_then(move |request| ask_db(db.clone(), request))
+        .collect()
+}
+
+/// Sends a write, replying `OK` once it has been applied.
+fn ask_write(
+    db: mpsc::Sender<Message>,
+    request: Request,
+) -> impl Future<Item = Value, Error = Value> {
+    ask_db(db, request).and_then(|response| {
+        if response.get_success() {
+            Ok(ok("OK"))
+        } else {
+            Err(error_value("ERR the write failed"))
+        }
+    })
+}
+
+fn value(get: &response::Get) -> Value {
+    if get.get_is_found() {
+        Value::Bulk(get.get_value().as_bytes().to_vec())
+    } else {
+        Value::Null
+    }
+}
+
+fn count<'a, I, F>(responses: I, found: F) -> Value
+where
+    I: Iterator<Item = &'a Response>,
+    F: Fn(&Response) -> bool,
+{
+    Value::Integer(responses.filter(|response| found(response)).count() as i64)
+}
+
+fn info(info: &response::Info) -> Value {
+    let ids = |ids: &[u64]| {
+        ids.iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let lines = vec![
+        String::from("# Raft"),
+        format!("node_id:{}", info.get_id()),
+        format!("leader_id:{}", info.get_leader_id()),
+        format!("term:{}", info.get_term()),
+        format!("applied:{}", info.get_applied()),
+        format!("peers:{}", ids(info.get_peers())),
+        format!("learners:{}", ids(info.get_learners())),
+    ];
+    Value::Bulk(format!("{}\r\n", lines.join("\r\n")).into_bytes())
+}
+
+/// An error from the database, named by its code.
+fn db_error(e: &response::Error) -> Value {
+    let mut message = format!("{:?} {}", e.get_code(), e.get_message());
+    if e.get_leader_id() > 0 {
+        message.push_str(&format!(
+            ", the leader is {} at {}",
+            e.get_leader_id(),
+            e.get_leader_addr()
+        ));
+    }
+    error_value(&message)
+}
+
+fn protocol_error(e: codec::Error) -> Value {
+    match e {
+        codec::Error::FrameTooLarge(len, max) => error_value(&format!(
+            "ERR Protocol error: {} bytes is more than the limit of {}",
+            len, max
+        )),
+        _ => error_value("ERR Protocol error: the command doesn't parse"),
+    }
+}
+
+fn ok(text: &str) -> Value {
+    Value::Simple(text.to_string())
+}
+
+fn error_value(text: &str) -> Value {
+    Value::Error(text.to_string())
+}
+
+fn fail(text: &str) -> Reply {
+    Box::new(future::err(error_value(text)))
+}
+
+/// The part of a pattern before its first wildcard, which every key it
+/// matches starts with.
+fn literal_prefix(pattern: &str) -> String {
+    pattern
+        .chars()
+        .take_while(|&c| !"*?[\\".contains(c))
+        .collect()
+}
+
+/// Matches a key against a Redis pattern, where `*` matches anything, `?`
+/// any one character, `[abc]`, `[a-z]` and `[^a]` a character from a set,
+/// and `\` escapes the character after it.
+///
+/// Only the last `*` is ever gone back to, letting it take one more character
+/// when what follows doesn't match, so a pattern full of them is still
+/// matched in time bounded by the pattern's length times the key's.
+fn glob(pattern: &str, key: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let key: Vec<char> = key.chars().collect();
+
+    // Just past the last `*`, and where in the key it stopped taking
+    let mut star = None;
+    let (mut p, mut k) = (0, 0);
+    while k < key.len() {
+        let next = match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, k));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(p + 1),
+            Some('[') => {
+                let (matched, rest) = class(&pattern[p + 1..], key[k]);
+                if matched {
+                    Some(pattern.len() - rest.len())
+                } else {
+                    None
+                }
+            }
+            Some('\\') if p + 1 < pattern.len() => {
+                if pattern[p + 1] == key[k] {
+                    Some(p + 2)
+                } else {
+                    None
+                }
+            }
+            Some(&c) if c == key[k] => Some(p + 1),
+            _ => None,
+        };
+        match (next, star) {
+            (Some(next), _) => {
+                p = next;
+                k += 1;
+            }
+            (None, Some((after, taken))) => {
+                star = Some((after, taken + 1));
+                p = after;
+                k = taken + 1;
+            }
+            (None, None) => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Matches a character against the set at the start of `pattern`, just
+/// past its `[`, returning whether it matched and the rest of the pattern.
+fn class(pattern: &[char], c: char) -> (bool, &[char]) {
+    let negate = pattern.first() == Some(&'^');
+    let mut i = if negate { 1 } else { 0 };
+    let mut matched = false;
+    while i < pattern.len() && pattern[i] != ']' {
+        if pattern[i] == '\\' && i + 1 < pattern.len() {
+            i += 1;
+            matched |= pattern[i] == c;
+            i += 1;
+        } else if i + 2 < pattern.len() && pattern[i + 1] == '-' && pattern[i + 2] != ']' {
+            let (low, high) = if pattern[i] <= pattern[i + 2] {
+                (pattern[i], pattern[i + 2])
+            } else {
+                (pattern[i + 2], pattern[i])
+            };
+            matched |= low <= c && c <= high;
+            i += 3;
+        } else {
+            matched |= pattern[i] == c;
+            i += 1;
+        }
+    }
+    // A set left open runs to the end of the pattern
+    let rest = if i < pattern.len() {
+        &pattern[i + 1..]
+    } else {
+        &pattern[pattern.len()..]
+    };
+    (matched != negate, rest)
+}
+
+fn to_hex(token: &str) -> String {
+    if token.is_empty() {
+        return String::from("0");
+    }
+    token.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(cursor: &str) -> Option<String> {
+    if cursor == "0" {
+        return Some(String::new());
+    }
+    if cursor.len() % 2 != 0 {
+        return None;
+    }
+    let bytes = cursor
+        .as_bytes()
+        .chunks(2)
+        .map(|hex| {
+            let hex = str::from_utf8(hex).ok()?;
+            u8::from_str_radix(hex, 16).ok()
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+fn handle_err(e: impl ::std::fmt::Debug) {
+    println!("error occurred: {:?}", e);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns() {
+        assert!(glob("*", ""));
+        assert!(glob("user:*", "user:1"));
+        assert!(!glob("user:*", "users"));
+        assert!(glob("h?llo", "hello"));
+        assert!(glob("h[ae]llo", "hallo"));
+        assert!(!glob("h[^e]llo", "hello"));
+        assert!(glob("h[a-b]llo", "hbllo"));
+        assert!(glob("h\\*llo", "h*llo"));
+        assert!(!glob("h\\*llo", "hello"));
+        assert!(glob("*:*:end", "a:b:c:end"));
+        assert!(glob("a*b*c", "abbbc"));
+        assert!(!glob("a*b*c", "abbb"));
+        assert!(!glob("a[", "a"));
+
+        // Would take exponential time if every `*` were gone back to
+        let key = "a".repeat(100);
+        assert!(!glob("*a*a*a*a*a*a*a*a*a*a*a*a*b", &key));
+        assert!(glob("*a*a*a*a*a*a*a*a*a*a*a*a*", &key));
+
+        assert_eq!(literal_prefix("user:*"), "user:");
+        assert_eq!(literal_prefix("h[ae]llo"), "h");
+    }
+
+    #[test]
+    fn test_cursors() {
+        assert_eq!(to_hex(""), "0");
+        assert_eq!(to_hex("0"), "30");
+        assert_eq!(from_hex("30"), Some(String::from("0")));
+        assert_eq!(from_hex("0"), Some(String::new()));
+        assert_eq!(from_hex("3"), None);
+        assert_eq!(from_hex("zz"), None);
+    }
+
+    #[test]
+    fn test_unsupported_commands_are_refused() {
+        let (db, _rx) = mpsc::channel(1);
+        let reply = |args: &[&str]| {
+            let args = args.iter().map(|arg| arg.as_bytes().to_vec()).collect();
+            execute(db.clone(), args).wait().unwrap_err()
+        };
+        assert_eq!(
+            reply(&["FLUSHALL"]),
+            Value::Error(String::from("ERR unknown command 'FLUSHALL'"))
+        );
+        assert_eq!(
+            reply(&["get", "a", "b"]),
+            Value::Error(String::from(
+                "ERR wrong number of arguments for 'get' command"
+            ))
+        );
+        assert_eq!(
+            reply(&["MSET", "a"]),
+            Value::Error(String::from(
+                "ERR wrong number of arguments for 'mset' command"
+            ))
+        );
+        assert_eq!(
+            reply(&["SCAN", "0", "COUNT"]),
+            Value::Error(String::from("ERR syntax error"))
+        );
+    }
+
+    #[test]
+    fn test_a_taken_address_is_an_error() {
+        let taken = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let (db_channel, _) = mpsc::channel(1);
+        assert!(listen(db_channel, &taken.local_addr().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! The Redis serialization protocol, version 2. Commands arrive as arrays of
+//! bulk strings, or as a line of words when typed in by hand, and replies
+//! go out as any RESP value.
+
+use bytes::{BufMut, BytesMut};
+use codec::{Error, DEFAULT_MAX_FRAME};
+use std::cmp;
+use tokio_codec::{Decoder, Encoder};
+
+/// The most arguments a command may have.
+const MAX_ARGS: usize = 1024 * 1024;
+
+/// A reply to a command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Simple(String),
+    /// Starts with the kind of error, such as `ERR`.
+    Error(String),
+    Integer(i64),
+    Bulk(Vec<u8>),
+    /// The null bulk string, for a key that isn't set.
+    Null,
+    Array(Vec<Value>),
+}
+
+/// Decodes commands as their arguments, the first being the command's name,
+/// and encodes replies.
+///
+/// A command or argument longer than the codec's maximum is refused, as is
+/// anything that doesn't parse. Either way the connection should be closed,
+/// since it can't be read past the error.
+#[derive(Debug)]
+pub struct Resp {
+    max_frame: usize,
+}
+
+impl Resp {
+    pub fn new() -> Self {
+        Resp {
+            max_frame: DEFAULT_MAX_FRAME,
+        }
+    }
+}
+
+impl Decoder for Resp {
+    type Item = Vec<Vec<u8>>;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            match parse(src, self.max_frame)? {
+                Some((args, len)) => {
+                    src.split_to(len);
+                    // Blank lines are skipped rather than answered
+                    if !args.is_empty() {
+                        return Ok(Some(args));
+                    }
+                }
+                // A command can't grow forever while it's waiting for the rest
+                None if src.len() > self.max_frame => {
+                    return Err(Error::FrameTooLarge(src.len(), self.max_frame))
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+impl Encoder for Resp {
+    type Item = Value;
+    type Error = Error;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        write(&item, dst);
+        Ok(())
+    }
+}
+
+/// Parses the command at the start of the buffer, returning its arguments
+/// and length, or `None` if it hasn't all arrived.
+fn parse(buf: &[u8], max_frame: usize) -> Result<Option<(Vec<Vec<u8>>, usize)>, Error> {
+    if buf.is_empty() {
+        return Ok(None);
+    }
+    if buf[0] != b'*' {
+        return Ok(parse_inline(buf));
+    }
+
+    let (count, mut pos) = match parse_length(buf, 1)? {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    if count > MAX_ARGS {
+        return Err(Error::FrameTooLarge(count, MAX_ARGS));
+    }
+
+    let mut args = Vec::with_capacity(cmp::min(count, 64));
+    for _ in 0..count {
+        if pos >= buf.len() {
+            return Ok(None);
+        }
+        if buf[pos] != b'$' {
+            return Err(Error::InvalidByteSequence);
+        }
+        let (len, start) = match parse_length(buf, pos + 1)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        if len > max_frame {
+            return Err(Error::FrameTooLarge(len, max_frame));
+        }
+        let end = start + len;
+        if buf.len() < end + 2 {
+            return Ok(None);
+        }
+        if &buf[end..end + 2] != b"\r\n" {
+            return Err(Error::InvalidByteSequence);
+        }
+        args.push(buf[start..end].to_vec());
+        pos = end + 2;
+    }
+    Ok(Some((args, pos)))
+}
+
+/// A command typed in by hand, as words separated by spaces on one line.
+fn parse_inline(buf: &[u8]) -> Option<(Vec<Vec<u8>>, usize)> {
+    let newline = buf.iter().position(|&b| b == b'\n')?;
+    let args = buf[..newline]
+        .split(|b| b.is_ascii_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_vec())
+        .collect();
+    Some((args, newline + 1))
+}
+
+/// Parses the length on the line starting at `from`, returning it and where
+/// the next line starts.
+fn parse_length(buf: &[u8], from: usize) -> Result<Option<(usize, usize)>, Error> {
+    let end = match buf[from..].windows(2).position(|w| w == b"\r\n") {
+        Some(end) => from + end,
+        None => return Ok(None),
+    };
+    let digits = &buf[from..end];
+    if digits.is_empty() || digits.len() > 19 || !digits.iter().all(|b| b.is_ascii_digit()) {
+        return Err(Error::InvalidByteSequence);
+    }
+    let len = digits
+        .iter()
+        .fold(0, |len, &digit| len * 10 + (digit - b'0') as usize);
+    Ok(Some((len, end + 2)))
+}
+
+fn write(value: &Value, dst: &mut BytesMut) {
+    match *value {
+        Value::Simple(ref text) => line(dst, b'+', text.as_bytes()),
+        Value::Error(ref text) => line(dst, b'-', text.as_bytes()),
+        Value::Integer(n) => line(dst, b':', n.to_string().as_bytes()),
+        Value::Bulk(ref data) => {
+            line(dst, b'$', data.len().to_string().as_bytes());
+            dst.extend_from_slice(data);
+            dst.extend_from_slice(b"\r\n");
+        }
+        Value::Null => line(dst, b'$', b"-1"),
+        Value::Array(ref values) => {
+            line(dst, b'*', values.len().to_string().as_bytes());
+            for value in values {
+                write(value, dst);
+            }
+        }
+    }
+}
+
+/// Writes a line, replacing any line breaks in it since RESP can't escape
+/// them outside a bulk string.
+fn line(dst: &mut BytesMut, kind: u8, text: &[u8]) {
+    dst.reserve(text.len() + 3);
+    dst.put_u8(kind);
+    for &b in text {
+        dst.put_u8(if b == b'\r' || b == b'\n' { b' ' } else { b });
+    }
+    dst.put_slice(b"\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(words: &[&str]) -> Vec<Vec<u8>> {
+        words.iter().map(|word| word.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_decoding_commands() {
+        let mut src = BytesMut::from(&b"*3\r\n$3\r\nSET\r\n$1\r\na\r\n$5\r\nhe"[..]);
+        let mut resp = Resp::new();
+        assert!(resp.decode(&mut src).unwrap().is_none());
+
+        src.extend_from_slice(b"llo\r\n\r\nPING  hi\r\n");
+        assert_eq!(
+            resp.decode(&mut src).unwrap(),
+            Some(args(&["SET", "a", "hello"]))
+        );
+        assert_eq!(resp.decode(&mut src).unwrap(), Some(args(&["PING", "hi"])));
+        assert!(src.is_empty());
+
+        let mut malformed = BytesMut::from(&b"*1\r\n:1\r\n"[..]);
+        match resp.decode(&mut malformed) {
+            Err(Error::InvalidByteSequence) => (),
+            other => panic!("expected invalid bytes, got {:?}", other),
+        }
+
+        let mut oversized = BytesMut::from(&b"*1\r\n$99999999999\r\n"[..]);
+        match resp.decode(&mut oversized) {
+            Err(Error::FrameTooLarge(..)) => (),
+            other => panic!("expected the frame to be too large, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_encoding_replies() {
+        let mut dst = BytesMut::new();
+        let reply = Value::Array(vec![
+            Value::Simple(String::from("OK")),
+            Value::Error(String::from("ERR no\r\nway")),
+            Value::Integer(-2),
+            Value::Bulk(b"a\r\nb".to_vec()),
+            Value::Null,
+        ]);
+        Resp::new().encode(reply, &mut dst).unwrap();
+        assert_eq!(
+            &dst[..],
+            &b"*5\r\n+OK\r\n-ERR no  way\r\n:-2\r\n$4\r\na\r\nb\r\n$-1\r\n"[..]
+        );
+    }
+}
@@ -1,90 +1,420 @@
+use super::crc;
+use super::engine::{read_delimited, write_delimited};
 use super::proto;
-use protobuf::{parse_from_bytes, Message};
+use super::wal::Wal;
+use bytes::{Buf, BufMut, IntoBuf};
+use protobuf::{parse_from_bytes, Message, ProtobufError};
 use raft::{self, prelude::*, storage::MemStorage};
-use std::collections::HashMap;
-use std::fs::File;
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
+/// Every file the storage writes starts with a header of the magic number,
+/// the format version, a crc of the contents and the length of the contents.
+const MAGIC: &[u8] = b"KVRS";
+const FORMAT_VERSION: u32 = 1;
+const FILE_HEADER: usize = 20;
+/// How many entries may be applied between snapshots.
+const SNAPSHOT_ENTRIES: u64 = 1000;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Proto(ProtobufError),
+    Raft(raft::Error),
+    /// The file doesn't start with the magic number, so it isn't ours or it
+    /// was written before the header was added, which can't be read.
+    BadMagic(PathBuf),
+    /// The file was written by a format version this build can't read.
+    UnsupportedVersion(PathBuf, u32),
+    /// The file is shorter than its header says it should be.
+    Truncated(PathBuf),
+    /// The contents don't match the checksum in the header.
+    Checksum(PathBuf),
+    Corrupt(String),
+    /// The node was asked to bootstrap a cluster it can't, such as when it
+    /// already holds data.
+    Bootstrap(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref e) => write!(f, "io error: {}", e),
+            Error::Proto(ref e) => write!(f, "unable to decode protobuf: {}", e),
+            Error::Raft(ref e) => write!(f, "raft error: {}", e),
+            Error::BadMagic(ref path) => write!(
+                f,
+                "{} is not a kv-raft snapshot (bad magic), files from builds before \
+                 the header must be removed and the node resynced",
+                path.display()
+            ),
+            Error::UnsupportedVersion(ref path, version) => write!(
+                f,
+                "{} has format version {}, but only version {} is supported",
+                path.display(),
+                version,
+                FORMAT_VERSION
+            ),
+            Error::Truncated(ref path) => write!(f, "{} is truncated", path.display()),
+            Error::Checksum(ref path) => write!(f, "{} failed its checksum", path.display()),
+            Error::Corrupt(ref msg) => write!(f, "storage is corrupt: {}", msg),
+            Error::Bootstrap(ref msg) => write!(f, "refusing to bootstrap: {}", msg),
+        }
+    }
+}
+
+impl ::std::error::Error for Error {
+    fn description(&self) -> &str {
+        "storage error"
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<ProtobufError> for Error {
+    fn from(e: ProtobufError) -> Error {
+        Error::Proto(e)
+    }
+}
+
+impl From<raft::Error> for Error {
+    fn from(e: raft::Error) -> Error {
+        Error::Raft(e)
+    }
+}
+
 /// The file store is how we persist the state to the file system.
 ///
 /// Generally we don't want to "confirm" that we've saved until the file
 /// system comes back as ok, which in async is tough.
+///
+/// Entries are written to the write-ahead log before they are handed to the
+/// memory storage, and are replayed from it on startup. The hard state is
+/// kept in its own small file next to the snapshot.
+///
+/// Application data belongs to the state machine, which the storage never
+/// sees. A snapshot on disk only records the index, term, conf state and
+/// peers the state machine was checkpointed at.
 pub struct KeyValueCore {
-    data: HashMap<String, String>,
     peers: Vec<proto::Peer>,
+    conf_state: ConfState,
     file: PathBuf,
+    hard_state: PathBuf,
+    incoming: PathBuf,
+    /// The data behind the snapshot in `incoming`.
+    incoming_data: PathBuf,
+    /// Where the data of a snapshot from the leader is put together as its
+    /// chunks arrive.
+    partial: PathBuf,
+    receiving: Option<Receiving>,
+    /// The data behind the last snapshot generated for a follower.
+    outgoing: PathBuf,
+    /// Holds the token of the cluster the node bootstrapped or joined.
+    cluster: PathBuf,
+    /// A snapshot from the leader that a restart interrupted.
+    interrupted: Option<Snapshot>,
+    /// The last snapshot built for a follower, and whether raft has asked
+    /// for a newer one.
+    generated: Option<Snapshot>,
+    snapshot_requested: bool,
     mem: MemStorage,
+    wal: Wal,
 }
 
 impl KeyValueCore {
-    fn new(file: PathBuf) -> Self {
+    fn new(file: PathBuf) -> Result<Self, Error> {
+        let (wal, mut entries) = Wal::open(sibling(&file, ".wal"))?;
+
         let mut core = KeyValueCore {
-            data: HashMap::new(),
             mem: MemStorage::new(),
+            hard_state: sibling(&file, ".hardstate"),
+            incoming: sibling(&file, ".incoming"),
+            incoming_data: sibling(&file, ".incoming-data"),
+            partial: sibling(&file, ".partial"),
+            receiving: None,
+            outgoing: sibling(&file, ".outgoing"),
+            cluster: sibling(&file, ".cluster"),
+            interrupted: None,
+            generated: None,
+            snapshot_requested: false,
+            conf_state: ConfState::new(),
             file,
             peers: Vec::new(),
+            wal,
         };
         if core.file.is_file() {
-            let mut handle = File::open(&core.file).unwrap();
-            let mut buf = vec![];
+            let buf = read_file(&core.file)?;
+            let snap = parse_from_bytes::<Snapshot>(&buf)?;
+            core.load_snapshot(snap)?;
+        }
+
+        if core.incoming.is_file() {
+            match read_file(&core.incoming) {
+                Ok(buf) => {
+                    // A snapshot from the leader was only part way applied.
+                    // It replaces the log, so nothing in the wal is kept.
+                    core.interrupted = Some(parse_from_bytes::<Snapshot>(&buf)?);
+                    entries.clear();
+                }
+                Err(Error::Io(e)) => return Err(Error::Io(e)),
+                Err(_) => {
+                    // Torn while being written, so it was never applied.
+                    fs::remove_file(&core.incoming)?;
+                }
+            }
+        }
 
-            use std::io::Read;
-            handle.read_to_end(&mut buf).unwrap();
+        // Anything the snapshot already covers is skipped, the rest must
+        // continue on from the snapshot.
+        let snap_index = core.mem.first_index()? - 1;
+        let entries: Vec<Entry> = entries
+            .into_iter()
+            .filter(|e| e.get_index() > snap_index)
+            .collect();
 
-            // This could OOM the device if the raft gets too large
-            let snap = parse_from_bytes::<Snapshot>(&buf).expect("Db corrupt");
-            core.apply_snapshot(snap).unwrap();
+        if let Some(first) = entries.first() {
+            if first.get_index() != snap_index + 1 {
+                return Err(Error::Corrupt(format!(
+                    "wal starts at {} but the snapshot ends at {}",
+                    first.get_index(),
+                    snap_index
+                )));
+            }
         }
-        core
+        core.mem.wl().append(&entries)?;
+        println!("Replayed {} entries from the wal", entries.len());
+
+        if core.hard_state.is_file() {
+            let buf = read_file(&core.hard_state)?;
+            let hs = parse_from_bytes::<HardState>(&buf)?;
+            core.mem.wl().set_hardstate(hs);
+        }
+
+        Ok(core)
     }
 
-    pub fn get(&self, key: &str) -> Option<String> {
-        self.data.get(&key.to_string()).map(String::to_owned)
+    /// Checks whether the node should bootstrap the cluster named by
+    /// `token`, and records the token if so. Only a node that has never held
+    /// any raft state may bootstrap. One restarted after bootstrapping the
+    /// same cluster carries on as it was, while one holding data from
+    /// anywhere else is refused, so a cluster can't be bootstrapped twice.
+    pub fn bootstrap(&mut self, token: &str) -> Result<bool, Error> {
+        if self.cluster.is_file() {
+            let joined = read_file(&self.cluster)?;
+            if joined != token.as_bytes() {
+                return Err(Error::Bootstrap(format!(
+                    "the node belongs to cluster {:?}",
+                    String::from_utf8_lossy(&joined)
+                )));
+            }
+            // Nothing is written until the first ready, so a crash before
+            // then leaves it to bootstrap again
+            return Ok(self.is_empty()?);
+        }
+
+        if !self.is_empty()? {
+            return Err(Error::Bootstrap(String::from(
+                "the node already holds raft state",
+            )));
+        }
+        write_file(&self.cluster, token.as_bytes())?;
+        Ok(true)
     }
 
-    pub fn scan(&self) -> Vec<String> {
-        self.data.keys().map(String::to_owned).collect()
+    /// Records the token of a cluster the node was added to, unless it
+    /// already knows its cluster. From then on it's held to that cluster
+    /// as if it had bootstrapped it.
+    pub fn join(&mut self, token: &str) -> io::Result<bool> {
+        if self.cluster.is_file() {
+            return Ok(false);
+        }
+        write_file(&self.cluster, token.as_bytes())?;
+        Ok(true)
     }
 
-    pub fn set(&mut self, key: &str, value: &str) {
-        self.data.insert(key.to_string(), value.to_string());
+    /// Whether the node has never held a log, a snapshot or a vote.
+    fn is_empty(&self) -> raft::Result<bool> {
+        let state = self.mem.initial_state()?;
+        Ok(self.mem.last_index()? == 0 && state.hard_state == HardState::new())
     }
 
-    pub fn delete(&mut self, key: &str) -> Option<String> {
-        self.data.remove(key)
+    /// Takes the snapshot a restart interrupted, which must be restored into
+    /// the state machine and finished before anything else happens.
+    pub fn take_interrupted(&mut self) -> Option<Snapshot> {
+        self.interrupted.take()
     }
 
-    pub fn apply_snapshot(&mut self, snapshot: Snapshot) -> raft::Result<()> {
-        let snap =
-            parse_from_bytes::<proto::Snap>(snapshot.get_data()).expect("Unexpected marshall err");
+    /// Brings the snapshot up to the state machine's last checkpoint. A crash
+    /// between checkpointing the machine and writing the snapshot leaves the
+    /// machine ahead. Entries it holds must not be applied twice, so the
+    /// snapshot is rolled forward to meet it.
+    pub fn catch_up(&mut self, checkpointed: u64) -> Result<(), Error> {
+        let snap_index = self.mem.first_index()? - 1;
+        if checkpointed < snap_index {
+            return Err(Error::Corrupt(format!(
+                "state machine was checkpointed at {} but the snapshot is at {}",
+                checkpointed, snap_index
+            )));
+        }
+        if checkpointed == snap_index {
+            return Ok(());
+        }
+        if checkpointed > self.mem.last_index()? {
+            return Err(Error::Corrupt(format!(
+                "state machine was checkpointed at {} but the log ends at {}",
+                checkpointed,
+                self.mem.last_index()?
+            )));
+        }
+
+        self.replay_conf_changes(snap_index, checkpointed)?;
+        self.create_snapshot(checkpointed)
+    }
 
-        let mut data = HashMap::with_capacity(snap.get_data().len());
-        for datum in snap.get_data() {
-            let key = datum.get_key().to_string();
-            let value = datum.get_value().to_string();
-            data.insert(key, value);
+    /// Writes a chunk of a snapshot's data from the leader. The chunks of a
+    /// snapshot arrive in order, so a chunk from a new snapshot starts over,
+    /// one seen before is a duplicate and one past a gap abandons the data.
+    pub fn receive_chunk(&mut self, chunk: &proto::SnapshotChunk) -> io::Result<()> {
+        let same = |r: &Receiving| r.index == chunk.get_index() && r.term == chunk.get_term();
+        if chunk.get_offset() == 0 && !self.receiving.as_ref().map_or(false, &same) {
+            self.receiving = Some(Receiving {
+                index: chunk.get_index(),
+                term: chunk.get_term(),
+                offset: 0,
+                done: false,
+                out: File::create(&self.partial)?,
+            });
         }
+
+        let gap = match self.receiving {
+            Some(ref receiving) if same(receiving) => {
+                if chunk.get_offset() < receiving.offset || receiving.done {
+                    return Ok(());
+                }
+                chunk.get_offset() > receiving.offset
+            }
+            _ => return Ok(()),
+        };
+        if gap {
+            println!(
+                "Chunk of snapshot {} is missing data before {}",
+                chunk.get_index(),
+                chunk.get_offset()
+            );
+            self.receiving = None;
+            return Ok(());
+        }
+
+        if let Some(ref mut receiving) = self.receiving {
+            receiving.out.write_all(chunk.get_data())?;
+            receiving.offset += chunk.get_data().len() as u64;
+            receiving.done = chunk.get_last();
+        }
+        Ok(())
+    }
+
+    /// Takes the data received for the snapshot at `index` and `term`, which
+    /// becomes the data the snapshot is restored from. Returns false if not
+    /// all of it arrived.
+    pub fn take_received(&mut self, index: u64, term: u64) -> io::Result<bool> {
+        match self.receiving.take() {
+            Some(receiving) => {
+                if !receiving.done || receiving.index != index || receiving.term != term {
+                    return Ok(false);
+                }
+                receiving.out.sync_all()?;
+                fs::rename(&self.partial, &self.incoming_data)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Opens the data of the snapshot being restored, returning the peers
+    /// written at its start and the reader positioned at the state
+    /// machine's data that follows.
+    pub fn read_incoming(&self) -> io::Result<(proto::Snap, BufReader<File>)> {
+        let mut input = BufReader::new(File::open(&self.incoming_data)?);
+        let snap = read_delimited::<proto::Snap, _>(&mut input)?.unwrap_or_else(proto::Snap::new);
+        Ok((snap, input))
+    }
+
+    /// Keeps a snapshot received from the leader on disk until it's fully
+    /// applied, so a crash part way through picks up where it left off on
+    /// restart. Its data must already have been received.
+    pub fn begin_snapshot(&mut self, snapshot: &Snapshot) -> Result<(), Error> {
+        write_file(&self.incoming, &snapshot.write_to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Records a snapshot once its data has been restored into the state
+    /// machine. It replaces the whole log, so the wal is dropped with it.
+    pub fn finish_snapshot(
+        &mut self,
+        mut snapshot: Snapshot,
+        snap: proto::Snap,
+    ) -> Result<(), Error> {
+        snapshot.set_data(snap.write_to_bytes()?);
+        write_file(&self.file, &snapshot.write_to_bytes()?)?;
+        self.wal.clear()?;
+        self.load_snapshot(snapshot)?;
+        self.generated = None;
+
+        if self.incoming.is_file() {
+            fs::remove_file(&self.incoming)?;
+        }
+        if self.incoming_data.is_file() {
+            fs::remove_file(&self.incoming_data)?;
+        }
+        Ok(())
+    }
+
+    /// Loads a snapshot as it's kept on disk, which holds only the peers.
+    fn load_snapshot(&mut self, mut snapshot: Snapshot) -> raft::Result<()> {
+        let snap = parse_from_bytes::<proto::Snap>(snapshot.get_data())?;
         self.peers = snap.get_peers().iter().map(|p| p.clone()).collect();
-        self.data = data;
+        self.conf_state = snapshot.get_metadata().get_conf_state().clone();
+
+        snapshot.clear_data();
         self.mem.wl().apply_snapshot(snapshot)
     }
 
     pub fn append(&mut self, ents: &[Entry]) -> raft::Result<()> {
+        self.wal.append(ents)?;
         self.mem.wl().append(ents)
     }
 
-    pub fn set_hardstate(&mut self, hs: HardState) {
+    /// Syncs the hard state to disk before it's handed to memory, so a
+    /// restarted node can't vote twice in the same term.
+    pub fn set_hardstate(&mut self, hs: HardState) -> raft::Result<()> {
+        write_file(&self.hard_state, &hs.write_to_bytes()?)?;
         self.mem.wl().set_hardstate(hs);
+        Ok(())
+    }
+
+    pub fn set_conf_state(&mut self, cs: ConfState) {
+        self.conf_state = cs;
     }
 
+    /// Adds a peer, or replaces the one with the same id, as when a learner
+    /// is promoted.
     pub fn add_node(&mut self, peer: proto::Peer) {
-        if peer.get_id() == 0 || self.peers.iter().any(|p| &peer == p) {
-            // Already added
+        if peer.get_id() == 0 {
             return;
         }
 
-        self.peers.push(peer);
+        match self.peers.iter().position(|p| p.id == peer.id) {
+            Some(index) => self.peers[index] = peer,
+            None => self.peers.push(peer),
+        }
     }
 
     pub fn remove_node(&mut self, id: u64) {
@@ -97,52 +427,251 @@ impl KeyValueCore {
         &self.peers[..]
     }
 
-    pub fn create_snapshot<'a>(&'a mut self, idx: u64, cs: Option<ConfState>) {
-        let data = self.to_snap()
-            .write_to_bytes()
-            .expect("Unexpected marshal err");
+    /// Whether enough has been applied since the last snapshot, up to `idx`,
+    /// that it's time for another.
+    pub fn needs_snapshot(&self, idx: u64) -> raft::Result<bool> {
+        let snap_index = self.mem.first_index()? - 1;
+        Ok(idx > snap_index && idx - snap_index >= SNAPSHOT_ENTRIES)
+    }
+
+    /// Writes the snapshot for a state machine checkpointed at `idx` and then
+    /// drops the log the snapshot covers.
+    pub fn create_snapshot(&mut self, idx: u64) -> Result<(), Error> {
+        let mut snap = self.mem
+            .wl()
+            .create_snapshot(idx, Some(self.conf_state.clone()), vec![])
+            .map(|snap| snap.clone())?;
+
+        snap.set_data(self.peers_snap().write_to_bytes()?);
+        write_file(&self.file, &snap.write_to_bytes()?)?;
+        self.compact(idx)?;
+        println!("Snap written to disk at {}", idx);
+        Ok(())
+    }
 
-        if let Ok(snap) = self.mem.wl().create_snapshot(idx, cs, data) {
-            use std::io::Write;
+    /// Whether raft wants a snapshot to send to a follower that the last
+    /// generated one can't serve.
+    pub fn snapshot_requested(&self) -> bool {
+        self.snapshot_requested
+    }
 
-            let bytes = snap.write_to_bytes().unwrap();
-            let mut file = File::create(&self.file).unwrap();
-            file.write_all(&bytes).unwrap();
-            println!("Snap written to disk");
-        } else {
-            println!("Snap not created");
+    /// Stores a snapshot of the state machine as of `applied`, to be handed
+    /// to raft the next time it asks. The peers are written ahead of the
+    /// machine's own data, to a file that's streamed to the follower. The
+    /// snapshot raft sees only holds the metadata.
+    ///
+    /// The file is replaced by renaming over it, so one already opened to be
+    /// sent still reads the data it was opened with.
+    pub fn set_generated_snapshot<F>(&mut self, applied: u64, write: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+    {
+        // A restart drops the generated snapshot, so it's never synced
+        let tmp = sibling(&self.outgoing, ".tmp");
+        {
+            let mut out = BufWriter::new(File::create(&tmp)?);
+            write_delimited(&mut out, &self.peers_snap())?;
+            write(&mut out)?;
+            out.flush()?;
         }
+        fs::rename(&tmp, &self.outgoing)?;
+
+        let mut snapshot = Snapshot::new();
+        snapshot.mut_metadata().set_index(applied);
+        snapshot.mut_metadata().set_term(self.mem.term(applied)?);
+        snapshot
+            .mut_metadata()
+            .set_conf_state(self.conf_state.clone());
+
+        self.generated = Some(snapshot);
+        self.snapshot_requested = false;
+        Ok(())
     }
 
-    pub fn compact(&mut self, idx: u64) -> raft::Result<()> {
-        self.mem.wl().compact(idx)
+    /// Opens the data generated for the snapshot at `index` and `term`, or
+    /// `None` if it has since been replaced.
+    pub fn open_generated(&self, index: u64, term: u64) -> io::Result<Option<File>> {
+        match self.generated {
+            Some(ref snapshot)
+                if snapshot.get_metadata().get_index() == index
+                    && snapshot.get_metadata().get_term() == term =>
+            {
+                File::open(&self.outgoing).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Only `Db` can read the state machine, so the storage can't build a
+    /// snapshot when raft asks for one. Instead the request is noted, and
+    /// raft tries again once `Db` has generated it.
+    fn snapshot(&mut self) -> raft::Result<Snapshot> {
+        let snap_index = self.mem.first_index()? - 1;
+        if let Some(ref snapshot) = self.generated {
+            // An older snapshot would leave a gap before the log starts
+            if snapshot.get_metadata().get_index() >= snap_index {
+                return Ok(snapshot.clone());
+            }
+        }
+
+        self.snapshot_requested = true;
+        Err(raft::Error::Store(
+            raft::StorageError::SnapshotTemporarilyUnavailable,
+        ))
+    }
+
+    /// Replays the conf changes in `(from, to]` onto the conf state and peers,
+    /// the same way `Db` does when it applies them.
+    fn replay_conf_changes(&mut self, from: u64, to: u64) -> Result<(), Error> {
+        for entry in self.mem.entries(from + 1, to + 1, u64::max_value())? {
+            if entry.get_entry_type() != EntryType::EntryConfChange {
+                continue;
+            }
+
+            let cc = parse_from_bytes::<ConfChange>(entry.get_data())?;
+            let id = cc.get_node_id();
+            match cc.get_change_type() {
+                ConfChangeType::AddNode => {
+                    self.conf_state.mut_learners().retain(|n| *n != id);
+                    if !self.conf_state.get_nodes().contains(&id) {
+                        self.conf_state.mut_nodes().push(id);
+                    }
+                    if let Ok(peer) = parse_from_bytes::<proto::Peer>(cc.get_context()) {
+                        self.add_node(peer);
+                    }
+                }
+                ConfChangeType::AddLearnerNode => {
+                    if !self.conf_state.get_learners().contains(&id) {
+                        self.conf_state.mut_learners().push(id);
+                    }
+                    if let Ok(peer) = parse_from_bytes::<proto::Peer>(cc.get_context()) {
+                        self.add_node(peer);
+                    }
+                }
+                ConfChangeType::RemoveNode => {
+                    self.conf_state.mut_nodes().retain(|n| *n != id);
+                    self.conf_state.mut_learners().retain(|n| *n != id);
+                    self.remove_node(id);
+                }
+            }
+        }
+        Ok(())
     }
 
-    fn to_snap(&self) -> proto::Snap {
+    fn compact(&mut self, idx: u64) -> raft::Result<()> {
+        self.mem.wl().compact(idx)?;
+        self.wal.compact(idx)?;
+        Ok(())
+    }
+
+    fn peers_snap(&self) -> proto::Snap {
         let mut snap = proto::Snap::new();
-        let mut data = Vec::with_capacity(self.data.len());
-        for (k, v) in &self.data {
-            let mut datum = proto::Datum::new();
-            datum.set_key(k.to_owned());
-            datum.set_value(v.to_owned());
-            data.push(datum)
-        }
-        snap.set_data(data.into());
         snap.set_peers(self.peers.clone().into());
         snap
     }
 }
 
+/// The data of a snapshot from the leader that's still arriving.
+struct Receiving {
+    index: u64,
+    term: u64,
+    /// How much has been written so far.
+    offset: u64,
+    /// Whether the last chunk has arrived.
+    done: bool,
+    out: File,
+}
+
+/// The token of the cluster the node keeping its data at `file`
+/// bootstrapped or joined, if it knows it.
+pub fn cluster_token(file: &str) -> Result<Option<String>, Error> {
+    let path = sibling(Path::new(file), ".cluster");
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let token = read_file(&path)?;
+    Ok(Some(String::from_utf8_lossy(&token).into_owned()))
+}
+
+/// Builds a path next to `file` by appending `suffix` to its name.
+fn sibling(file: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(file.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Writes the contents behind a header so they can be verified on load.
+pub fn write_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(FILE_HEADER + contents.len());
+    buf.extend_from_slice(MAGIC);
+    buf.put_u32_be(FORMAT_VERSION);
+    buf.put_u32_be(crc::checksum(contents));
+    buf.put_u64_be(contents.len() as u64);
+    buf.extend_from_slice(contents);
+    write_atomic(path, &buf)
+}
+
+/// Reads a file written by `write_file`, returning its verified contents.
+pub fn read_file(path: &Path) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![];
+    File::open(path)?.read_to_end(&mut buf)?;
+
+    if buf.len() < MAGIC.len() || &buf[..MAGIC.len()] != MAGIC {
+        return Err(Error::BadMagic(path.to_owned()));
+    }
+    if buf.len() < FILE_HEADER {
+        return Err(Error::Truncated(path.to_owned()));
+    }
+
+    let mut header = (&buf[MAGIC.len()..FILE_HEADER]).into_buf();
+    let version = header.get_u32_be();
+    let sum = header.get_u32_be();
+    let len = header.get_u64_be() as usize;
+
+    if version != FORMAT_VERSION {
+        return Err(Error::UnsupportedVersion(path.to_owned(), version));
+    }
+    if buf.len() - FILE_HEADER != len {
+        return Err(Error::Truncated(path.to_owned()));
+    }
+
+    let contents = buf.split_off(FILE_HEADER);
+    if crc::checksum(&contents) != sum {
+        return Err(Error::Checksum(path.to_owned()));
+    }
+    Ok(contents)
+}
+
+/// Writes the file by way of a temporary file that's synced and renamed over
+/// the original, so a crash leaves either the old or the new contents.
+fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp = sibling(path, ".tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)?;
+
+    // The rename itself only survives a crash once the directory is synced.
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
 #[derive(Clone)]
 pub struct KeyValue {
     core: Arc<RwLock<KeyValueCore>>,
 }
 
 impl KeyValue {
-    pub fn new<P: Into<PathBuf>>(file: P) -> Self {
-        KeyValue {
-            core: Arc::new(RwLock::new(KeyValueCore::new(file.into()))),
-        }
+    pub fn new<P: Into<PathBuf>>(file: P) -> Result<Self, Error> {
+        let core = KeyValueCore::new(file.into())?;
+        Ok(KeyValue {
+            core: Arc::new(RwLock::new(core)),
+        })
     }
 
     pub fn wl(&self) -> RwLockWriteGuard<KeyValueCore> {
@@ -176,6 +705,135 @@ impl Storage for KeyValue {
     }
 
     fn snapshot(&self) -> raft::Result<Snapshot> {
-        self.rl().mem.snapshot()
+        self.wl().snapshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("kv-raft-storage-{}", name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let path = temp_file("round-trip");
+        write_file(&path, b"hello world").unwrap();
+        assert_eq!(read_file(&path).unwrap(), b"hello world".to_vec());
+    }
+
+    #[test]
+    fn test_corrupt_files_are_rejected() {
+        let path = temp_file("corrupt");
+        write_file(&path, b"hello world").unwrap();
+
+        let mut buf = vec![];
+        File::open(&path).unwrap().read_to_end(&mut buf).unwrap();
+
+        // Flip a bit in the contents
+        let mut flipped = buf.clone();
+        let last = flipped.len() - 1;
+        flipped[last] ^= 1;
+        write_atomic(&path, &flipped).unwrap();
+        match read_file(&path) {
+            Err(Error::Checksum(_)) => (),
+            other => panic!("expected a checksum error, got {:?}", other),
+        }
+
+        // Cut it short as a crash mid-write would have
+        write_atomic(&path, &buf[..buf.len() - 3]).unwrap();
+        match read_file(&path) {
+            Err(Error::Truncated(_)) => (),
+            other => panic!("expected a truncated error, got {:?}", other),
+        }
+
+        // Something that was never ours
+        write_atomic(&path, b"not a snapshot at all").unwrap();
+        match read_file(&path) {
+            Err(Error::BadMagic(_)) => (),
+            other => panic!("expected a bad magic error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bootstrap_only_once() {
+        let path = temp_file("bootstrap");
+        let _ = fs::remove_dir_all(sibling(&path, ".wal"));
+        let _ = fs::remove_file(sibling(&path, ".cluster"));
+
+        let mut core = KeyValueCore::new(path.clone()).unwrap();
+        assert!(core.bootstrap("alpha").unwrap());
+        // Nothing was written yet, so it may still bootstrap
+        assert!(core.bootstrap("alpha").unwrap());
+
+        let mut entry = Entry::new();
+        entry.set_index(1);
+        entry.set_term(1);
+        core.append(&[entry]).unwrap();
+        assert!(!core.bootstrap("alpha").unwrap());
+        match core.bootstrap("beta") {
+            Err(Error::Bootstrap(_)) => (),
+            other => panic!("expected a bootstrap error, got {:?}", other),
+        }
+
+        // Data without a token was never bootstrapped by this cluster
+        fs::remove_file(sibling(&path, ".cluster")).unwrap();
+        match core.bootstrap("alpha") {
+            Err(Error::Bootstrap(_)) => (),
+            other => panic!("expected a bootstrap error, got {:?}", other),
+        }
+
+        // Joining a cluster holds the node to it, and only the first one counts
+        assert!(core.join("alpha").unwrap());
+        assert!(!core.join("beta").unwrap());
+        let file = path.to_str().unwrap();
+        assert_eq!(cluster_token(file).unwrap(), Some(String::from("alpha")));
+        assert!(!core.bootstrap("alpha").unwrap());
+    }
+
+    fn chunk(offset: u64, data: &[u8], last: bool) -> proto::SnapshotChunk {
+        let mut chunk = proto::SnapshotChunk::new();
+        chunk.set_index(10);
+        chunk.set_term(2);
+        chunk.set_offset(offset);
+        chunk.set_data(data.to_vec());
+        chunk.set_last(last);
+        chunk
+    }
+
+    #[test]
+    fn test_snapshot_data_is_received_in_chunks() {
+        let path = temp_file("chunks");
+        let _ = fs::remove_dir_all(sibling(&path, ".wal"));
+        let mut core = KeyValueCore::new(path.clone()).unwrap();
+
+        // Duplicates are skipped
+        core.receive_chunk(&chunk(0, b"ab", false)).unwrap();
+        core.receive_chunk(&chunk(0, b"ab", false)).unwrap();
+        core.receive_chunk(&chunk(2, b"cd", true)).unwrap();
+        core.receive_chunk(&chunk(2, b"cd", true)).unwrap();
+        assert!(!core.take_received(10, 3).unwrap());
+
+        core.receive_chunk(&chunk(0, b"ab", false)).unwrap();
+        core.receive_chunk(&chunk(2, b"cd", true)).unwrap();
+        assert!(core.take_received(10, 2).unwrap());
+        let mut data = vec![];
+        File::open(sibling(&path, ".incoming-data"))
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(data, b"abcd".to_vec());
+
+        // A gap abandons the data, as does a missing last chunk
+        core.receive_chunk(&chunk(0, b"ab", false)).unwrap();
+        core.receive_chunk(&chunk(4, b"ef", true)).unwrap();
+        assert!(!core.take_received(10, 2).unwrap());
+        core.receive_chunk(&chunk(0, b"ab", false)).unwrap();
+        assert!(!core.take_received(10, 2).unwrap());
     }
 }
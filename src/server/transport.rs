@@ -0,0 +1,414 @@
+//! The transport carries messages between the nodes of a cluster. Over TCP
+//! that's the network, while `MemoryNetwork` links nodes running in the same
+//! process so they can be tested together, with faults injected between them.
+
+use super::proto;
+use futures::prelude::*;
+use futures::stream;
+use protobuf::Message;
+use raft;
+use std::fs::File;
+use std::io::{self, Read};
+use std::thread::JoinHandle;
+
+/// How much of a snapshot's data goes in each chunk.
+pub const SNAPSHOT_CHUNK: usize = 1024 * 1024;
+
+/// Sends messages from this node to its peers. Messages may be lost on the
+/// way, raft sends again whatever isn't acknowledged.
+pub trait Transport: Send + 'static {
+    fn send(&mut self, to: u64, msg: proto::PeerMessage);
+
+    /// A sink of messages to a single peer, for replies that are only ready
+    /// after the caller has moved on.
+    fn sink(&self, to: u64) -> Box<Sink<SinkItem = proto::PeerMessage, SinkError = ()> + Send>;
+
+    /// Starts routing messages to the peer, or updates where they go.
+    fn add_peer(&mut self, peer: &proto::Peer);
+
+    fn remove_peer(&mut self, id: u64);
+
+    /// Sends a snapshot to a follower, streaming the data behind it from
+    /// `data` in chunks ahead of the raft message.
+    fn send_snapshot(&mut self, to: u64, msg: &raft::eraftpb::Message, data: File) {
+        for chunk in SnapshotChunks::new(msg, data) {
+            match chunk {
+                Ok(chunk) => self.send(to, chunk),
+                Err(e) => {
+                    println!("Unable to read the snapshot for {}: {}", to, e);
+                    break;
+                }
+            }
+        }
+        self.send(to, raft_message(msg));
+    }
+
+    /// A peer connected and said where it listens. The address is only a
+    /// way to reach a sender the transport hasn't been told about.
+    fn discovered(&mut self, _id: u64, _addr: &str) {}
+
+    /// The peers the transport failed to reach, which raft must be told
+    /// about so it stops streaming entries to them.
+    fn unreachable(&mut self) -> Box<Stream<Item = u64, Error = ()> + Send> {
+        Box::new(stream::empty())
+    }
+
+    /// Takes the thread the transport runs on, if it has one. It finishes
+    /// once the transport has been dropped and what was sent has gone out.
+    fn take_thread(&mut self) -> Option<JoinHandle<()>> {
+        None
+    }
+}
+
+/// Wraps a raft message to be sent to a peer.
+pub fn raft_message(msg: &raft::eraftpb::Message) -> proto::PeerMessage {
+    let mut peer_msg = proto::PeerMessage::new();
+    peer_msg.set_raft(msg.write_to_bytes().expect("Raft message should serialize"));
+    peer_msg
+}
+
+/// Reads the data of a snapshot as the chunks that carry it to a follower.
+/// There's always at least one chunk, and the last is marked so the
+/// follower knows it has all of it.
+#[derive(Debug)]
+pub struct SnapshotChunks {
+    index: u64,
+    term: u64,
+    offset: u64,
+    data: File,
+    done: bool,
+}
+
+impl SnapshotChunks {
+    pub fn new(msg: &raft::eraftpb::Message, data: File) -> SnapshotChunks {
+        let metadata = msg.get_snapshot().get_metadata();
+        SnapshotChunks {
+            index: metadata.get_index(),
+            term: metadata.get_term(),
+            offset: 0,
+            data,
+            done: false,
+        }
+    }
+
+    /// Fills the buffer unless the data ends first, returning how much was
+    /// read.
+    fn fill(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        while read < buf.len() {
+            match self.data.read(&mut buf[read..])? {
+                0 => break,
+                n => read += n,
+            }
+        }
+        Ok(read)
+    }
+}
+
+impl Iterator for SnapshotChunks {
+    type Item = io::Result<proto::PeerMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut buf = vec![0; SNAPSHOT_CHUNK];
+        let read = match self.fill(&mut buf) {
+            Ok(read) => read,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+        buf.truncate(read);
+        // A full chunk may be followed by an empty last one
+        self.done = read < SNAPSHOT_CHUNK;
+
+        let mut msg = proto::PeerMessage::new();
+        {
+            let chunk = msg.mut_chunk();
+            chunk.set_index(self.index);
+            chunk.set_term(self.term);
+            chunk.set_offset(self.offset);
+            chunk.set_data(buf);
+            chunk.set_last(self.done);
+        }
+        self.offset += read as u64;
+        Some(Ok(msg))
+    }
+}
+
+#[cfg(test)]
+pub use self::memory::{Fault, MemoryNetwork, MemoryTransport};
+
+/// The in-memory transport, which only tests link nodes with.
+#[cfg(test)]
+mod memory {
+    use super::*;
+    use futures::sync::mpsc;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::{Arc, Mutex};
+
+    /// What happens to the messages sent from one node to another.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Fault {
+        /// Every message is lost.
+        Drop,
+        /// Messages are held back until the fault is cleared, then delivered in
+        /// the order they were sent.
+        Delay,
+        /// Every message is delivered twice.
+        Duplicate,
+        /// Each pair of messages is delivered the other way around.
+        Reorder,
+    }
+
+    #[derive(Default)]
+    struct Links {
+        inboxes: HashMap<u64, mpsc::UnboundedSender<proto::PeerMessage>>,
+        faults: HashMap<(u64, u64), Fault>,
+        held: HashMap<(u64, u64), Vec<proto::PeerMessage>>,
+    }
+
+    impl Links {
+        fn send(&mut self, from: u64, to: u64, msg: proto::PeerMessage) {
+            let link = (from, to);
+            match self.faults.get(&link) {
+                Some(&Fault::Drop) => (),
+                Some(&Fault::Delay) => self.held.entry(link).or_insert_with(Vec::new).push(msg),
+                Some(&Fault::Duplicate) => {
+                    self.deliver(to, msg.clone());
+                    self.deliver(to, msg);
+                }
+                Some(&Fault::Reorder) => match self.held.remove(&link) {
+                    Some(held) => {
+                        self.deliver(to, msg);
+                        for msg in held {
+                            self.deliver(to, msg);
+                        }
+                    }
+                    None => {
+                        self.held.insert(link, vec![msg]);
+                    }
+                },
+                None => self.deliver(to, msg),
+            }
+        }
+
+        fn deliver(&mut self, to: u64, msg: proto::PeerMessage) {
+            if let Some(inbox) = self.inboxes.get(&to) {
+                let _ = inbox.unbounded_send(msg);
+            }
+        }
+    }
+
+    /// Links nodes running in one process. Each node joins to get its transport
+    /// and the stream of messages sent to it. Messages arrive in the order they
+    /// were sent unless a fault says otherwise.
+    #[derive(Clone, Default)]
+    pub struct MemoryNetwork {
+        links: Arc<Mutex<Links>>,
+    }
+
+    impl MemoryNetwork {
+        pub fn new() -> MemoryNetwork {
+            MemoryNetwork::default()
+        }
+
+        /// Adds a node, returning its transport and the messages sent to it.
+        pub fn join(
+            &self,
+            id: u64,
+        ) -> (MemoryTransport, mpsc::UnboundedReceiver<proto::PeerMessage>) {
+            let (tx, rx) = mpsc::unbounded();
+            self.links.lock().unwrap().inboxes.insert(id, tx);
+
+            let transport = MemoryTransport {
+                id,
+                peers: HashSet::new(),
+                network: self.clone(),
+            };
+            (transport, rx)
+        }
+
+        /// Applies a fault to the messages sent from one node to another.
+        pub fn set_fault(&self, from: u64, to: u64, fault: Fault) {
+            let mut links = self.links.lock().unwrap();
+            links.faults.insert((from, to), fault);
+        }
+
+        /// Heals the link between two nodes, delivering anything held back.
+        pub fn clear_fault(&self, from: u64, to: u64) {
+            let mut links = self.links.lock().unwrap();
+            links.faults.remove(&(from, to));
+            for msg in links.held.remove(&(from, to)).unwrap_or_else(Vec::new) {
+                links.deliver(to, msg);
+            }
+        }
+
+        fn send(&self, from: u64, to: u64, msg: proto::PeerMessage) {
+            self.links.lock().unwrap().send(from, to, msg);
+        }
+    }
+
+    /// A node's end of a `MemoryNetwork`. Like the network, it only sends to
+    /// peers it has been told about.
+    pub struct MemoryTransport {
+        id: u64,
+        peers: HashSet<u64>,
+        network: MemoryNetwork,
+    }
+
+    impl Transport for MemoryTransport {
+        fn send(&mut self, to: u64, msg: proto::PeerMessage) {
+            if self.peers.contains(&to) {
+                self.network.send(self.id, to, msg);
+            }
+        }
+
+        fn sink(&self, to: u64) -> Box<Sink<SinkItem = proto::PeerMessage, SinkError = ()> + Send> {
+            Box::new(LinkSink {
+                from: self.id,
+                to,
+                network: self.network.clone(),
+            })
+        }
+
+        fn add_peer(&mut self, peer: &proto::Peer) {
+            if peer.get_id() != self.id {
+                self.peers.insert(peer.get_id());
+            }
+        }
+
+        fn remove_peer(&mut self, id: u64) {
+            self.peers.remove(&id);
+        }
+
+        fn discovered(&mut self, id: u64, _addr: &str) {
+            if id != self.id {
+                self.peers.insert(id);
+            }
+        }
+    }
+
+    struct LinkSink {
+        from: u64,
+        to: u64,
+        network: MemoryNetwork,
+    }
+
+    impl Sink for LinkSink {
+        type SinkItem = proto::PeerMessage;
+        type SinkError = ();
+
+        fn start_send(&mut self, msg: proto::PeerMessage) -> StartSend<proto::PeerMessage, ()> {
+            self.network.send(self.from, self.to, msg);
+            Ok(AsyncSink::Ready)
+        }
+
+        fn poll_complete(&mut self) -> Poll<(), ()> {
+            Ok(Async::Ready(()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future;
+    use futures::sync::mpsc;
+
+    fn message(n: u8) -> proto::PeerMessage {
+        let mut msg = proto::PeerMessage::new();
+        msg.set_raft(vec![n]);
+        msg
+    }
+
+    fn peer(id: u64) -> proto::Peer {
+        let mut peer = proto::Peer::new();
+        peer.set_id(id);
+        peer
+    }
+
+    /// Takes what's arrived in the inbox so far.
+    fn received(inbox: &mut mpsc::UnboundedReceiver<proto::PeerMessage>) -> Vec<u8> {
+        future::lazy(|| {
+            let mut received = vec![];
+            while let Ok(Async::Ready(Some(msg))) = inbox.poll() {
+                received.push(msg.get_raft()[0]);
+            }
+            Ok::<_, ()>(received)
+        }).wait()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_faults_between_chosen_nodes() {
+        let network = MemoryNetwork::new();
+        let (mut one, _) = network.join(1);
+        let (_, mut two) = network.join(2);
+
+        // Nothing goes to a node that isn't a peer
+        one.send(2, message(0));
+        assert!(received(&mut two).is_empty());
+
+        one.add_peer(&peer(2));
+        one.send(2, message(1));
+        assert_eq!(received(&mut two), vec![1]);
+
+        network.set_fault(1, 2, Fault::Drop);
+        one.send(2, message(2));
+        assert!(received(&mut two).is_empty());
+
+        network.set_fault(1, 2, Fault::Duplicate);
+        one.send(2, message(3));
+        assert_eq!(received(&mut two), vec![3, 3]);
+
+        network.set_fault(1, 2, Fault::Reorder);
+        for n in 4..8 {
+            one.send(2, message(n));
+        }
+        assert_eq!(received(&mut two), vec![5, 4, 7, 6]);
+
+        network.set_fault(1, 2, Fault::Delay);
+        one.send(2, message(8));
+        one.send(2, message(9));
+        assert!(received(&mut two).is_empty());
+        network.clear_fault(1, 2);
+        assert_eq!(received(&mut two), vec![8, 9]);
+
+        one.remove_peer(2);
+        one.send(2, message(10));
+        assert!(received(&mut two).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_data_is_split_into_chunks() {
+        let path = ::std::env::temp_dir().join("kv-raft-transport-chunks");
+        let mut msg = raft::eraftpb::Message::new();
+        msg.mut_snapshot().mut_metadata().set_index(7);
+
+        let sizes = |len: usize| {
+            ::std::fs::write(&path, vec![1; len]).unwrap();
+            SnapshotChunks::new(&msg, File::open(&path).unwrap())
+                .map(|chunk| {
+                    let chunk = chunk.unwrap().take_chunk();
+                    assert_eq!(chunk.get_index(), 7);
+                    (chunk.get_offset(), chunk.get_data().len(), chunk.get_last())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(sizes(0), vec![(0, 0, true)]);
+        assert_eq!(
+            sizes(SNAPSHOT_CHUNK + 3),
+            vec![(0, SNAPSHOT_CHUNK, false), (SNAPSHOT_CHUNK as u64, 3, true)]
+        );
+        // A last chunk that's empty ends data that fills the one before
+        assert_eq!(
+            sizes(SNAPSHOT_CHUNK),
+            vec![(0, SNAPSHOT_CHUNK, false), (SNAPSHOT_CHUNK as u64, 0, true)]
+        );
+    }
+}
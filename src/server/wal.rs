@@ -0,0 +1,452 @@
+//! The write-ahead log keeps every raft entry this node has accepted on
+//! disk, so a crash between snapshots doesn't lose anything that was
+//! already acknowledged.
+//!
+//! The log is split into segments, each named after the index of the first
+//! entry it holds. A record is a 4 byte length, a 4 byte crc of the payload
+//! and then the protobuf encoded entry. Segments are only ever appended to,
+//! except when raft overwrites a conflicting suffix of the log.
+
+use super::crc;
+use bytes::{Buf, BufMut, IntoBuf};
+use protobuf::{parse_from_bytes, Message};
+use raft::eraftpb::Entry;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Once a segment grows past this size, new entries go to a fresh segment.
+const SEGMENT_BYTES: u64 = 64 * 1024 * 1024;
+const HEADER: usize = 8;
+const EXTENSION: &str = "log";
+
+struct Segment {
+    first_index: u64,
+    path: PathBuf,
+    /// Byte offset of each record, `offsets[i]` holds entry `first_index + i`.
+    offsets: Vec<u64>,
+    len: u64,
+}
+
+impl Segment {
+    fn next_index(&self) -> u64 {
+        self.first_index + self.offsets.len() as u64
+    }
+}
+
+pub struct Wal {
+    dir: PathBuf,
+    segments: Vec<Segment>,
+    active: Option<File>,
+    segment_bytes: u64,
+}
+
+impl Wal {
+    /// Opens the log in `dir`, creating it if needed, and returns every
+    /// entry it holds in order. A partial record at the very end of the log
+    /// is cut off since it could never have been acknowledged, while any
+    /// other record that doesn't decode is an error.
+    pub fn open<P: Into<PathBuf>>(dir: P) -> io::Result<(Wal, Vec<Entry>)> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let mut firsts = vec![];
+        for dirent in fs::read_dir(&dir)? {
+            if let Some(first_index) = segment_index(&dirent?.path()) {
+                firsts.push(first_index);
+            }
+        }
+        firsts.sort();
+
+        let mut entries = vec![];
+        let mut segments: Vec<Segment> = Vec::with_capacity(firsts.len());
+        let count = firsts.len();
+        for (n, first_index) in firsts.into_iter().enumerate() {
+            if let Some(prev) = segments.last() {
+                if prev.next_index() != first_index {
+                    return Err(corrupt(format!(
+                        "segment {} does not follow segment {}",
+                        first_index, prev.first_index
+                    )));
+                }
+            }
+
+            let path = segment_path(&dir, first_index);
+            let segment = read_segment(path, first_index, n + 1 == count, &mut entries)?;
+            segments.push(segment);
+        }
+
+        let active = match segments.last() {
+            Some(segment) => Some(open_append(&segment.path)?),
+            None => None,
+        };
+
+        let wal = Wal {
+            dir,
+            segments,
+            active,
+            segment_bytes: SEGMENT_BYTES,
+        };
+        Ok((wal, entries))
+    }
+
+    /// Appends the entries and syncs them to disk. Any entries at or after
+    /// the index of the first new entry are overwritten, the same way
+    /// `MemStorage` handles a conflicting suffix.
+    pub fn append(&mut self, ents: &[Entry]) -> io::Result<()> {
+        if ents.is_empty() {
+            return Ok(());
+        }
+
+        let first = ents[0].get_index();
+        self.truncate_from(first)?;
+
+        if let Some(next) = self.next_index() {
+            if next != first {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("missing log entry [next: {}, append at: {}]", next, first),
+                ));
+            }
+        }
+
+        let mut buf = Vec::new();
+        for entry in ents {
+            if self.needs_roll() {
+                self.roll(entry.get_index())?;
+            }
+
+            let payload = entry
+                .write_to_bytes()
+                .map_err(|e| corrupt(format!("{:?}", e)))?;
+
+            buf.clear();
+            buf.put_u32_be(payload.len() as u32);
+            buf.put_u32_be(crc::checksum(&payload));
+            buf.extend_from_slice(&payload);
+
+            let segment = self.segments.last_mut().expect("Active segment");
+            let file = self.active.as_mut().expect("Active segment file");
+
+            file.write_all(&buf)?;
+            segment.offsets.push(segment.len);
+            segment.len += buf.len() as u64;
+        }
+
+        match self.active {
+            Some(ref file) => file.sync_data(),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes segments that only hold entries before `index`. The segment
+    /// holding `index` is kept, so a segment may outlive a few of its entries.
+    pub fn compact(&mut self, index: u64) -> io::Result<()> {
+        while self.segments.len() > 1 && self.segments[1].first_index <= index {
+            let segment = self.segments.remove(0);
+            fs::remove_file(&segment.path)?;
+        }
+        Ok(())
+    }
+
+    /// Drops the entire log. Used when a snapshot replaces the log.
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.active = None;
+        for segment in self.segments.drain(..) {
+            fs::remove_file(&segment.path)?;
+        }
+        sync_dir(&self.dir)
+    }
+
+    fn next_index(&self) -> Option<u64> {
+        self.segments.last().map(Segment::next_index)
+    }
+
+    fn needs_roll(&self) -> bool {
+        self.segments
+            .last()
+            .map_or(true, |segment| segment.len >= self.segment_bytes)
+    }
+
+    fn roll(&mut self, first_index: u64) -> io::Result<()> {
+        if let Some(file) = self.active.take() {
+            file.sync_all()?;
+        }
+
+        let path = segment_path(&self.dir, first_index);
+        let file = open_append(&path)?;
+        sync_dir(&self.dir)?;
+
+        self.segments.push(Segment {
+            first_index,
+            path,
+            offsets: vec![],
+            len: 0,
+        });
+        self.active = Some(file);
+        Ok(())
+    }
+
+    /// Removes every entry at or after `index`.
+    fn truncate_from(&mut self, index: u64) -> io::Result<()> {
+        loop {
+            let (first, next) = match self.segments.last() {
+                Some(segment) => (segment.first_index, segment.next_index()),
+                None => return Ok(()),
+            };
+
+            if index >= next {
+                if self.active.is_none() {
+                    let file = open_append(&self.segments[self.segments.len() - 1].path)?;
+                    self.active = Some(file);
+                }
+                return Ok(());
+            }
+
+            if index <= first {
+                self.active = None;
+                let segment = self.segments.pop().expect("Segment to remove");
+                fs::remove_file(&segment.path)?;
+                continue;
+            }
+
+            let segment = self.segments.last_mut().expect("Segment to truncate");
+            let keep = (index - first) as usize;
+            segment.len = segment.offsets[keep];
+            segment.offsets.truncate(keep);
+
+            let file = open_append(&segment.path)?;
+            file.set_len(segment.len)?;
+            file.sync_all()?;
+            self.active = Some(file);
+            return Ok(());
+        }
+    }
+}
+
+fn read_segment(
+    path: PathBuf,
+    first_index: u64,
+    is_last: bool,
+    entries: &mut Vec<Entry>,
+) -> io::Result<Segment> {
+    let mut buf = vec![];
+    File::open(&path)?.read_to_end(&mut buf)?;
+
+    let mut offsets = vec![];
+    let mut pos = 0;
+    while pos < buf.len() {
+        let (entry, len) = match decode_record(&buf[pos..]) {
+            Ok(Some(record)) => record,
+            Ok(None) => break,
+            Err(e) => {
+                return Err(corrupt(format!(
+                    "segment {} is corrupt at byte {}: {}",
+                    first_index, pos, e
+                )))
+            }
+        };
+        let expected = first_index + offsets.len() as u64;
+        if entry.get_index() != expected {
+            return Err(corrupt(format!(
+                "expected entry {} in segment {} but found {}",
+                expected,
+                first_index,
+                entry.get_index()
+            )));
+        }
+        offsets.push(pos as u64);
+        entries.push(entry);
+        pos += len;
+    }
+
+    if pos < buf.len() {
+        if !is_last {
+            return Err(corrupt(format!(
+                "segment {} is corrupt at byte {}",
+                first_index, pos
+            )));
+        }
+
+        // A torn write at the tail of the log, it was never synced so it
+        // was never acknowledged either.
+        println!("Truncating torn write in segment {}", first_index);
+        let file = OpenOptions::new().write(true).open(&path)?;
+        file.set_len(pos as u64)?;
+        file.sync_all()?;
+    }
+
+    Ok(Segment {
+        first_index,
+        path,
+        offsets,
+        len: pos as u64,
+    })
+}
+
+/// Decodes the record at the start of `buf`, or returns `None` if it runs
+/// past the end, which is all a torn write can leave behind. A record that
+/// is all there but doesn't decode is an error.
+fn decode_record(buf: &[u8]) -> Result<Option<(Entry, usize)>, &'static str> {
+    if buf.len() < HEADER {
+        return Ok(None);
+    }
+
+    let mut header = (&buf[..HEADER]).into_buf();
+    let len = header.get_u32_be() as usize;
+    let sum = header.get_u32_be();
+
+    if buf.len() < HEADER + len {
+        return Ok(None);
+    }
+
+    let payload = &buf[HEADER..HEADER + len];
+    if crc::checksum(payload) != sum {
+        return Err("checksum mismatch");
+    }
+
+    parse_from_bytes::<Entry>(payload)
+        .map(|entry| Some((entry, HEADER + len)))
+        .map_err(|_| "undecodable entry")
+}
+
+fn segment_path(dir: &Path, first_index: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", first_index, EXTENSION))
+}
+
+fn segment_index(path: &Path) -> Option<u64> {
+    if path.extension().and_then(|e| e.to_str()) != Some(EXTENSION) {
+        return None;
+    }
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.parse().ok())
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+fn corrupt(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("kv-raft-wal-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn entry(index: u64, term: u64) -> Entry {
+        let mut entry = Entry::new();
+        entry.set_index(index);
+        entry.set_term(term);
+        entry.set_data(format!("entry-{}-{}", index, term).into_bytes());
+        entry
+    }
+
+    #[test]
+    fn test_append_and_replay() {
+        let dir = temp_dir("replay");
+
+        let (mut wal, entries) = Wal::open(&dir).unwrap();
+        assert!(entries.is_empty());
+        wal.append(&[entry(1, 1), entry(2, 1), entry(3, 1)])
+            .unwrap();
+        drop(wal);
+
+        let (_, entries) = Wal::open(&dir).unwrap();
+        assert_eq!(entries, vec![entry(1, 1), entry(2, 1), entry(3, 1)]);
+    }
+
+    #[test]
+    fn test_conflicting_suffix_is_overwritten() {
+        let dir = temp_dir("conflict");
+
+        let (mut wal, _) = Wal::open(&dir).unwrap();
+        wal.append(&[entry(1, 1), entry(2, 1), entry(3, 1)])
+            .unwrap();
+        wal.append(&[entry(2, 2)]).unwrap();
+        drop(wal);
+
+        let (_, entries) = Wal::open(&dir).unwrap();
+        assert_eq!(entries, vec![entry(1, 1), entry(2, 2)]);
+    }
+
+    #[test]
+    fn test_segments_roll_and_compact() {
+        let dir = temp_dir("segments");
+
+        let (mut wal, _) = Wal::open(&dir).unwrap();
+        wal.segment_bytes = 1;
+        let ents: Vec<Entry> = (1..6).map(|i| entry(i, 1)).collect();
+        wal.append(&ents).unwrap();
+        assert_eq!(wal.segments.len(), 5);
+
+        // Overwriting across a segment boundary removes later segments
+        wal.append(&[entry(3, 2), entry(4, 2)]).unwrap();
+        assert_eq!(wal.segments.len(), 4);
+
+        wal.compact(3).unwrap();
+        assert_eq!(wal.segments[0].first_index, 3);
+        drop(wal);
+
+        let (_, entries) = Wal::open(&dir).unwrap();
+        assert_eq!(entries, vec![entry(3, 2), entry(4, 2)]);
+    }
+
+    #[test]
+    fn test_torn_tail_is_truncated() {
+        let dir = temp_dir("torn");
+
+        let (mut wal, _) = Wal::open(&dir).unwrap();
+        wal.append(&[entry(1, 1), entry(2, 1)]).unwrap();
+        drop(wal);
+
+        {
+            let mut file = open_append(&segment_path(&dir, 1)).unwrap();
+            file.write_all(&[0, 0, 0, 42, 1, 2]).unwrap();
+        }
+
+        let (mut wal, entries) = Wal::open(&dir).unwrap();
+        assert_eq!(entries, vec![entry(1, 1), entry(2, 1)]);
+        wal.append(&[entry(3, 1)]).unwrap();
+        drop(wal);
+
+        let (_, entries) = Wal::open(&dir).unwrap();
+        assert_eq!(entries.len(), 3);
+    }
+
+    #[test]
+    fn test_corrupt_record_before_valid_ones_is_an_error() {
+        let dir = temp_dir("corrupt");
+
+        let (mut wal, _) = Wal::open(&dir).unwrap();
+        wal.append(&[entry(1, 1), entry(2, 1), entry(3, 1)])
+            .unwrap();
+        let second = wal.segments[0].offsets[1];
+        drop(wal);
+
+        // Flip a byte in the payload of the second record
+        let path = segment_path(&dir, 1);
+        let mut buf = fs::read(&path).unwrap();
+        buf[second as usize + HEADER] ^= 0xff;
+        fs::write(&path, &buf).unwrap();
+
+        match Wal::open(&dir) {
+            Err(e) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+            Ok(_) => panic!("a corrupt record should fail the open"),
+        }
+        // Nothing was truncated
+        assert_eq!(fs::read(&path).unwrap().len(), buf.len());
+    }
+}